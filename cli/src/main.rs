@@ -68,6 +68,19 @@ fn main() {
                     arg!(-l --list "lists all errors").action(ArgAction::SetTrue)
                 )
             )
+            .subcommand(
+                Command::new("diff")
+                .about("list the changes of the specs of a catalog between two git references, fails on breaking changes")
+                .arg(
+                    arg!(-c --catalog <ID> "the id of the catalog").required(true)
+                )
+                .arg(
+                    arg!(-f --from <REF> "the reference (branch, tag or commit) to compare from").required(true)
+                )
+                .arg(
+                    arg!(-t --to <REF> "the reference (branch, tag or commit) to compare to").required(true)
+                )
+            )
         )
         .get_matches();
 
@@ -100,6 +113,19 @@ fn main() {
                 let _ = get_all_specs_in_errors();
             } 
         }
+        if let Some(matches) = matches.subcommand_matches("diff") {
+            let catalog = matches.get_one::<String>("catalog").unwrap();
+            let from = matches.get_one::<String>("from").unwrap();
+            let to = matches.get_one::<String>("to").unwrap();
+            match diff_catalog(catalog, from, to) {
+                Ok(breaking) if breaking > 0 => std::process::exit(1),
+                Ok(_) => {},
+                Err(why) => {
+                    error!("Unable to diff catalog [{}] - {}", catalog, why);
+                    std::process::exit(2);
+                }
+            };
+        }
     }
 }
 
@@ -146,15 +172,47 @@ fn refresh_all_catalogs() -> Result<(), reqwest::Error> {
     );
 
     let resp = client.post(&url).send()?;
-    println!("Refreshed Catalogs with status {:?}", &resp.status());
+    debug!("body: {:?}", &resp.status());
+    let mut job: RefreshJob = resp.json()?;
+    println!("Refreshing Catalogs in job [{}]", &job.id);
+
+    //the refresh runs in the background
+    let url = format!(
+        "http://{address}/v1/jobs/{id}",
+        address = &SETTINGS.server.address,
+        id = &job.id
+    );
+    while job.state == "pending" || job.state == "running" {
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        job = client.get(&url).send()?.json()?;
+        debug!("job [{}] is [{}] - step [{:?}]", &job.id, &job.state, &job.step);
+    }
+
+    println!("Refreshed Catalogs with status [{}] - [{}] specs, [{}] errors in [{}] ms", &job.state, job.specs, job.errors, job.duration_ms.unwrap_or(0));
+    if let Some(error) = job.error {
+        println!("Error: {}", error);
+    }
     Ok(())
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RefreshJob {
+    pub id: String,
+    pub state: String,
+    pub step: Option<String>,
+    pub specs: usize,
+    pub errors: usize,
+    pub duration_ms: Option<u128>,
+    pub error: Option<String>,
+}
+
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SpecError {
     pub spec_path: String,
     pub error: String,
+    #[serde(default)]
+    pub category: String,
 }
 
 fn get_all_specs_in_errors()-> Result<(), reqwest::Error> {
@@ -170,9 +228,9 @@ fn get_all_specs_in_errors()-> Result<(), reqwest::Error> {
     //
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
-    table.set_titles(row![b -> "path", b -> "Reason"]);
+    table.set_titles(row![b -> "path", b -> "Category", b -> "Reason"]);
     for val in errors {
-        table.add_row(row![val.spec_path, val.error]);
+        table.add_row(row![val.spec_path, val.category, val.error]);
     }
 
     // Print the table to stdout
@@ -181,6 +239,59 @@ fn get_all_specs_in_errors()-> Result<(), reqwest::Error> {
     Ok(())
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpecChange {
+    pub level: String,
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpecDiff {
+    pub spec_path: String,
+    pub changes: Vec<SpecChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogDiff {
+    pub catalog_id: String,
+    pub from: String,
+    pub to: String,
+    pub breaking: usize,
+    pub non_breaking: usize,
+    pub annotations: usize,
+    pub specs: Vec<SpecDiff>,
+}
+
+/// prints the changes and returns the number of breaking ones
+fn diff_catalog(catalog: &str, from: &str, to: &str) -> Result<usize, reqwest::Error> {
+    let client = Client::new();
+    let url = format!(
+        "http://{address}/v1/catalogs/{catalog}/diff",
+        address = &SETTINGS.server.address,
+        catalog = catalog
+    );
+
+    let resp = client.get(&url).query(&[("from", from), ("to", to)]).send()?.error_for_status()?;
+    debug!("body: {:?}", &resp.status());
+    let diff: CatalogDiff = resp.json()?;
+    //
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    table.set_titles(row![b -> "Spec", b -> "Level", b -> "Location", b -> "Change"]);
+    for spec in &diff.specs {
+        for change in &spec.changes {
+            table.add_row(row![spec.spec_path, change.level, change.location, change.message]);
+        }
+    }
+
+    // Print the table to stdout
+    table.printstd();
+    println!("[{}] breaking, [{}] non-breaking, [{}] annotation changes between [{}] and [{}]", diff.breaking, diff.non_breaking, diff.annotations, &diff.from, &diff.to);
+
+    Ok(diff.breaking)
+}
+
 
 //
 #[derive(Serialize, Deserialize, Debug)]
@@ -0,0 +1,18 @@
+-- catalogs registered through the API, the ones of the config file are not stored
+-- credentials are not stored either, only the environment variable holding them
+
+CREATE TABLE IF NOT EXISTS catalogs (
+    catalog_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    dir TEXT NOT NULL,
+    include_glob_pattern TEXT NOT NULL,
+    http_base_uri TEXT NOT NULL,
+    scm_url TEXT NOT NULL,
+    scm_branch TEXT NOT NULL,
+    scm_depth INTEGER NOT NULL,
+    scm_username TEXT NOT NULL,
+    scm_password_env TEXT NOT NULL,
+    watch INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
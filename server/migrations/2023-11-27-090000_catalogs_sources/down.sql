@@ -0,0 +1,3 @@
+-- DROP COLUMN not support by sqlite -> no rollback
+-- ALTER TABLE catalogs DROP COLUMN source_type;
+-- ALTER TABLE catalogs DROP COLUMN source_url;
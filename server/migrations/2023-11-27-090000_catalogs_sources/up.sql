@@ -0,0 +1,4 @@
+-- catalogs fed by an archive or an HTTP index rather than a git repository
+
+ALTER TABLE catalogs ADD COLUMN source_type TEXT NOT NULL DEFAULT '';
+ALTER TABLE catalogs ADD COLUMN source_url TEXT NOT NULL DEFAULT '';
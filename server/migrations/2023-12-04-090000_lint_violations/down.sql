@@ -0,0 +1 @@
+drop table if exists metrics_lint_violations;
\ No newline at end of file
@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS metrics_lint_violations (
+    date_time TEXT NOT NULL UNIQUE, 
+    data_points TEXT NOT NULL
+);
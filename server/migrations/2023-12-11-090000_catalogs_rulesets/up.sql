@@ -0,0 +1,3 @@
+-- governance ruleset kept in the repository of the catalog
+
+ALTER TABLE catalogs ADD COLUMN ruleset_path TEXT NOT NULL DEFAULT '';
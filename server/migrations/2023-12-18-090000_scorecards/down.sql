@@ -0,0 +1 @@
+drop table if exists metrics_scorecards;
\ No newline at end of file
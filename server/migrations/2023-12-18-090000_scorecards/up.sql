@@ -0,0 +1,7 @@
+-- one snapshot per day and dimension (all, spec, domain, system or layer), the last one of the day wins
+CREATE TABLE IF NOT EXISTS metrics_scorecards (
+    date_time TEXT NOT NULL, 
+    dimension TEXT NOT NULL,
+    data_points TEXT NOT NULL,
+    UNIQUE(date_time, dimension)
+);
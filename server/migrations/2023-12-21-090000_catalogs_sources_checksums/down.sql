@@ -0,0 +1 @@
+drop table if exists catalogs_sources;
@@ -0,0 +1,8 @@
+-- last fetch of the archive and HTTP index catalogs, whether of the config file or registered through the API
+
+CREATE TABLE IF NOT EXISTS catalogs_sources (
+    catalog_id TEXT NOT NULL UNIQUE,
+    checksum TEXT NOT NULL,
+    files INTEGER NOT NULL,
+    fetched_at TEXT NOT NULL
+);
@@ -11,9 +11,10 @@ use crate::app::dao::repo_apis::*;
 use crate::app::dao::repo_domains::*;
 use crate::app::dao::catalog::*;
 use crate::app::dao::catalog::spec::*;
+use crate::app::dao::catalog::review::{SpecDelta, DeltaKind};
 use crate::shared::settings::*;
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use uuid::Uuid;
 
@@ -36,9 +37,14 @@ pub struct Spec {
 #[get("/v1/specs")]
 pub async fn get_all_specs() -> impl Responder {
     debug!("get_all_specs()");
+
+    let all_specs = list_specs(&crate::app::catalogs::get_all_catalogs());
+    HttpResponse::Ok().json(to_specs(all_specs))
+}
+
+pub fn to_specs(mut all_specs: Vec<SpecItem>) -> Specs {
     let mut specs = Specs { specs: Vec::new() };
 
-    let mut all_specs = list_specs(&SETTINGS.catalogs);
     while let Some(spec) = all_specs.pop() {
         info!("Analysing file [{:?}]", spec.get_file_path());
 
@@ -54,20 +60,27 @@ pub async fn get_all_specs() -> impl Responder {
         };
         specs.specs.push(spec);
     }
-    HttpResponse::Ok().json(specs)
+
+    specs
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SpecError {
     pub spec_path: String,
     pub error: String,
+    /// `parse`, `ruleset` or `conflict`
+    pub category: String,
 }
 
 #[get("/v1/specs/errors")]
 pub async fn get_all_errors() -> impl Responder{
     info!("get all errors");
 
-    let errors = crate::app::dao::catalog::list_all_errors();
+    let catalogs = crate::app::catalogs::get_all_catalogs();
+    let mut errors = crate::app::dao::catalog::list_errors(&catalogs);
+    //the specs conflicting with each other, across the catalogs
+    let conflicts = crate::app::dao::catalog::conflicts::list_conflicts(&list_specs(&catalogs));
+    errors.extend(crate::app::dao::catalog::conflicts::to_errors(&conflicts));
 
     let mut all_errors = Vec::new();
 
@@ -75,12 +88,395 @@ pub async fn get_all_errors() -> impl Responder{
         all_errors.push( SpecError{
             spec_path: error.file_path.to_string(),
             error: error.reason.to_string(),
+            category: error.category.to_string(),
         } );
     }
 
     HttpResponse::Ok().json(&all_errors)
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LintViolation {
+    pub rule_id: String,
+    pub severity: String,
+    pub pointer: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpecLint {
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub violations: Vec<LintViolation>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LintRule {
+    pub id: String,
+    pub severity: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Lints {
+    /// the enabled rules
+    pub rules: Vec<LintRule>,
+    pub violations_per_rule: std::collections::HashMap<String, usize>,
+    pub specs: Vec<SpecLint>,
+}
+
+/// the specs (as named by `/v1/specs`) which violate the ruleset, refer to the `[lint]` settings
+#[get("/v1/specs/lint")]
+pub async fn get_all_lints() -> impl Responder{
+    info!("get all lints");
+
+    let all_specs = list_specs(&crate::app::catalogs::get_all_catalogs());
+    let rules = crate::app::dao::catalog::lint::RULES.iter()
+        .filter_map(|rule| Some(LintRule {
+            id: String::from(rule.id),
+            severity: crate::app::dao::catalog::lint::get_severity(rule, &SETTINGS.lint)?.to_string(),
+            description: String::from(rule.description),
+        }))
+        .collect();
+    let mut lints = Lints { rules, violations_per_rule: std::collections::HashMap::new(), specs: Vec::new() };
+    for spec in &all_specs {
+        let violations = crate::app::dao::catalog::lint::lint(spec, &SETTINGS.lint);
+        if violations.is_empty() {
+            continue;
+        }
+
+        for violation in &violations {
+            *lints.violations_per_rule.entry(String::from(&violation.rule_id)).or_insert(0) += 1;
+        }
+        lints.specs.push(SpecLint {
+            spec_path: String::from(spec.get_spec_short_path()),
+            catalog_id: String::from(spec.get_catalog_id()),
+            violations: violations.into_iter().map(|violation| LintViolation {
+                rule_id: violation.rule_id,
+                severity: violation.severity.to_string(),
+                pointer: violation.pointer,
+                message: violation.message,
+            }).collect(),
+        });
+    }
+    lints.specs.sort_by(|a, b| a.spec_path.cmp(&b.spec_path));
+
+    HttpResponse::Ok().json(&lints)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConflictingSpec {
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpecConflict {
+    /// `path-collision`, `duplicate-api-id` or `diverging-versions`
+    pub kind: String,
+    pub key: String,
+    pub message: String,
+    pub specs: Vec<ConflictingSpec>,
+}
+
+/// the specs, possibly of different catalogs, which declare the same operations, reuse an `x-api-id` or diverge on the version of an API
+#[get("/v1/specs/conflicts")]
+pub async fn get_all_conflicts() -> impl Responder{
+    info!("get all conflicts");
+
+    let all_specs = list_specs(&crate::app::catalogs::get_all_catalogs());
+    let conflicts: Vec<SpecConflict> = crate::app::dao::catalog::conflicts::list_conflicts(&all_specs).into_iter()
+        .map(|conflict| SpecConflict {
+            kind: conflict.kind.to_string(),
+            message: conflict.get_message(),
+            key: conflict.key,
+            specs: conflict.specs.into_iter().map(|spec| ConflictingSpec {
+                spec_path: spec.spec_path,
+                catalog_id: spec.catalog_id,
+                detail: spec.detail,
+            }).collect(),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(&conflicts)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpecIgnore {
+    pub rule: String,
+    /// `global`, `path` or `operation`
+    pub scope: String,
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub domain: String,
+    pub path: Option<String>,
+    pub operation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpecIgnores {
+    /// the specs suppressing each rule
+    pub specs_per_rule: std::collections::BTreeMap<String, Vec<String>>,
+    pub ignores: Vec<SpecIgnore>,
+}
+
+#[derive(Deserialize)]
+pub struct SpecIgnoresQuery {
+    rule: Option<String>,
+}
+
+/// the rules suppressed by `x-zally-ignore`, and where
+#[get("/v1/specs/ignores")]
+pub async fn get_all_ignores(query: web::Query<SpecIgnoresQuery>) -> impl Responder{
+    info!("get all ignores");
+
+    let all_specs = list_specs(&crate::app::catalogs::get_all_catalogs());
+    let mut ignores = SpecIgnores { specs_per_rule: std::collections::BTreeMap::new(), ignores: Vec::new() };
+    for ignore in crate::app::dao::catalog::list_zally_ignores(&all_specs) {
+        if query.rule.as_ref().is_some_and(|rule| *rule != ignore.rule) {
+            continue;
+        }
+
+        let specs = ignores.specs_per_rule.entry(String::from(&ignore.rule)).or_default();
+        if !specs.contains(&ignore.spec_path) {
+            specs.push(String::from(&ignore.spec_path));
+        }
+        ignores.ignores.push(SpecIgnore {
+            rule: ignore.rule,
+            scope: ignore.scope.to_string(),
+            spec_path: ignore.spec_path,
+            catalog_id: ignore.catalog_id,
+            domain: ignore.domain,
+            path: ignore.path,
+            operation: ignore.operation,
+        });
+    }
+    for specs in ignores.specs_per_rule.values_mut() {
+        specs.sort();
+    }
+    ignores.ignores.sort_by(|a, b| a.spec_path.cmp(&b.spec_path));
+
+    HttpResponse::Ok().json(&ignores)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeclaredSecurityScheme {
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub name: String,
+    pub scheme_type: String,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OperationSecurityRow {
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub audience: String,
+    /// e.g. `GET /pets`
+    pub operation: String,
+    /// per column the operation requires (in any of its alternatives), the scopes of the scheme (none for the authorities)
+    pub cells: std::collections::BTreeMap<String, Vec<String>>,
+    /// the alternatives of the schemes required all together, refer to `OperationSecurity`
+    pub requirements: Vec<Vec<(String, Vec<String>)>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SecurityMatrix {
+    pub schemes: Vec<DeclaredSecurityScheme>,
+    /// `scheme:<name>` for the security schemes, `authority:<name>` for the `x-has-authority`
+    pub columns: Vec<String>,
+    pub operations: Vec<OperationSecurityRow>,
+}
+
+#[derive(Deserialize)]
+pub struct SecurityQuery {
+    /// the `x-audience` of the operations (or of their spec)
+    audience: Option<String>,
+}
+
+/// spec path, catalog id, audience and security of an operation
+type OperationSecurityEntry = (String, String, String, crate::app::dao::catalog::handlers::OperationSecurity);
+
+/// the operations of the specs, in the given audience if any, and the security schemes of their specs
+fn list_operations_security(audience: &Option<String>) -> (Vec<DeclaredSecurityScheme>, Vec<OperationSecurityEntry>) {
+    let all_specs = list_specs(&crate::app::catalogs::get_all_catalogs());
+
+    let mut schemes = Vec::new();
+    let mut operations = Vec::new();
+    for spec in all_specs {
+        let spec_operations: Vec<OperationSecurityEntry> = spec.get_operations_security().into_iter()
+            .map(|operation| {
+                let operation_audience = operation.audience.clone().unwrap_or_else(|| spec.get_audience());
+                (String::from(spec.get_spec_short_path()), String::from(spec.get_catalog_id()), operation_audience, operation)
+            })
+            .filter(|(_spec_path, _catalog_id, operation_audience, _operation)| audience.as_ref().map_or(true, |audience| audience == operation_audience))
+            .collect();
+        if spec_operations.is_empty() {
+            continue;
+        }
+
+        schemes.extend(spec.get_security_schemes().into_iter().map(|scheme| DeclaredSecurityScheme {
+            spec_path: String::from(spec.get_spec_short_path()),
+            catalog_id: String::from(spec.get_catalog_id()),
+            name: scheme.name,
+            scheme_type: scheme.scheme_type,
+            detail: scheme.detail,
+        }));
+        operations.extend(spec_operations);
+    }
+
+    (schemes, operations)
+}
+
+/// the operations × the security schemes and authorities they require
+#[get("/v1/specs/security")]
+pub async fn get_security_matrix(query: web::Query<SecurityQuery>) -> impl Responder{
+    info!("get security matrix");
+
+    let (schemes, operations) = list_operations_security(&query.audience);
+
+    let mut columns = std::collections::BTreeSet::new();
+    let mut rows = Vec::new();
+    for (spec_path, catalog_id, audience, operation) in operations {
+        let mut cells: std::collections::BTreeMap<String, Vec<String>> = std::collections::BTreeMap::new();
+        for (scheme, scopes) in operation.requirements.iter().flatten() {
+            let cell = cells.entry(format!("scheme:{}", scheme)).or_default();
+            cell.extend(scopes.iter().cloned());
+            cell.sort();
+            cell.dedup();
+        }
+        for authority in operation.authorities {
+            cells.insert(format!("authority:{}", authority), Vec::new());
+        }
+        columns.extend(cells.keys().cloned());
+
+        rows.push(OperationSecurityRow {
+            spec_path,
+            catalog_id,
+            audience,
+            operation: format!("{} {}", operation.method, operation.path),
+            cells,
+            requirements: operation.requirements,
+        });
+    }
+    let matrix = SecurityMatrix { schemes, columns: columns.into_iter().collect(), operations: rows };
+
+    HttpResponse::Ok().json(&matrix)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnsecuredOperation {
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub audience: String,
+    pub operation: String,
+}
+
+/// the operations which require neither a security scheme nor an authority
+#[get("/v1/specs/security/unsecured")]
+pub async fn get_unsecured_operations(query: web::Query<SecurityQuery>) -> impl Responder{
+    info!("get unsecured operations");
+
+    let (_schemes, operations) = list_operations_security(&query.audience);
+    let unsecured: Vec<UnsecuredOperation> = operations.into_iter()
+        .filter(|(_spec_path, _catalog_id, _audience, operation)| operation.is_unsecured())
+        .map(|(spec_path, catalog_id, audience, operation)| UnsecuredOperation {
+            spec_path,
+            catalog_id,
+            audience,
+            operation: format!("{} {}", operation.method, operation.path),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(&unsecured)
+}
+
+#[derive(Deserialize)]
+pub struct BundledSpecQuery {
+    catalog_id: String,
+    spec_path: String,
+}
+
+/// the spec (as named by `/v1/specs`) of the catalog as a single document, relative `$ref`s being inlined
+#[get("/v1/specs/bundle")]
+pub async fn get_bundled_spec(param: web::Query<BundledSpecQuery>) -> impl Responder {
+    info!("get bundled spec [{:?}] from catalog [{:?}]", &param.spec_path, &param.catalog_id);
+
+    let catalog = match get_catalog_by_id(&crate::app::catalogs::get_all_catalogs(), &param.catalog_id) {
+        Some(catalog) => catalog,
+        None => return HttpResponse::NotFound().finish(),
+    };
+    match crate::app::dao::catalog::get_bundled_spec(&catalog, &param.spec_path) {
+        Some(content) => HttpResponse::Ok().content_type("application/yaml").body(content),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpecHistory {
+    catalog_id: String,
+    spec_path: String,
+    revisions: Vec<SpecRevision>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpecRevision {
+    commit: String,
+    author: String,
+    date: chrono::DateTime<chrono::Utc>,
+    summary: String,
+    change: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    version_bump: bool,
+    endpoints_before: usize,
+    endpoints_after: usize,
+}
+
+/// every commit which changed the spec (as named by `/v1/specs`), the most recent first
+#[get("/v1/specs/{catalog}/{path:.*}/history")]
+pub async fn get_spec_history(path: web::Path<(String, String)>) -> impl Responder {
+    let (catalog_id, spec_path) = path.into_inner();
+    info!("get history of spec [{:?}] from catalog [{:?}]", &spec_path, &catalog_id);
+
+    let catalog = match get_catalog_by_id(&crate::app::catalogs::get_all_catalogs(), &catalog_id) {
+        Some(catalog) => catalog,
+        None => return HttpResponse::NotFound().finish(),
+    };
+    let all_specs = crate::app::dao::catalog::list_catalog_specs(&catalog);
+    let spec = match all_specs.iter().find(|spec| spec.get_spec_short_path() == spec_path) {
+        Some(spec) => spec,
+        None => return HttpResponse::NotFound().finish(),
+    };
+
+    match crate::app::dao::catalog::history::get_spec_history(&catalog, spec.get_file_path()) {
+        Ok(revisions) => HttpResponse::Ok().json(SpecHistory {
+            catalog_id,
+            spec_path,
+            revisions: revisions.iter().map(|revision| SpecRevision {
+                commit: String::from(&revision.commit),
+                author: String::from(&revision.author),
+                date: revision.date,
+                summary: String::from(&revision.summary),
+                change: String::from(&revision.change),
+                previous_version: revision.previous_version.clone(),
+                version: revision.version.clone(),
+                version_bump: revision.is_version_bump(),
+                endpoints_before: revision.endpoints_before,
+                endpoints_after: revision.endpoints_after,
+            }).collect(),
+        }),
+        Err(why) => {
+            error!("{}", why);
+            HttpResponse::NotFound().json(why)
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Api {
     pub id: Uuid,
@@ -358,6 +754,7 @@ pub async fn get_pull_requests(status: &str) -> PullRequests {
 pub enum ObjectType {
     ZALLY,
     PATH,
+    OPERATION,
     AUDIENCE,
     PERMISSION,
 }
@@ -369,6 +766,10 @@ pub struct Diff {
     #[serde(rename(serialize = "objectType", deserialize = "type"))]
     pub object_type: ObjectType,
     pub line: String,
+    /// the spec file, in the repository
+    pub spec_path: String,
+    pub path: Option<String>,
+    pub operation: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -384,28 +785,17 @@ pub struct Reviews {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct Line {
-    source: u64,
-    destination: u64,
-    line: String,
-    truncated: bool,
-}
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct Segment {
-    #[serde(rename(serialize = "type", deserialize = "type"))]
-    typ: String,
-    lines: Vec<Line>,
-}
-
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-struct Hunk {
-    segments: Vec<Segment>,
+struct DiffPath {
+    #[serde(rename(serialize = "toString", deserialize = "toString"))]
+    to_string: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 struct PullRequestDiff {
-    hunks: Vec<Hunk>,
+    /// none if the file is added
+    source: Option<DiffPath>,
+    /// none if the file is deleted
+    destination: Option<DiffPath>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -417,6 +807,46 @@ struct PullRequestDiffs {
     pub diffs: Vec<PullRequestDiff>,
 }
 
+fn is_spec_file(path: &str) -> bool {
+    [".yaml", ".yml", ".json"].iter().any(|extension| path.ends_with(extension))
+}
+
+/// content of a file of the repository at a given commit, none if it does not exist
+async fn get_file_content(client: &reqwest::Client, path: &str, commit: &str) -> Result<Option<String>, String> {
+    let url = format!("{}/raw/{}?at={}", SETTINGS.stash_config.base_uri, path, commit);
+    let resp = client
+        .get(url.as_str())
+        .header("Authorization", format!("Bearer {}", SETTINGS.stash_config.access_token))
+        .send()
+        .await;
+
+    match resp {
+        Ok(resp) if resp.status().is_success() => resp.text().await.map(Some).map_err(|why| format!("Calling {} - got error {:?}", url, why)),
+        Ok(resp) if resp.status() == reqwest::StatusCode::NOT_FOUND => Ok(None),
+        Ok(resp) => Err( format!("Calling {} - got HTTP Status {:?}", url, resp.status()) ),
+        Err(why) => Err( format!("Calling {} - got error {:?}", url, why) ),
+    }
+}
+
+fn to_diff(delta: SpecDelta) -> Diff {
+    Diff {
+        typ: delta.typ.to_string(),
+        object_type: match delta.kind {
+            DeltaKind::Path => ObjectType::PATH,
+            DeltaKind::Operation => ObjectType::OPERATION,
+            DeltaKind::Zally => ObjectType::ZALLY,
+            DeltaKind::Audience => ObjectType::AUDIENCE,
+            DeltaKind::Permission => ObjectType::PERMISSION,
+        },
+        line: delta.detail,
+        spec_path: delta.spec_path,
+        path: delta.path,
+        operation: delta.operation,
+    }
+}
+
+/// the structured changes (paths, operations, `x-zally-ignore`, `x-audience` and `x-has-authority`) of the specs
+/// modified by the opened pull requests
 #[get("/v1/reviews")]
 pub async fn list_all_reviews() -> impl Responder {
     info!("list all reviews");
@@ -437,7 +867,7 @@ pub async fn list_all_reviews() -> impl Responder {
             "{}/pull-requests/{}/diff",
             SETTINGS.stash_config.base_uri, pr_id
         );
-        let mut resp = client
+        let resp = client
             .get(url.as_str())
             .header("Authorization", format!("Bearer {}", access_token))
             .send()
@@ -446,52 +876,42 @@ pub async fn list_all_reviews() -> impl Responder {
 
         let response: PullRequestDiffs = resp.json().await.unwrap();
 
+        //the files are compared as they were at fromHash (before) and toHash (after)
         let mut diffs: Vec<Diff> = Vec::new();
         for diff in &response.diffs {
-            for hunk in &diff.hunks {
-                for segment in &hunk.segments {
-                    if "ADDED".eq_ignore_ascii_case(segment.typ.as_str())
-                        || "REMOVED".eq_ignore_ascii_case(segment.typ.as_str())
-                    {
-                        for line in &segment.lines {
-                            if line.line.trim_start().starts_with("/") {
-                                let diff = Diff {
-                                    typ: String::from(&segment.typ),
-                                    object_type: ObjectType::PATH,
-                                    line: String::from(&line.line),
-                                };
-                                diffs.push(diff);
-                            } else if line.line.trim_start().starts_with("x-zally-ignore") {
-                                let diff = Diff {
-                                    typ: String::from(&segment.typ),
-                                    object_type: ObjectType::ZALLY,
-                                    line: String::from(&line.line),
-                                };
-                                diffs.push(diff);
-                            } else if line.line.trim_start().starts_with("x-has-authority") {
-                                let diff = Diff {
-                                    typ: String::from(&segment.typ),
-                                    object_type: ObjectType::PERMISSION,
-                                    line: String::from(&line.line),
-                                };
-                                diffs.push(diff);
-                            } else if line.line.trim_start().starts_with("x-audience") {
-                                let diff = Diff {
-                                    typ: String::from(&segment.typ),
-                                    object_type: ObjectType::AUDIENCE,
-                                    line: String::from(&line.line),
-                                };
-                                diffs.push(diff);
-                            } else {
-                                debug!(
-                                    "line [{:?}] - does not contain interesting information",
-                                    line.line
-                                );
-                            }
-                        }
-                    }
+            let source = diff.source.as_ref().map(|path| path.to_string.as_str()).filter(|path| is_spec_file(path));
+            let destination = diff.destination.as_ref().map(|path| path.to_string.as_str()).filter(|path| is_spec_file(path));
+            let spec_path = match destination.or(source) {
+                Some(spec_path) => spec_path,
+                None => continue,
+            };
+
+            //a side is empty only if the file is added (no source) or deleted (no destination)
+            let before = match source {
+                Some(source) => get_file_content(&client, source, &response.from_hash).await.and_then(|content| content.ok_or_else(|| format!("[{}] not found at [{}]", source, response.from_hash))),
+                None => Ok(String::new()),
+            };
+            let after = match destination {
+                Some(destination) => get_file_content(&client, destination, &response.to_hash).await.and_then(|content| content.ok_or_else(|| format!("[{}] not found at [{}]", destination, response.to_hash))),
+                None => Ok(String::new()),
+            };
+            let (before, after) = match (before, after) {
+                (Ok(before), Ok(after)) => (before, after),
+                (Err(why), _) | (_, Err(why)) => {
+                    warn!("PR [{:?}] - unable to review [{:?}], skipped - [{}]", pr_id, spec_path, why);
+                    continue;
                 }
-            }
+            };
+
+            let deltas = match crate::app::dao::catalog::review::review(spec_path, source.map(|_| before.as_str()), destination.map(|_| after.as_str())) {
+                Ok(deltas) => deltas,
+                Err(why) => {
+                    warn!("PR [{:?}] - unable to review [{:?}], skipped - [{}]", pr_id, spec_path, why);
+                    continue;
+                }
+            };
+            debug!("PR [{:?}] - [{:?}] changes in [{:?}]", pr_id, deltas.len(), spec_path);
+            diffs.extend(deltas.into_iter().map(to_diff));
         }
 
         let review = Review {
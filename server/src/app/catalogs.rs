@@ -1,13 +1,18 @@
 
-use actix_web::{get, post, Responder};
+use actix_web::{delete, get, post, put, Responder};
 use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 extern crate reqwest;
 use crate::shared::settings::Catalog as RepoCatalog;
+use crate::shared::settings::RegisteredCatalogs;
 use crate::shared::settings::SETTINGS;
 
+use crate::app::jobs::{Job, JobState, update_job, update_catalog_progress};
+use crate::app::dao::catalog::source::SourceType;
+use crate::app::dao::catalog::diff::ChangeLevel;
+
 use log::{info, error};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -15,6 +20,123 @@ pub struct Catalog {
     pub id: String,
     pub name: String,
     pub http_base_uri: String, 
+    /// catalogs of the config file cannot be updated nor deleted through the API
+    pub read_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_load: Option<CatalogLoad>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scm: Option<CatalogScm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<CatalogSource>,
+    /// only when a single catalog is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<CatalogContent>,
+}
+
+/// figures of the last (re)load of the catalog specs
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogLoad {
+    pub files: usize,
+    pub specs: usize,
+    pub parsed: usize,
+    pub from_cache: usize,
+    pub errors: usize,
+    pub duration_ms: u128,
+    pub loaded_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn get_last_load(catalog_id: &str) -> Option<CatalogLoad> {
+    let stats = crate::app::dao::catalog::get_catalog_load_stats(catalog_id)?;
+
+    Some(CatalogLoad {
+        files: stats.files,
+        specs: stats.specs,
+        parsed: stats.parsed,
+        from_cache: stats.from_cache,
+        errors: stats.errors,
+        duration_ms: stats.duration_ms,
+        loaded_at: stats.loaded_at,
+    })
+}
+
+/// specs of the catalog, per grammar (e.g. `OpenAPI.v3`, `AsyncAPI.v2`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogContent {
+    pub specs: usize,
+    pub errors: usize,
+    pub grammars: HashMap<String, usize>,
+}
+
+fn get_content(catalog: &RepoCatalog) -> CatalogContent {
+    let specs = crate::app::dao::catalog::list_catalog_specs(catalog);
+
+    let mut grammars = HashMap::new();
+    for spec in &specs {
+        *grammars.entry(spec.get_spec_type().to_string()).or_insert(0) += 1;
+    }
+
+    CatalogContent {
+        specs: specs.len(),
+        errors: crate::app::dao::catalog::list_catalog_errors(catalog).len(),
+        grammars,
+    }
+}
+
+/// git status of the catalog, only when natively cloned / fetched (refer to `catalog_scm_url`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogScm {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_fetch: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+fn get_scm(catalog_id: &str) -> Option<CatalogScm> {
+    let status = crate::app::dao::catalog::scm::get_status(catalog_id)?;
+
+    Some(CatalogScm {
+        head_commit: status.head_commit,
+        branch: status.branch,
+        last_fetch: status.last_fetch,
+        last_error: status.last_error,
+    })
+}
+
+/// archive or HTTP index the catalog is fetched from (refer to `catalog_source_type`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogSource {
+    #[serde(rename = "type")]
+    pub source_type: String,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    pub files: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_fetch: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+fn get_source(catalog: &RepoCatalog) -> Option<CatalogSource> {
+    let source_type = match SourceType::parse(&catalog.catalog_source_type) {
+        Ok(SourceType::Git) => return None,
+        Ok(source_type) => String::from(source_type.as_str()),
+        Err(_) => String::from(&catalog.catalog_source_type),
+    };
+    let status = crate::app::dao::catalog::source::get_status(&SETTINGS.database, &catalog.catalog_id).unwrap_or_default();
+
+    Some(CatalogSource {
+        source_type,
+        url: String::from(&catalog.catalog_source_url),
+        checksum: status.checksum,
+        files: status.files,
+        last_fetch: status.last_fetch,
+        last_error: status.last_error,
+    })
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -22,52 +144,605 @@ pub struct Catalogs {
     pub catalogs: Vec<Catalog>,
 }
 
+/// Catalogs of the config file (read only seeds) followed by the ones registered through the API
+pub fn get_all_catalogs() -> Vec<RepoCatalog> {
+    let mut catalogs = SETTINGS.catalogs.to_vec();
+
+    match crate::app::dao::repo_catalogs::list_all_catalogs(&SETTINGS.database) {
+        Ok(registered) => catalogs.extend(registered.into_iter().filter(|catalog| !is_read_only(&catalog.catalog_id))),
+        Err(e) => error!("Unable to read the catalogs registered through the API - [{:?}]", e),
+    };
+
+    catalogs
+}
+
+fn is_read_only(catalog_id: &str) -> bool {
+    SETTINGS.catalogs.iter().any(|catalog| catalog.catalog_id == catalog_id)
+}
+
+fn to_catalog(catalog: &RepoCatalog) -> Catalog {
+    Catalog {
+        id: String::from(&catalog.catalog_id),
+        name: String::from(&catalog.catalog_name),
+        http_base_uri: String::from(&catalog.catalog_http_base_uri),
+        read_only: is_read_only(&catalog.catalog_id),
+        last_load: get_last_load(&catalog.catalog_id),
+        scm: get_scm(&catalog.catalog_id),
+        source: get_source(catalog),
+        content: None,
+    }
+}
+
+fn find_catalog(id: &str) -> Option<RepoCatalog> {
+    get_all_catalogs().into_iter().find(|catalog| catalog.catalog_id == id)
+}
+
 #[get("/v1/catalogs/{id}")]
 pub async fn get_catalog_by_id(path: web::Path<String>) -> impl Responder{
     let id: String = path.into_inner();
     info!("get catalog for id [{:?}]", &id);
 
-    let catalog_as_vec = &SETTINGS.catalogs;
+    match find_catalog(&id) {
+        Some(curr_catalog) => {
+            let mut catalog = to_catalog(&curr_catalog);
+            catalog.content = Some(get_content(&curr_catalog));
+            HttpResponse::Ok().json(catalog)
+        },
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+/// the specs of a single catalog, as listed by `/v1/specs`
+#[get("/v1/catalogs/{id}/specs")]
+pub async fn get_catalog_specs(path: web::Path<String>) -> impl Responder{
+    let id: String = path.into_inner();
+    info!("get specs of catalog [{:?}]", &id);
+
+    match find_catalog(&id) {
+        Some(catalog) => HttpResponse::Ok().json(crate::app::apis::to_specs(crate::app::dao::catalog::list_catalog_specs(&catalog))),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogDiffQuery {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpecChange {
+    pub level: String,
+    pub location: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpecDiff {
+    pub spec_path: String,
+    pub changes: Vec<SpecChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogDiff {
+    pub catalog_id: String,
+    pub from: String,
+    pub to: String,
+    pub breaking: usize,
+    pub non_breaking: usize,
+    pub annotations: usize,
+    pub specs: Vec<SpecDiff>,
+}
 
-    let catalog_as_map = catalog_as_vec.into_iter().map(|data| (data.catalog_id.clone(), data.clone())).collect::<HashMap<String, RepoCatalog>>();
-    let curr_catalog = &catalog_as_map.get(&id).unwrap();
+/// the changes of the specs of a catalog between two git references, e.g. `?from=v1.2.0&to=main`
+#[get("/v1/catalogs/{id}/diff")]
+pub async fn get_catalog_diff(path: web::Path<String>, query: web::Query<CatalogDiffQuery>) -> impl Responder{
+    let id: String = path.into_inner();
+    let query = query.into_inner();
+    info!("get diff of catalog [{:?}] between [{:?}] and [{:?}]", &id, &query.from, &query.to);
 
-    let returned_catalog = self::Catalog {
-        id: String::from(&curr_catalog.catalog_id),
-        name: String::from(&curr_catalog.catalog_name),
-        http_base_uri: String::from(&curr_catalog.catalog_http_base_uri)
+    let catalog = match find_catalog(&id) {
+        Some(catalog) => catalog,
+        None => return HttpResponse::NotFound().finish(),
     };
 
-    HttpResponse::Ok().json(&returned_catalog)
+    match crate::app::dao::catalog::diff::diff_refs(&catalog, &query.from, &query.to) {
+        Ok(spec_diffs) => {
+            let count = |level: ChangeLevel| spec_diffs.iter().flat_map(|spec_diff| &spec_diff.changes).filter(|change| change.level == level).count();
+            HttpResponse::Ok().json(CatalogDiff {
+                catalog_id: id,
+                breaking: count(ChangeLevel::Breaking),
+                non_breaking: count(ChangeLevel::NonBreaking),
+                annotations: count(ChangeLevel::Annotation),
+                from: query.from,
+                to: query.to,
+                specs: spec_diffs.iter().map(|spec_diff| SpecDiff {
+                    spec_path: String::from(&spec_diff.spec_path),
+                    changes: spec_diff.changes.iter().map(|change| SpecChange {
+                        level: change.level.to_string(),
+                        location: String::from(&change.location),
+                        message: String::from(&change.message),
+                    }).collect(),
+                }).collect(),
+            })
+        },
+        Err(why) => {
+            error!("{}", why);
+            HttpResponse::BadRequest().json(why)
+        }
+    }
 }
 
 #[get("/v1/catalogs")]
 pub async fn get_all_catalog() -> impl Responder{
     info!("get all catalogs");
 
-    let catalog_as_vec = &SETTINGS.catalogs;
+    let catalog_as_vec = get_all_catalogs();
     
     let mut returned_catalog: Vec<Catalog> = Vec::new();
-    for cat in catalog_as_vec{
-        returned_catalog.push(Catalog{ 
-            id: String::from(&cat.catalog_id), 
-            name: String::from(&cat.catalog_name), 
-            http_base_uri: String::from(&cat.catalog_http_base_uri) });
+    for cat in &catalog_as_vec{
+        returned_catalog.push(to_catalog(cat));
     }
 
     HttpResponse::Ok().json(&returned_catalog)
 }
 
+/// Definition of a catalog registered through the API, its repository is cloned natively
+/// (or its archive / HTTP index is fetched)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogDefinition {
+    /// generated if not provided
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// where the repository is cloned, relative to (or within) `registered_catalogs.root_path`
+    pub path: String,
+    /// prefix removed from the spec paths, `path` if not provided
+    #[serde(default)]
+    pub dir: String,
+    /// `**/*.{yml,yaml,json}` if not provided
+    #[serde(default)]
+    pub include_glob_pattern: Vec<String>,
+    #[serde(default)]
+    pub http_base_uri: String,
+    #[serde(default)]
+    pub scm_url: String,
+    #[serde(default)]
+    pub scm_branch: String,
+    #[serde(default)]
+    pub scm_depth: i32,
+    #[serde(default)]
+    pub scm_username: String,
+    /// rejected, credentials are not stored - refer to `scm_password_env`
+    #[serde(default)]
+    pub scm_password: String,
+    /// environment variable (of the server) holding the password or token, it must start with `registered_catalogs.password_env_prefix`
+    #[serde(default)]
+    pub scm_password_env: String,
+    #[serde(default)]
+    pub watch: bool,
+    /// `git` if not provided, `archive` or `http_index`
+    #[serde(default)]
+    pub source_type: String,
+    /// archive or index URL (`http` or `https`)
+    #[serde(default)]
+    pub source_url: String,
+    /// governance ruleset (YAML) of the repository, relative to `path`
+    #[serde(default)]
+    pub ruleset_path: String,
+}
+
+impl CatalogDefinition {
+    /// the catalog, cloned (or fetched) into `registered_catalogs.root_path` - only remote repositories and sources are accepted
+    fn to_repo_catalog(&self, id: &str, registered_catalogs: &RegisteredCatalogs) -> Result<RepoCatalog, String> {
+        if self.name.trim().is_empty() || self.path.trim().is_empty() {
+            return Err( String::from("name and path of the catalog are mandatory") );
+        }
+        let path = resolve_catalog_path(&registered_catalogs.root_path, &self.path)?;
+        if !self.scm_password.is_empty() {
+            return Err( String::from("scm_password is not stored, set scm_password_env to the environment variable holding it") );
+        }
+        //any other variable of the server (e.g. an access token) would be sent to the scm_url
+        let password_env = self.scm_password_env.trim();
+        let password_env_prefix = registered_catalogs.password_env_prefix.trim();
+        if !password_env.is_empty() && (password_env_prefix.is_empty() || !password_env.starts_with(password_env_prefix)) {
+            return Err( format!("scm_password_env [{}] must start with [{}] (registered_catalogs.password_env_prefix)", password_env, password_env_prefix) );
+        }
+        if !self.scm_url.trim().is_empty() && !is_remote_url(self.scm_url.trim(), &["http://", "https://", "ssh://", "git://"]) {
+            return Err( format!("scm_url [{}] must be a remote (http, https, ssh or git) repository", self.scm_url) );
+        }
+        let source_type = SourceType::parse(&self.source_type)?;
+        if source_type != SourceType::Git && !is_remote_url(self.source_url.trim(), &["http://", "https://"]) {
+            return Err( format!("an http or https source_url is mandatory for [{}] catalogs", source_type.as_str()) );
+        }
+        let ruleset_path = std::path::Path::new(self.ruleset_path.trim());
+        if ruleset_path.is_absolute() || ruleset_path.components().any(|component| component == std::path::Component::ParentDir) {
+            return Err( format!("ruleset_path [{}] must be relative to the catalog path", self.ruleset_path) );
+        }
+
+        Ok(RepoCatalog {
+            catalog_id: String::from(id),
+            catalog_name: String::from(&self.name),
+            catalog_dir: match self.dir.is_empty() {
+                true => String::from(&path),
+                false => String::from(&self.dir),
+            },
+            catalog_path: path,
+            catalog_include_glob_pattern: match self.include_glob_pattern.is_empty() {
+                true => vec![ String::from("**/*.{yml,yaml,json}") ],
+                false => self.include_glob_pattern.to_vec(),
+            },
+            catalog_http_base_uri: String::from(&self.http_base_uri),
+            catalog_scm_clone: !self.scm_url.is_empty(),
+            catalog_scm_url: String::from(&self.scm_url),
+            catalog_scm_branch: String::from(&self.scm_branch),
+            catalog_scm_depth: self.scm_depth,
+            catalog_scm_username: String::from(&self.scm_username),
+            catalog_scm_password_env: String::from(self.scm_password_env.trim()),
+            catalog_watch: self.watch,
+            catalog_source_type: String::from(source_type.as_str()),
+            catalog_source_url: String::from(self.source_url.trim()),
+            catalog_ruleset_path: String::from(self.ruleset_path.trim()),
+            ..Default::default()
+        })
+    }
+}
+
+/// `path` of a registered catalog, relative to `root_path` (or absolute, within it)
+fn resolve_catalog_path(root_path: &str, path: &str) -> Result<String, String> {
+    if root_path.trim().is_empty() {
+        return Err( String::from("catalogs cannot be registered through the API, registered_catalogs.root_path is not configured") );
+    }
+    let root = std::path::Path::new(root_path.trim());
+    let path = std::path::Path::new(path.trim());
+    if path.components().any(|component| component == std::path::Component::ParentDir) {
+        return Err( format!("path [{}] must not contain `..`", path.display()) );
+    }
+
+    let resolved = root.join(path);
+    let outside = || format!("path [{}] must be a sub-directory of [{}]", path.display(), root.display());
+    if !resolved.starts_with(root) || resolved == root {
+        return Err( outside() );
+    }
+    //nor lead out of it through symbolic links
+    if let (Ok(root), Ok(resolved)) = (std::fs::canonicalize(root), std::fs::canonicalize(&resolved)) {
+        if !resolved.starts_with(root) {
+            return Err( outside() );
+        }
+    }
+
+    Ok(String::from(resolved.to_string_lossy()))
+}
+
+/// the catalog, unless its path is (or is within, or holds) the path of another catalog - they would share their working copy and index documents
+fn check_path_overlaps(catalog: RepoCatalog, all_catalogs: &[RepoCatalog]) -> Result<RepoCatalog, String> {
+    let path = std::path::Path::new(&catalog.catalog_path);
+    let overlapping = all_catalogs.iter()
+        .filter(|other| other.catalog_id != catalog.catalog_id)
+        .find(|other| {
+            let other_path = std::path::Path::new(&other.catalog_path);
+            path.starts_with(other_path) || other_path.starts_with(path)
+        });
+
+    match overlapping {
+        Some(other) => Err( format!("path [{}] overlaps the path [{}] of catalog [{}]", catalog.catalog_path, other.catalog_path, other.catalog_id) ),
+        None => Ok(catalog),
+    }
+}
+
+/// `url` starts with one of the `schemes`, or is a scp-like ssh url (e.g. `git@host:apis.git`) if `ssh://` is accepted
+fn is_remote_url(url: &str, schemes: &[&str]) -> bool {
+    lazy_static! {
+        static ref SCP_LIKE: regex::Regex = regex::Regex::new(r"^[\w.-]+@[\w.-]+:[^/]").unwrap();
+    }
+
+    schemes.iter().any(|scheme| url.starts_with(scheme)) || (schemes.contains(&"ssh://") && SCP_LIKE.is_match(url))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogRegistration {
+    pub catalog: Catalog,
+    /// clone and indexing of the catalog
+    pub job: Job,
+}
+
+/// Registers a catalog, it is then cloned and indexed in the background
+#[post("/v1/catalogs")]
+pub async fn create_catalog(definition: web::Json<CatalogDefinition>) -> impl Responder{
+    info!("create catalog [{:?}]", definition.name);
+
+    let id = match definition.id.trim().is_empty() {
+        true => format!("{}", uuid::Uuid::new_v4()),
+        false => String::from(definition.id.trim()),
+    };
+    if get_all_catalogs().iter().any(|catalog| catalog.catalog_id == id) {
+        return HttpResponse::BadRequest().json(format!("Catalog [{}] already exists", id));
+    }
+    let catalog = match definition.to_repo_catalog(&id, &SETTINGS.registered_catalogs).and_then(|catalog| check_path_overlaps(catalog, &get_all_catalogs())) {
+        Ok(catalog) => catalog,
+        Err(why) => return HttpResponse::BadRequest().json(why),
+    };
+
+    match crate::app::dao::repo_catalogs::add_catalog(&SETTINGS.database, &catalog) {
+        Ok(_) => HttpResponse::Accepted().json(register_catalog(catalog)),
+        Err(e) => {
+            error!("Error while creating catalog [{:?}] - [{:?}]", id, e);
+            HttpResponse::BadRequest().json("Error while creating catalog")
+        }
+    }
+}
+
+/// Updates a catalog registered through the API - its specs are purged, then it is cloned (or fetched) and indexed again
+#[put("/v1/catalogs/{id}")]
+pub async fn update_catalog(path: web::Path<String>, definition: web::Json<CatalogDefinition>) -> impl Responder{
+    let id: String = path.into_inner();
+    info!("update catalog [{:?}]", &id);
+
+    if is_read_only(&id) {
+        return HttpResponse::Forbidden().json(format!("Catalog [{}] is defined in the config file and cannot be updated", id));
+    }
+    let catalog = match definition.to_repo_catalog(&id, &SETTINGS.registered_catalogs).and_then(|catalog| check_path_overlaps(catalog, &get_all_catalogs())) {
+        Ok(catalog) => catalog,
+        Err(why) => return HttpResponse::BadRequest().json(why),
+    };
+
+    match crate::app::dao::repo_catalogs::update_catalog(&SETTINGS.database, &catalog) {
+        Ok(0) => HttpResponse::NotFound().finish(),
+        Ok(_) => {
+            purge_catalog(&id);
+            HttpResponse::Accepted().json(register_catalog(catalog))
+        },
+        Err(e) => {
+            error!("Error while updating catalog [{:?}] - [{:?}]", id, e);
+            HttpResponse::BadRequest().json("Error while updating catalog")
+        }
+    }
+}
+
+/// Deletes a catalog registered through the API and purges its specs from the cache and the index.
+/// The cloned repository is kept on disk.
+#[delete("/v1/catalogs/{id}")]
+pub async fn delete_catalog(path: web::Path<String>) -> impl Responder{
+    let id: String = path.into_inner();
+    info!("delete catalog [{:?}]", &id);
+
+    if is_read_only(&id) {
+        return HttpResponse::Forbidden().json(format!("Catalog [{}] is defined in the config file and cannot be deleted", id));
+    }
+
+    match crate::app::dao::repo_catalogs::delete_catalog(&SETTINGS.database, &id) {
+        Ok(0) => HttpResponse::NotFound().finish(),
+        Ok(_) => {
+            purge_catalog(&id);
+            HttpResponse::NoContent().finish()
+        },
+        Err(e) => {
+            error!("Error while deleting catalog [{:?}] - [{:?}]", id, e);
+            HttpResponse::BadRequest().json("Error while deleting catalog")
+        }
+    }
+}
+
+fn purge_catalog(id: &str) {
+    crate::app::dao::catalog::watch::unwatch(id);
+    crate::app::dao::catalog::scm::remove_status(id);
+    crate::app::dao::catalog::source::remove_status(&SETTINGS.database, id);
+    crate::app::dao::catalog::history::remove_history(id);
+
+    let removed_files = crate::app::dao::catalog::purge_catalog(id);
+    if let Err(e) = crate::app::dao::search::update_index(&SETTINGS.search.index_path, &removed_files, &Vec::new()) {
+        error!("Error while removing the specs of catalog [{:?}] from the index - [{:?}]", id, e);
+    }
+}
+
+/// Clones and indexes the catalog in the background
+fn register_catalog(catalog: RepoCatalog) -> CatalogRegistration {
+    let (job, created) = crate::app::jobs::create_job(&format!("catalog_register_{}", catalog.catalog_id), vec![String::from(&catalog.catalog_id)]);
+    if created {
+        let id = String::from(&job.id);
+        let registered = catalog.clone();
+        std::thread::spawn(move || run_job(&id, || register_in_job(&id, &registered)));
+    }
+
+    CatalogRegistration { catalog: to_catalog(&catalog), job }
+}
+
+fn register_in_job(id: &str, catalog: &RepoCatalog) -> Result<(), String> {
+    update_job(id, |job| job.step = Some(String::from("cloning")));
+    update_catalog_progress(id, &catalog.catalog_id, |progress| progress.state = JobState::Running);
+    if let Err(why) = crate::app::dao::catalog::pull_catalog(catalog, true) {
+        update_catalog_progress(id, &catalog.catalog_id, |progress| progress.error = Some(why));
+    }
+
+    reload_and_index(id, catalog)?;
+
+    if catalog.catalog_watch {
+        watch_catalog(catalog);
+    }
+
+    Ok(())
+}
+
+/// Refreshes the catalogs in the background - the returned job can be followed through `/v1/jobs/{id}`.
+/// If a refresh is already pending or running, this one is returned.
 #[post("/v1/catalogs/refresh")]
 pub async fn refresh_all_catalogs() -> impl Responder{
     info!("refresh_all_catalogs");
-    resfresh_caches_and_indexes(false);
-    HttpResponse::Ok().json(())
+
+    let (job, created) = create_refresh_job();
+    if created {
+        let id = String::from(&job.id);
+        std::thread::spawn(move || run_refresh_job(&id));
+    }
+
+    HttpResponse::Accepted().json(job)
+}
+
+/// Refreshes a single catalog in the background, the other catalogs are neither pulled nor reloaded.
+/// If a refresh of the catalog, or of all the catalogs, is already pending or running, this one is returned.
+#[post("/v1/catalogs/{id}/refresh")]
+pub async fn refresh_catalog(path: web::Path<String>) -> impl Responder{
+    let id: String = path.into_inner();
+    info!("refresh catalog [{:?}]", &id);
+
+    let catalog = match find_catalog(&id) {
+        Some(catalog) => catalog,
+        None => return HttpResponse::NotFound().finish(),
+    };
+
+    let kind = format!("{}{}", CATALOG_REFRESH_JOB, id);
+    let (job, created) = crate::app::jobs::create_exclusive_job(&kind, vec![String::from(&id)], |other| other == kind || other == REFRESH_JOB);
+    match created {
+        true => {
+            let job_id = String::from(&job.id);
+            std::thread::spawn(move || run_job(&job_id, || refresh_catalog_in_job(&job_id, &catalog)));
+        },
+        false => info!("Refresh job [{:?}] is already [{:?}] - requested refresh of catalog [{:?}] is coalesced", job.id, job.state, &id),
+    };
+
+    HttpResponse::Accepted().json(job)
+}
+
+fn refresh_catalog_in_job(id: &str, catalog: &RepoCatalog) -> Result<(), String> {
+    update_job(id, |job| job.step = Some(String::from("pulling")));
+    update_catalog_progress(id, &catalog.catalog_id, |progress| progress.state = JobState::Running);
+    if let Err(why) = crate::app::dao::catalog::pull_catalog(catalog, false) {
+        update_catalog_progress(id, &catalog.catalog_id, |progress| progress.error = Some(why));
+    }
+
+    reload_and_index(id, catalog)
+}
+
+const REFRESH_JOB: &str = "catalogs_refresh";
+/// followed by the id of the refreshed catalog
+const CATALOG_REFRESH_JOB: &str = "catalog_refresh_";
+
+/// Creates a refresh job, unless one (of all the catalogs or of a single one) is already pending or running -
+/// the latter is then returned (with false)
+pub fn create_refresh_job() -> (Job, bool) {
+    let catalog_ids = get_all_catalogs().iter().map(|catalog| String::from(&catalog.catalog_id)).collect();
+
+    let (job, created) = crate::app::jobs::create_exclusive_job(REFRESH_JOB, catalog_ids, |other| other == REFRESH_JOB || other.starts_with(CATALOG_REFRESH_JOB));
+    if !created {
+        info!("Refresh job [{:?}] is already [{:?}] - requested refresh is coalesced", job.id, job.state);
+    }
+
+    (job, created)
+}
+
+/// Pulls the catalogs, reloads the specs and rebuilds the index. A catalog which cannot be pulled is
+/// flagged as failed, its specs are still reloaded. The job fails if it cannot be completed.
+/// Returns the error of the job or of its catalogs, if any
+pub fn run_refresh_job(id: &str) -> Result<(), String> {
+    run_job(id, || refresh_in_job(id));
+
+    match crate::app::jobs::get_job(id).and_then(|job| job.get_error()) {
+        Some(why) => Err(why),
+        None => Ok(()),
+    }
+}
+
+/// Keeps the state, timing and error of the job up to date while running `task`
+fn run_job<F>(id: &str, task: F)
+where
+    F: FnOnce() -> Result<(), String> + std::panic::UnwindSafe,
+{
+    let now = std::time::Instant::now();
+    update_job(id, |job| {
+        job.state = JobState::Running;
+        job.started_at = Some(chrono::Utc::now());
+    });
+
+    let result = std::panic::catch_unwind(task);
+
+    update_job(id, |job| {
+        job.step = None;
+        job.ended_at = Some(chrono::Utc::now());
+        job.duration_ms = Some(now.elapsed().as_millis());
+        match result {
+            Ok(Ok(())) => job.state = JobState::Succeeded,
+            Ok(Err(why)) => {
+                job.state = JobState::Failed;
+                job.error = Some(why);
+            },
+            Err(why) => {
+                job.state = JobState::Failed;
+                job.error = Some(crate::app::jobs::get_panic_message(&why));
+            },
+        };
+    });
+    info!("Job [{:?}] is done in [{}] milli seconds", id, now.elapsed().as_millis());
+}
+
+fn refresh_in_job(id: &str) -> Result<(), String> {
+    let catalogs = get_all_catalogs();
+
+    update_job(id, |job| job.step = Some(String::from("pulling")));
+    for catalog in &catalogs {
+        update_catalog_progress(id, &catalog.catalog_id, |progress| progress.state = JobState::Running);
+        if let Err(why) = crate::app::dao::catalog::pull_catalog(catalog, false) {
+            update_catalog_progress(id, &catalog.catalog_id, |progress| progress.error = Some(why));
+        }
+    }
+
+    load_and_index(id, &catalogs)
+}
+
+/// Reloads the specs of all the catalogs and rebuilds the index, the job tracks the catalogs it has been created for
+fn load_and_index(id: &str, catalogs: &Vec<RepoCatalog>) -> Result<(), String> {
+    update_job(id, |job| job.step = Some(String::from("loading")));
+    let specs = crate::app::dao::catalog::reload_catalogs(catalogs);
+    for catalog in catalogs {
+        end_catalog_progress(id, &catalog.catalog_id);
+    }
+
+    let errors = crate::app::dao::catalog::list_errors(catalogs).len();
+    update_job(id, |job| {
+        job.step = Some(String::from("indexing"));
+        job.specs = specs.len();
+        job.errors = errors;
+    });
+    crate::app::dao::search::build_index(&SETTINGS.search.index_path, &specs).map_err(|e| format!("Error while indexing all specs - {:?}", e))
+}
+
+/// Sets the figures of the last load of the catalog into the job, returns its number of errors
+fn end_catalog_progress(id: &str, catalog_id: &str) -> usize {
+    let stats = crate::app::dao::catalog::get_catalog_load_stats(catalog_id);
+    let errors = stats.as_ref().map(|stats| stats.errors).unwrap_or(0);
+    update_catalog_progress(id, catalog_id, |progress| {
+        if let Some(stats) = stats {
+            progress.specs = stats.specs;
+            progress.errors = stats.errors;
+            progress.duration_ms = Some(stats.duration_ms);
+        }
+        progress.state = match progress.error {
+            Some(_) => JobState::Failed,
+            None => JobState::Succeeded,
+        };
+    });
+
+    errors
+}
+
+/// Reloads the specs of a single catalog and replaces them in the index
+fn reload_and_index(id: &str, catalog: &RepoCatalog) -> Result<(), String> {
+    update_job(id, |job| job.step = Some(String::from("loading")));
+    let (previous_files, specs) = crate::app::dao::catalog::reload_catalog(catalog);
+    let errors = end_catalog_progress(id, &catalog.catalog_id);
+
+    update_job(id, |job| {
+        job.step = Some(String::from("indexing"));
+        job.specs = specs.len();
+        job.errors = errors;
+    });
+    crate::app::dao::search::update_index(&SETTINGS.search.index_path, &previous_files, &specs)
+        .map_err(|e| format!("Error while indexing specs of catalog [{}] - {:?}", catalog.catalog_id, e))
 }
 
 pub fn resfresh_caches_and_indexes(init: bool) {
-    crate::app::dao::catalog::refresh_catalogs(&SETTINGS.catalogs, init);
-    let specs = crate::app::dao::catalog::list_specs(&SETTINGS.catalogs);
+    let catalogs = get_all_catalogs();
+    crate::app::dao::catalog::refresh_catalogs(&catalogs, init);
+    let specs = crate::app::dao::catalog::list_specs(&catalogs);
     match crate::app::dao::search::build_index(&SETTINGS.search.index_path, &specs){
         Ok(_results) => {
             //does nothing, logs have already been written
@@ -76,4 +751,90 @@ pub fn resfresh_caches_and_indexes(init: bool) {
             error!("Error while indexing all specs - [{:?}]", e);
         }
     };
-}
\ No newline at end of file
+}
+
+/// Starts watching the catalogs with `catalog_watch`, changed files are reloaded and reindexed
+pub fn watch_catalogs() {
+    for catalog in get_all_catalogs().iter().filter(|catalog| catalog.catalog_watch) {
+        watch_catalog(catalog);
+    }
+}
+
+fn watch_catalog(catalog: &RepoCatalog) {
+    let watched = crate::app::dao::catalog::watch::watch(catalog, |catalog, changed_files| {
+        let (reloaded_files, specs) = crate::app::dao::catalog::reload_files(catalog, changed_files);
+        if reloaded_files.is_empty() {
+            return;
+        }
+        if let Err(e) = crate::app::dao::search::update_index(&SETTINGS.search.index_path, &reloaded_files, &specs) {
+            error!("Error while indexing specs of catalog [{:?}] - [{:?}]", catalog.catalog_id, e);
+        }
+    });
+
+    if let Err(why) = watched {
+        error!("{}", why);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    fn definition(path: &str) -> super::CatalogDefinition {
+        serde_json::from_str(&format!(r#"{{ "name": "apis", "path": "{}", "scm_url": "https://git.company.com/apis.git" }}"#, path)).unwrap()
+    }
+
+    fn registered_catalogs() -> super::RegisteredCatalogs {
+        super::RegisteredCatalogs { root_path: String::from("/var/catalogs"), password_env_prefix: String::from("APIS_") }
+    }
+
+    #[test]
+    fn test_to_repo_catalog() {
+        let catalog = definition("apis").to_repo_catalog("id", &registered_catalogs()).unwrap();
+        assert_eq!(catalog.catalog_path, "/var/catalogs/apis");
+        assert_eq!(catalog.catalog_dir, "/var/catalogs/apis");
+        assert!(catalog.catalog_scm_clone);
+
+        //within the root directory only
+        assert!(definition("/var/catalogs/apis").to_repo_catalog("id", &registered_catalogs()).is_ok());
+        assert!(definition("/home/user").to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("must be a sub-directory"));
+        assert!(definition("apis/../..").to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("`..`"));
+        assert!(definition(".").to_repo_catalog("id", &registered_catalogs()).is_err());
+        assert!(definition("apis").to_repo_catalog("id", &super::RegisteredCatalogs::default()).unwrap_err().contains("root_path is not configured"));
+
+        //remote repositories and sources only, credentials are not stored
+        let mut local = definition("apis");
+        local.scm_url = String::from("/home/user/repo");
+        assert!(local.to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("scm_url"));
+        local.scm_url = String::from("git@git.company.com:apis.git");
+        assert!(local.to_repo_catalog("id", &registered_catalogs()).is_ok());
+        local.source_type = String::from("archive");
+        local.source_url = String::from("file:///etc/passwd");
+        assert!(local.to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("source_url"));
+
+        let mut secret = definition("apis");
+        secret.scm_password = String::from("secret");
+        assert!(secret.to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("scm_password_env"));
+        secret.scm_password = String::new();
+        secret.scm_password_env = String::from("APIS_GIT_TOKEN");
+        assert_eq!(secret.to_repo_catalog("id", &registered_catalogs()).unwrap().catalog_scm_password_env, "APIS_GIT_TOKEN");
+        //nor any other secret of the server
+        secret.scm_password_env = String::from("STASH_CONFIG_ACCESS_TOKEN");
+        assert!(secret.to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("must start with [APIS_]"));
+        secret.scm_password_env = String::from("APIS_GIT_TOKEN");
+        let no_prefix = super::RegisteredCatalogs { password_env_prefix: String::new(), ..registered_catalogs() };
+        assert!(secret.to_repo_catalog("id", &no_prefix).is_err());
+
+        //nor any path of another catalog
+        let other = |id: &str, path: &str| super::RepoCatalog { catalog_id: String::from(id), catalog_path: String::from(path), ..Default::default() };
+        let catalog = definition("apis/v1").to_repo_catalog("id", &registered_catalogs()).unwrap();
+        assert!(super::check_path_overlaps(catalog.clone(), &[ other("other", "/var/catalogs/apis") ]).unwrap_err().contains("catalog [other]"));
+        assert!(super::check_path_overlaps(catalog.clone(), &[ other("other", "/var/catalogs/apis/v1") ]).is_err());
+        assert!(super::check_path_overlaps(catalog.clone(), &[ other("other", "/var/catalogs/apis/v1/pets") ]).is_err());
+        assert!(super::check_path_overlaps(catalog.clone(), &[ other("other", "/var/catalogs/apis/v10") ]).is_ok());
+        //updated in place
+        assert!(super::check_path_overlaps(catalog, &[ other("id", "/var/catalogs/apis/v1") ]).is_ok());
+
+        let mut ruleset = definition("apis");
+        ruleset.ruleset_path = String::from("../../etc/ruleset.yaml");
+        assert!(ruleset.to_repo_catalog("id", &registered_catalogs()).unwrap_err().contains("ruleset_path"));
+    }
+}
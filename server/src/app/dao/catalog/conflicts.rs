@@ -0,0 +1,357 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use log::debug;
+use regex::Regex;
+use strum_macros::{Display, EnumString};
+
+use super::diff::{entries, get, HTTP_METHODS};
+use super::handlers::SpecType;
+use super::spec::SpecItem;
+use super::{ErrorCategory, SpecInError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumString, Display)]
+pub enum ConflictKind {
+    /// the same base path + path + method
+    #[strum(serialize = "path-collision")]
+    PathCollision,
+    /// the same `x-api-id` for different titles
+    #[strum(serialize = "duplicate-api-id")]
+    DuplicateApiId,
+    /// the same title with different versions
+    #[strum(serialize = "diverging-versions")]
+    DivergingVersions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictingSpec {
+    pub file_path: String,
+    /// as named by `/v1/specs`
+    pub spec_path: String,
+    pub catalog_id: String,
+    /// e.g. the declared operation, the title or the version of the spec
+    pub detail: String,
+}
+
+/// Specs, possibly of different catalogs, which conflict with each other
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    /// what the specs share, e.g. `GET /v1/pets/{}`, an `x-api-id` or a title
+    pub key: String,
+    pub specs: Vec<ConflictingSpec>,
+}
+
+impl Conflict {
+    pub fn get_message(&self) -> String {
+        let details: Vec<String> = self.specs.iter().map(|spec| format!("{} ({})", spec.spec_path, spec.detail)).collect();
+        match self.kind {
+            ConflictKind::PathCollision => format!("[{}] is declared by [{}]", self.key, details.join(", ")),
+            ConflictKind::DuplicateApiId => format!("x-api-id [{}] is used by [{}]", self.key, details.join(", ")),
+            ConflictKind::DivergingVersions => format!("[{}] has diverging versions [{}]", self.key, details.join(", ")),
+        }
+    }
+}
+
+lazy_static! {
+    static ref PATH_PARAMETER: Regex = Regex::new(r"\{[^}]*\}").unwrap();
+}
+
+/// Checks the specs of all the catalogs against each other. The specs without base path (i.e. `servers` or `basePath`)
+/// are not checked for path collisions
+pub fn list_conflicts(all_specs: &[SpecItem]) -> Vec<Conflict> {
+    let mut conflicts = get_path_collisions(all_specs);
+    conflicts.extend(get_duplicate_api_ids(all_specs));
+    conflicts.extend(get_diverging_versions(all_specs));
+
+    conflicts
+}
+
+/// the conflicts as errors of each of the conflicting specs
+pub fn to_errors(conflicts: &[Conflict]) -> Vec<SpecInError> {
+    conflicts.iter()
+        .flat_map(|conflict| {
+            let reason = format!("[{}] {}", conflict.kind, conflict.get_message());
+            conflict.specs.iter().map(move |spec| SpecInError {
+                file_path: format!("{:?}", std::path::Path::new(&spec.file_path)),
+                reason: String::from(&reason),
+                category: ErrorCategory::Conflict,
+            })
+        })
+        .collect()
+}
+
+fn to_conflicting_spec(spec: &SpecItem, detail: String) -> ConflictingSpec {
+    ConflictingSpec {
+        file_path: String::from(spec.get_file_path()),
+        spec_path: String::from(spec.get_spec_short_path()),
+        catalog_id: String::from(spec.get_catalog_id()),
+        detail,
+    }
+}
+
+fn get_path_collisions(all_specs: &[SpecItem]) -> Vec<Conflict> {
+    //operation -> the specs declaring it and how
+    let mut operations: BTreeMap<String, Vec<(usize, String)>> = BTreeMap::new();
+    for (index, spec) in all_specs.iter().enumerate() {
+        match spec.get_spec_type() {
+            SpecType::OpenAPIv3 | SpecType::OpenAPIv31 | SpecType::Swagger2 => {},
+            _ => continue,
+        };
+        let document = match spec.get_document() {
+            Some(document) => document,
+            None => continue,
+        };
+        let base_paths = get_base_paths(&document);
+        if base_paths.is_empty() {
+            debug!("no base path, skip spec [{:?}]", spec.get_file_path());
+            continue;
+        }
+
+        for (path, item) in entries(get(&document, "paths")) {
+            for method in HTTP_METHODS.iter().filter(|method| get(item, method).is_some()) {
+                //the same operation may be exposed on several servers
+                let keys: BTreeSet<String> = base_paths.iter()
+                    .map(|base_path| format!("{} {}", method.to_uppercase(), normalize(&format!("{}/{}", base_path, path))))
+                    .collect();
+                for key in keys {
+                    operations.entry(key).or_default().push((index, format!("{} {}", method.to_uppercase(), path)));
+                }
+            }
+        }
+    }
+
+    operations.into_iter()
+        .filter(|(_key, declarations)| declarations.iter().map(|(index, _operation)| index).collect::<BTreeSet<_>>().len() > 1)
+        .map(|(key, declarations)| Conflict {
+            kind: ConflictKind::PathCollision,
+            key,
+            specs: declarations.into_iter().map(|(index, operation)| to_conflicting_spec(&all_specs[index], operation)).collect(),
+        })
+        .collect()
+}
+
+/// the paths of all the `servers[].url` (OpenAPI) or the `basePath` (Swagger), e.g. `https://api.company.com/v1/pets?debug` -> `/v1/pets`
+fn get_base_paths(document: &serde_yaml::Value) -> Vec<String> {
+    let urls: Vec<&str> = match get(document, "swagger") {
+        Some(_) => get(document, "basePath").and_then(|base_path| base_path.as_str()).into_iter().collect(),
+        None => get(document, "servers")
+            .and_then(|servers| servers.as_sequence())
+            .map(|servers| servers.iter().filter_map(|server| get(server, "url").and_then(|url| url.as_str())).collect())
+            .unwrap_or_default(),
+    };
+
+    urls.into_iter()
+        .map(|url| {
+            //the host may hold server variables, e.g. `https://{env}.company.com/v1`, hence not parsed as an URL
+            let path = match url.split_once("://") {
+                Some((_scheme, authority_and_path)) => authority_and_path.find('/').map_or("/", |index| &authority_and_path[index..]),
+                None => url,
+            };
+            let end = path.find(['?', '#']).unwrap_or(path.len());
+
+            String::from(&path[..end])
+        })
+        .collect()
+}
+
+/// e.g. `/v1/pets//{petId}/` -> `/v1/pets/{}`
+fn normalize(path: &str) -> String {
+    let segments: Vec<String> = path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| PATH_PARAMETER.replace_all(segment, "{}").to_string())
+        .collect();
+
+    format!("/{}", segments.join("/"))
+}
+
+fn get_duplicate_api_ids(all_specs: &[SpecItem]) -> Vec<Conflict> {
+    let mut specs_per_api_id: BTreeMap<String, Vec<&SpecItem>> = BTreeMap::new();
+    for spec in all_specs {
+        let api_id = spec.get_api_id();
+        //not declared
+        if api_id.is_empty() || api_id == "0" {
+            continue;
+        }
+        specs_per_api_id.entry(api_id).or_default().push(spec);
+    }
+
+    specs_per_api_id.into_iter()
+        //the versions of an API share its x-api-id
+        .filter(|(_api_id, specs)| specs.iter().map(|spec| spec.get_title()).collect::<BTreeSet<String>>().len() > 1)
+        .map(|(api_id, specs)| Conflict {
+            kind: ConflictKind::DuplicateApiId,
+            key: api_id,
+            specs: specs.into_iter().map(|spec| to_conflicting_spec(spec, spec.get_title())).collect(),
+        })
+        .collect()
+}
+
+fn get_diverging_versions(all_specs: &[SpecItem]) -> Vec<Conflict> {
+    let mut specs_per_title: BTreeMap<String, Vec<&SpecItem>> = BTreeMap::new();
+    for spec in all_specs {
+        let title = spec.get_title();
+        if title.trim().is_empty() {
+            continue;
+        }
+        specs_per_title.entry(String::from(title.trim())).or_default().push(spec);
+    }
+
+    specs_per_title.into_iter()
+        .filter(|(_title, specs)| specs.iter().map(|spec| spec.get_version()).collect::<BTreeSet<String>>().len() > 1)
+        .map(|(title, specs)| Conflict {
+            kind: ConflictKind::DivergingVersions,
+            key: title,
+            specs: specs.into_iter().map(|spec| to_conflicting_spec(spec, spec.get_version())).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::ConflictKind;
+
+    fn spec(file_path: &str, catalog_id: &str, content: &str) -> crate::app::dao::catalog::spec::SpecItem {
+        crate::app::dao::catalog::spec::from_str(String::from(file_path), String::from(catalog_id), String::from("/specs"), content).unwrap()
+    }
+
+    const PETS: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.0.0
+  x-api-id: "42"
+servers:
+  - url: https://baseurl/v1/animals
+paths:
+  /pets/{petId}:
+    get:
+      responses:
+        '200':
+          description: ok
+    delete:
+      responses:
+        '204':
+          description: deleted
+"#;
+
+    const PETS_MIRROR: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.1.0
+  x-api-id: "42"
+servers:
+  - url: /v1/animals/
+paths:
+  /pets/{id}:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    const OWNERS: &str = r#"
+swagger: "2.0"
+info:
+  title: owners
+  version: 1.0.0
+  x-api-id: "42"
+basePath: /v1/animals/pets
+paths:
+  /{petId}:
+    delete:
+      responses:
+        '204':
+          description: deleted
+  /{petId}/owners:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    const PETS_SERVERS: &str = r#"
+openapi: 3.0.0
+info:
+  title: pet shop
+  version: 1.0.0
+servers:
+  - url: https://api-internal.company.com/v2/shop
+  - url: https://{env}.company.com/v1/animals/?debug=true
+    variables:
+      env:
+        default: api
+paths:
+  /pets/{petId}:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    const NO_SERVERS: &str = r#"
+openapi: 3.0.0
+info:
+  title: cats
+  version: 1.0.0
+paths:
+  /pets/{petId}:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    #[test]
+    fn test_list_conflicts() {
+        let specs = vec![
+            spec("/specs/pets-openapi.yaml", "catalog-1", PETS),
+            spec("/specs/mirror/pets-openapi.yaml", "catalog-2", PETS_MIRROR),
+            spec("/specs/owners-swagger.yaml", "catalog-1", OWNERS),
+            spec("/specs/cats-openapi.yaml", "catalog-2", NO_SERVERS),
+        ];
+
+        let conflicts = super::list_conflicts(&specs);
+        let summary: Vec<(ConflictKind, &str, Vec<&str>)> = conflicts.iter()
+            .map(|conflict| (conflict.kind, conflict.key.as_str(), conflict.specs.iter().map(|spec| spec.detail.as_str()).collect()))
+            .collect();
+        assert_eq!(summary, vec![
+            (ConflictKind::PathCollision, "DELETE /v1/animals/pets/{}", vec![ "DELETE /pets/{petId}", "DELETE /{petId}" ]),
+            (ConflictKind::PathCollision, "GET /v1/animals/pets/{}", vec![ "GET /pets/{petId}", "GET /pets/{id}" ]),
+            (ConflictKind::DuplicateApiId, "42", vec![ "pets", "pets", "owners" ]),
+            (ConflictKind::DivergingVersions, "pets", vec![ "1.0.0", "1.1.0" ]),
+        ]);
+        assert_eq!(conflicts[1].specs[1].catalog_id, "catalog-2");
+        assert_eq!(conflicts[3].get_message(), "[pets] has diverging versions [/pets-openapi.yaml (1.0.0), /mirror/pets-openapi.yaml (1.1.0)]");
+
+        let errors = super::to_errors(&conflicts);
+        assert_eq!(errors.len(), 9);
+        assert!(errors.iter().all(|error| error.category == crate::app::dao::catalog::ErrorCategory::Conflict));
+        assert_eq!(errors[0].file_path, "\"/specs/pets-openapi.yaml\"");
+        assert!(errors[0].reason.starts_with("[path-collision] [DELETE /v1/animals/pets/{}] is declared by "));
+
+        //a spec does not conflict with itself
+        assert!(super::list_conflicts(&specs[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_path_collisions_on_all_servers() {
+        let specs = vec![
+            spec("/specs/pets-openapi.yaml", "catalog-1", PETS),
+            spec("/specs/shop-openapi.yaml", "catalog-2", PETS_SERVERS),
+        ];
+
+        let conflicts = super::get_path_collisions(&specs);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].key, "GET /v1/animals/pets/{}");
+        assert_eq!(conflicts[0].specs.iter().map(|spec| spec.catalog_id.as_str()).collect::<Vec<&str>>(), vec![ "catalog-1", "catalog-2" ]);
+
+        let document: serde_yaml::Value = serde_yaml::from_str(PETS_SERVERS).unwrap();
+        assert_eq!(super::get_base_paths(&document), vec![ "/v2/shop", "/v1/animals/" ]);
+        let document: serde_yaml::Value = serde_yaml::from_str(OWNERS).unwrap();
+        assert_eq!(super::get_base_paths(&document), vec![ "/v1/animals/pets" ]);
+        let document: serde_yaml::Value = serde_yaml::from_str(NO_SERVERS).unwrap();
+        assert!(super::get_base_paths(&document).is_empty());
+    }
+}
@@ -0,0 +1,925 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use log::{debug, info};
+use serde_yaml::Value;
+use strum_macros::{Display, EnumString};
+
+use super::handlers::SpecType;
+use super::spec::SpecItem;
+use crate::shared::settings::Catalog;
+
+/// How a change impacts the consumers of the spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(EnumString, Display)]
+pub enum ChangeLevel {
+    /// not backward compatible, e.g. a removed response property or a new required parameter
+    #[strum(serialize = "breaking")]
+    Breaking,
+    /// backward compatible, e.g. a new endpoint or a new optional parameter
+    #[strum(serialize = "non-breaking")]
+    NonBreaking,
+    /// documentation only, e.g. a description or an `x-` extension
+    #[strum(serialize = "annotation")]
+    Annotation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub level: ChangeLevel,
+    /// e.g. `GET /pets > response 200 > application/json > body.items.name`
+    pub location: String,
+    pub message: String,
+}
+
+/// The changes of a spec between two revisions of a catalog
+#[derive(Debug, Clone)]
+pub struct SpecDiff {
+    pub spec_path: String,
+    pub changes: Vec<Change>,
+}
+
+/// What is sent to the API (request, published message) can be widened, what is received from it (response,
+/// subscribed message) can be narrowed - and the other way around for breaking changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Request,
+    Response,
+}
+
+pub(super) const HTTP_METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+/// schemas nested deeper are not compared
+const MAX_DEPTH: usize = 32;
+const MAX_REF_HOPS: usize = 16;
+
+/// Compares two revisions of a spec. OpenAPI (3.0, 3.1), Swagger 2 and AsyncAPI (2, 3) specs are supported
+pub fn diff(old: &SpecItem, new: &SpecItem) -> Result<Vec<Change>, String> {
+    let (old_document, new_document) = match (old.get_document(), new.get_document()) {
+        (Some(old_document), Some(new_document)) => (old_document, new_document),
+        _ => return Err( format!("Unable to compare [{}] with [{}] specs", old.get_spec_type(), new.get_spec_type()) ),
+    };
+
+    let mut differ = Differ { old_root: &old_document, new_root: &new_document, changes: Vec::new(), refs: Vec::new() };
+    match (old.get_spec_type(), new.get_spec_type()) {
+        (SpecType::OpenAPIv3 | SpecType::OpenAPIv31, SpecType::OpenAPIv3 | SpecType::OpenAPIv31) | (SpecType::Swagger2, SpecType::Swagger2) => differ.diff_openapi(),
+        (SpecType::AsyncAPIv2, SpecType::AsyncAPIv2) => differ.diff_asyncapi_v2(),
+        (SpecType::AsyncAPIv3, SpecType::AsyncAPIv3) => differ.diff_asyncapi_v3(),
+        (old_type, new_type) if old_type == new_type => return Err( format!("Unable to compare [{}] specs", old_type) ),
+        (old_type, new_type) => differ.push(ChangeLevel::Breaking, "spec", format!("grammar changed from [{}] to [{}]", old_type, new_type)),
+    };
+
+    Ok(differ.changes)
+}
+
+/// Compares the specs of the catalog between two git references (branches, tags or commits).
+/// Only the specs with changes are returned, sorted by path. A spec which cannot be parsed at `to` is breaking,
+/// one which cannot be parsed at `from` only is reported without being compared.
+pub fn diff_refs(catalog: &Catalog, from: &str, to: &str) -> Result<Vec<SpecDiff>, String> {
+    let from_specs = load_specs_at(catalog, from)?;
+    let to_specs = load_specs_at(catalog, to)?;
+
+    let spec_paths: BTreeSet<&String> = from_specs.keys().chain(to_specs.keys()).collect();
+    let mut spec_diffs = Vec::new();
+    for spec_path in spec_paths {
+        let changes = match (from_specs.get(spec_path), to_specs.get(spec_path)) {
+            //already unparsable
+            (Some(Err(_)), Some(Err(_))) => continue,
+            (_, Some(Err(why))) => vec![ Change { level: ChangeLevel::Breaking, location: String::from("spec"), message: format!("spec unparsable at [{}] - {}", to, why) } ],
+            (Some(Err(why)), _) => vec![ Change { level: ChangeLevel::NonBreaking, location: String::from("spec"), message: format!("spec unparsable at [{}], not compared - {}", from, why) } ],
+            (Some(Ok(_)), None) => vec![ Change { level: ChangeLevel::Breaking, location: String::from("spec"), message: String::from("spec removed") } ],
+            (None, Some(Ok(_))) => vec![ Change { level: ChangeLevel::NonBreaking, location: String::from("spec"), message: String::from("spec added") } ],
+            (Some(Ok(old)), Some(Ok(new))) => match diff(old, new) {
+                Ok(changes) => changes,
+                Err(why) => {
+                    debug!("Spec [{:?}] is not compared - {}", spec_path, why);
+                    continue;
+                }
+            },
+            (None, None) => continue,
+        };
+
+        if !changes.is_empty() {
+            spec_diffs.push(SpecDiff { spec_path: String::from(spec_path), changes });
+        }
+    }
+    info!("Compared catalog [{:?}] between [{:?}] and [{:?}] - [{:?}] specs changed", catalog.catalog_id, from, to, spec_diffs.len());
+
+    Ok(spec_diffs)
+}
+
+/// the specs of the catalog as of `reference` (or why they cannot be parsed), per path (as named by `/v1/specs`)
+fn load_specs_at(catalog: &Catalog, reference: &str) -> Result<BTreeMap<String, Result<SpecItem, String>>, String> {
+    let target = std::env::temp_dir().join(format!("apis-catalog-diff-{}", uuid::Uuid::new_v4()));
+    let checked_out = super::history::checkout(catalog, reference, &target);
+    let specs = checked_out.map(|_files| {
+        let checked_out_catalog = Catalog {
+            catalog_path: String::from(target.to_str().unwrap()),
+            catalog_dir: String::from(target.to_str().unwrap()),
+            ..catalog.clone()
+        };
+
+        globwalk::GlobWalkerBuilder::from_patterns(&target, &catalog.catalog_include_glob_pattern)
+            .build()
+            .map(|walker| walker.filter_map(Result::ok).collect::<Vec<globwalk::DirEntry>>())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|entry| {
+                let spec = super::read_spec_file(&checked_out_catalog, entry.path());
+                //named as the spec of the working copy
+                let file_path = String::from(std::path::Path::new(&catalog.catalog_path).join(entry.path().strip_prefix(&target).ok()?).to_str()?);
+                let spec_path = String::from(super::spec::extact_relative_path(&file_path, &catalog.catalog_dir));
+                Some((spec_path, spec))
+            })
+            .collect()
+    });
+    let _ = std::fs::remove_dir_all(&target);
+
+    specs
+}
+
+struct Differ<'a> {
+    old_root: &'a Value,
+    new_root: &'a Value,
+    changes: Vec<Change>,
+    /// `$ref`s being compared, to stop on recursive schemas
+    refs: Vec<(String, String)>,
+}
+
+impl<'a> Differ<'a> {
+    fn push(&mut self, level: ChangeLevel, location: &str, message: String) {
+        self.changes.push(Change { level, location: String::from(location), message });
+    }
+
+    fn diff_info(&mut self) {
+        let (old_info, new_info) = (get(self.old_root, "info"), get(self.new_root, "info"));
+        if let (Some(old_version), Some(new_version)) = (old_info.and_then(|info| get(info, "version")), new_info.and_then(|info| get(info, "version"))) {
+            if old_version != new_version {
+                self.push(ChangeLevel::Annotation, "info", format!("version changed from [{}] to [{}]", to_string(old_version), to_string(new_version)));
+            }
+        }
+        if let (Some(old_info), Some(new_info)) = (old_info, new_info) {
+            self.diff_annotations("info", old_info, new_info, &["title", "description", "contact", "license", "termsOfService"]);
+        }
+    }
+
+    /// documentation attributes and `x-` extensions
+    fn diff_annotations(&mut self, location: &str, old: &Value, new: &Value, attributes: &[&str]) {
+        for attribute in attributes {
+            if get(old, attribute) != get(new, attribute) {
+                self.push(ChangeLevel::Annotation, location, format!("[{}] changed", attribute));
+            }
+        }
+
+        let extensions: BTreeSet<String> = keys(old).into_iter().chain(keys(new)).filter(|key| key.starts_with("x-")).collect();
+        for extension in extensions {
+            if get(old, &extension) != get(new, &extension) {
+                self.push(ChangeLevel::Annotation, location, format!("extension [{}] changed", extension));
+            }
+        }
+
+        if !is_true(get(old, "deprecated")) && is_true(get(new, "deprecated")) {
+            self.push(ChangeLevel::Annotation, location, String::from("deprecated"));
+        }
+    }
+
+    /*
+     * OpenAPI / Swagger
+     */
+
+    fn diff_openapi(&mut self) {
+        self.diff_info();
+
+        let old_paths = entries(get(self.old_root, "paths"));
+        let new_paths = entries(get(self.new_root, "paths"));
+        for (path, old_item) in &old_paths {
+            match new_paths.get(path) {
+                Some(new_item) => self.diff_path_item(path, old_item, new_item),
+                None => self.push(ChangeLevel::Breaking, path, String::from("path removed")),
+            }
+        }
+        for path in new_paths.keys().filter(|path| !old_paths.contains_key(*path)) {
+            self.push(ChangeLevel::NonBreaking, path, String::from("path added"));
+        }
+    }
+
+    fn diff_path_item(&mut self, path: &str, old_item: &'a Value, new_item: &'a Value) {
+        let old_item = resolve(self.old_root, old_item);
+        let new_item = resolve(self.new_root, new_item);
+
+        for method in HTTP_METHODS {
+            let location = format!("{} {}", method.to_uppercase(), path);
+            match (get(old_item, method), get(new_item, method)) {
+                (Some(_), None) => self.push(ChangeLevel::Breaking, &location, String::from("operation removed")),
+                (None, Some(_)) => self.push(ChangeLevel::NonBreaking, &location, String::from("operation added")),
+                (Some(old_operation), Some(new_operation)) => {
+                    self.diff_annotations(&location, old_operation, new_operation, &["summary", "description", "operationId", "tags"]);
+
+                    let old_parameters = self.get_parameters(self.old_root, old_item, old_operation);
+                    let new_parameters = self.get_parameters(self.new_root, new_item, new_operation);
+                    self.diff_parameters(&location, &old_parameters, &new_parameters);
+
+                    self.diff_request_body(&location, get(old_operation, "requestBody"), get(new_operation, "requestBody"));
+                    self.diff_responses(&location, get(old_operation, "responses"), get(new_operation, "responses"));
+                },
+                (None, None) => {},
+            };
+        }
+    }
+
+    /// parameters of the path item, overridden by the ones of the operation - per `<in>.<name>`
+    fn get_parameters(&self, root: &'a Value, item: &'a Value, operation: &'a Value) -> BTreeMap<String, &'a Value> {
+        let mut parameters = BTreeMap::new();
+        for parameters_value in [get(item, "parameters"), get(operation, "parameters")].iter().flatten() {
+            for parameter in parameters_value.as_sequence().into_iter().flatten() {
+                let parameter = resolve(root, parameter);
+                let name = format!("{}.{}", get(parameter, "in").map(to_string).unwrap_or_default(), get(parameter, "name").map(to_string).unwrap_or_default());
+                parameters.insert(name, parameter);
+            }
+        }
+
+        parameters
+    }
+
+    fn diff_parameters(&mut self, location: &str, old_parameters: &BTreeMap<String, &'a Value>, new_parameters: &BTreeMap<String, &'a Value>) {
+        for (name, old_parameter) in old_parameters {
+            let parameter_location = format!("{} > parameter {}", location, name);
+            let new_parameter = match new_parameters.get(name) {
+                Some(new_parameter) => new_parameter,
+                None => {
+                    self.push(ChangeLevel::Breaking, &parameter_location, String::from("parameter removed"));
+                    continue;
+                }
+            };
+
+            match (is_true(get(old_parameter, "required")), is_true(get(new_parameter, "required"))) {
+                (false, true) => self.push(ChangeLevel::Breaking, &parameter_location, String::from("parameter became required")),
+                (true, false) => self.push(ChangeLevel::NonBreaking, &parameter_location, String::from("parameter became optional")),
+                _ => {},
+            };
+
+            //swagger parameters (but the body) hold their schema, and so their description
+            match (get(old_parameter, "schema"), get(new_parameter, "schema")) {
+                (Some(old_schema), Some(new_schema)) => {
+                    self.diff_annotations(&parameter_location, old_parameter, new_parameter, &["description"]);
+                    self.diff_schema(&parameter_location, "", old_schema, new_schema, Direction::Request, 0);
+                },
+                _ => self.diff_schema(&parameter_location, "", old_parameter, new_parameter, Direction::Request, 0),
+            };
+        }
+
+        for (name, new_parameter) in new_parameters.iter().filter(|(name, _parameter)| !old_parameters.contains_key(*name)) {
+            let parameter_location = format!("{} > parameter {}", location, name);
+            match is_true(get(new_parameter, "required")) {
+                true => self.push(ChangeLevel::Breaking, &parameter_location, String::from("new required parameter")),
+                false => self.push(ChangeLevel::NonBreaking, &parameter_location, String::from("parameter added")),
+            };
+        }
+    }
+
+    fn diff_request_body(&mut self, location: &str, old_body: Option<&'a Value>, new_body: Option<&'a Value>) {
+        let location = format!("{} > request body", location);
+        match (old_body.map(|body| resolve(self.old_root, body)), new_body.map(|body| resolve(self.new_root, body))) {
+            (Some(_), None) => self.push(ChangeLevel::Breaking, &location, String::from("request body removed")),
+            (None, Some(new_body)) => match is_true(get(new_body, "required")) {
+                true => self.push(ChangeLevel::Breaking, &location, String::from("new required request body")),
+                false => self.push(ChangeLevel::NonBreaking, &location, String::from("request body added")),
+            },
+            (Some(old_body), Some(new_body)) => {
+                if !is_true(get(old_body, "required")) && is_true(get(new_body, "required")) {
+                    self.push(ChangeLevel::Breaking, &location, String::from("request body became required"));
+                }
+                self.diff_content(&location, old_body, new_body, Direction::Request);
+            },
+            (None, None) => {},
+        };
+    }
+
+    fn diff_responses(&mut self, location: &str, old_responses: Option<&'a Value>, new_responses: Option<&'a Value>) {
+        let old_responses = entries(old_responses);
+        let new_responses = entries(new_responses);
+
+        for (code, old_response) in &old_responses {
+            let response_location = format!("{} > response {}", location, code);
+            match new_responses.get(code) {
+                Some(new_response) => {
+                    let old_response = resolve(self.old_root, old_response);
+                    let new_response = resolve(self.new_root, new_response);
+                    self.diff_annotations(&response_location, old_response, new_response, &["description"]);
+                    self.diff_content(&response_location, old_response, new_response, Direction::Response);
+                },
+                //clients rely on the success responses
+                None if code.starts_with('2') || code.starts_with('3') => self.push(ChangeLevel::Breaking, &response_location, String::from("response removed")),
+                None => self.push(ChangeLevel::NonBreaking, &response_location, String::from("response removed")),
+            };
+        }
+        for code in new_responses.keys().filter(|code| !old_responses.contains_key(*code)) {
+            self.push(ChangeLevel::NonBreaking, &format!("{} > response {}", location, code), String::from("response added"));
+        }
+    }
+
+    /// schemas per media type (OpenAPI 3) or the schema (Swagger 2) of a request body or a response
+    fn diff_content(&mut self, location: &str, old: &'a Value, new: &'a Value, direction: Direction) {
+        if let (Some(old_schema), Some(new_schema)) = (get(old, "schema"), get(new, "schema")) {
+            self.diff_schema(location, "body", old_schema, new_schema, direction, 0);
+        }
+
+        let old_content = entries(get(old, "content"));
+        let new_content = entries(get(new, "content"));
+        for (media_type, old_media) in &old_content {
+            let media_location = format!("{} > {}", location, media_type);
+            match new_content.get(media_type) {
+                Some(new_media) => {
+                    if let (Some(old_schema), Some(new_schema)) = (get(old_media, "schema"), get(new_media, "schema")) {
+                        self.diff_schema(&media_location, "body", old_schema, new_schema, direction, 0);
+                    }
+                },
+                None => self.push(ChangeLevel::Breaking, &media_location, String::from("media type removed")),
+            };
+        }
+        for media_type in new_content.keys().filter(|media_type| !old_content.contains_key(*media_type)) {
+            self.push(ChangeLevel::NonBreaking, &format!("{} > {}", location, media_type), String::from("media type added"));
+        }
+    }
+
+    /*
+     * Schemas
+     */
+
+    fn diff_schema(&mut self, location: &str, name: &str, old: &Value, new: &Value, direction: Direction, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        //recursive schemas are compared once
+        let refs = (get(old, "$ref").map(to_string).unwrap_or_default(), get(new, "$ref").map(to_string).unwrap_or_default());
+        let is_ref = !refs.0.is_empty() || !refs.1.is_empty();
+        if is_ref {
+            if self.refs.contains(&refs) {
+                return;
+            }
+            self.refs.push(refs);
+        }
+
+        let old = merge_all_of(self.old_root, resolve(self.old_root, old));
+        let new = merge_all_of(self.new_root, resolve(self.new_root, new));
+        self.diff_resolved_schema(location, name, &old, &new, direction, depth);
+
+        if is_ref {
+            self.refs.pop();
+        }
+    }
+
+    fn diff_resolved_schema(&mut self, location: &str, name: &str, old: &Value, new: &Value, direction: Direction, depth: usize) {
+        let schema_location = match name.is_empty() {
+            true => String::from(location),
+            false => format!("{} > {}", location, name),
+        };
+
+        let (old_types, new_types) = (get_types(old), get_types(new));
+        if !old_types.is_empty() && !new_types.is_empty() && old_types != new_types {
+            self.push(ChangeLevel::Breaking, &schema_location, format!("type changed from [{}] to [{}]", join(&old_types), join(&new_types)));
+            return;
+        }
+        if let (Some(old_format), Some(new_format)) = (get(old, "format"), get(new, "format")) {
+            if old_format != new_format {
+                self.push(ChangeLevel::Breaking, &schema_location, format!("format changed from [{}] to [{}]", to_string(old_format), to_string(new_format)));
+            }
+        }
+        self.diff_annotations(&schema_location, old, new, &["description", "title", "example"]);
+        self.diff_enum(&schema_location, old, new, direction);
+
+        //properties
+        let old_properties = entries(get(old, "properties"));
+        let new_properties = entries(get(new, "properties"));
+        let old_required = get_required(old);
+        let new_required = get_required(new);
+        for (property, old_property) in &old_properties {
+            let property_name = join_name(name, property);
+            match new_properties.get(property) {
+                Some(new_property) => {
+                    match direction {
+                        Direction::Request if !old_required.contains(property) && new_required.contains(property) => {
+                            self.push(ChangeLevel::Breaking, &format!("{} > {}", location, property_name), String::from("property became required"));
+                        },
+                        Direction::Response if old_required.contains(property) && !new_required.contains(property) => {
+                            self.push(ChangeLevel::Breaking, &format!("{} > {}", location, property_name), String::from("property became optional"));
+                        },
+                        _ => {},
+                    };
+                    self.diff_schema(location, &property_name, old_property, new_property, direction, depth + 1);
+                },
+                None => self.push(ChangeLevel::Breaking, &format!("{} > {}", location, property_name), String::from("property removed")),
+            };
+        }
+        for property in new_properties.keys().filter(|property| !old_properties.contains_key(*property)) {
+            let property_location = format!("{} > {}", location, join_name(name, property));
+            match direction == Direction::Request && new_required.contains(property) {
+                true => self.push(ChangeLevel::Breaking, &property_location, String::from("new required property")),
+                false => self.push(ChangeLevel::NonBreaking, &property_location, String::from("property added")),
+            };
+        }
+
+        //arrays
+        if let (Some(old_items), Some(new_items)) = (get(old, "items"), get(new, "items")) {
+            self.diff_schema(location, &format!("{}[]", name), old_items, new_items, direction, depth + 1);
+        }
+
+        //alternatives
+        for keyword in ["oneOf", "anyOf"] {
+            let old_alternatives = get(old, keyword).and_then(Value::as_sequence).map(Vec::as_slice).unwrap_or_default();
+            let new_alternatives = get(new, keyword).and_then(Value::as_sequence).map(Vec::as_slice).unwrap_or_default();
+            if new_alternatives.len() > old_alternatives.len() {
+                let level = widened(direction);
+                self.push(level, &schema_location, format!("[{}] alternatives added", keyword));
+            } else if new_alternatives.len() < old_alternatives.len() {
+                let level = narrowed(direction);
+                self.push(level, &schema_location, format!("[{}] alternatives removed", keyword));
+            }
+            for (index, (old_alternative, new_alternative)) in old_alternatives.iter().zip(new_alternatives).enumerate() {
+                self.diff_schema(location, &format!("{}<{}:{}>", name, keyword, index), old_alternative, new_alternative, direction, depth + 1);
+            }
+        }
+    }
+
+    fn diff_enum(&mut self, location: &str, old: &Value, new: &Value, direction: Direction) {
+        let old_values = get(old, "enum").and_then(Value::as_sequence).map(|values| values.iter().map(to_string).collect::<BTreeSet<String>>());
+        let new_values = get(new, "enum").and_then(Value::as_sequence).map(|values| values.iter().map(to_string).collect::<BTreeSet<String>>());
+
+        match (old_values, new_values) {
+            (Some(old_values), Some(new_values)) => {
+                let removed: BTreeSet<String> = old_values.difference(&new_values).cloned().collect();
+                let added: BTreeSet<String> = new_values.difference(&old_values).cloned().collect();
+                if !removed.is_empty() {
+                    self.push(narrowed(direction), location, format!("enum values removed [{}]", join(&removed)));
+                }
+                if !added.is_empty() {
+                    self.push(widened(direction), location, format!("enum values added [{}]", join(&added)));
+                }
+            },
+            (None, Some(_)) => self.push(narrowed(direction), location, String::from("enum added")),
+            (Some(_), None) => self.push(widened(direction), location, String::from("enum removed")),
+            (None, None) => {},
+        };
+    }
+
+    /*
+     * AsyncAPI
+     */
+
+    /// `subscribe` operations are what the application sends, `publish` ones what it receives
+    fn diff_asyncapi_v2(&mut self) {
+        self.diff_info();
+
+        let old_channels = entries(get(self.old_root, "channels"));
+        let new_channels = entries(get(self.new_root, "channels"));
+        for (channel, old_channel) in &old_channels {
+            let channel_location = format!("channel {}", channel);
+            let new_channel = match new_channels.get(channel) {
+                Some(new_channel) => new_channel,
+                None => {
+                    self.push(ChangeLevel::Breaking, &channel_location, String::from("channel removed"));
+                    continue;
+                }
+            };
+
+            for (operation, direction) in [("subscribe", Direction::Response), ("publish", Direction::Request)] {
+                let location = format!("{} > {}", channel_location, operation);
+                match (get(old_channel, operation), get(new_channel, operation)) {
+                    (Some(_), None) => self.push(ChangeLevel::Breaking, &location, String::from("operation removed")),
+                    (None, Some(_)) => self.push(ChangeLevel::NonBreaking, &location, String::from("operation added")),
+                    (Some(old_operation), Some(new_operation)) => {
+                        self.diff_annotations(&location, old_operation, new_operation, &["summary", "description", "operationId"]);
+                        let old_messages = get_messages(self.old_root, get(old_operation, "message"));
+                        let new_messages = get_messages(self.new_root, get(new_operation, "message"));
+                        self.diff_messages(&location, &old_messages, &new_messages, direction);
+                    },
+                    (None, None) => {},
+                };
+            }
+        }
+        for channel in new_channels.keys().filter(|channel| !old_channels.contains_key(*channel)) {
+            self.push(ChangeLevel::NonBreaking, &format!("channel {}", channel), String::from("channel added"));
+        }
+    }
+
+    /// `send` operations are what the application sends, `receive` ones what it receives
+    fn diff_asyncapi_v3(&mut self) {
+        self.diff_info();
+
+        let old_operations = entries(get(self.old_root, "operations"));
+        let new_operations = entries(get(self.new_root, "operations"));
+        for (operation, old_operation) in &old_operations {
+            let location = format!("operation {}", operation);
+            match new_operations.get(operation) {
+                Some(new_operation) => {
+                    if get(old_operation, "action") != get(new_operation, "action") {
+                        self.push(ChangeLevel::Breaking, &location, String::from("action changed"));
+                    }
+                    if get(old_operation, "channel") != get(new_operation, "channel") {
+                        self.push(ChangeLevel::Breaking, &location, String::from("channel changed"));
+                    }
+                    self.diff_annotations(&location, old_operation, new_operation, &["summary", "description", "title"]);
+                },
+                None => self.push(ChangeLevel::Breaking, &location, String::from("operation removed")),
+            };
+        }
+        for operation in new_operations.keys().filter(|operation| !old_operations.contains_key(*operation)) {
+            self.push(ChangeLevel::NonBreaking, &format!("operation {}", operation), String::from("operation added"));
+        }
+
+        let old_channels = entries(get(self.old_root, "channels"));
+        let new_channels = entries(get(self.new_root, "channels"));
+        for (channel, old_channel) in &old_channels {
+            let location = format!("channel {}", channel);
+            let new_channel = match new_channels.get(channel) {
+                Some(new_channel) => resolve(self.new_root, new_channel),
+                None => {
+                    self.push(ChangeLevel::Breaking, &location, String::from("channel removed"));
+                    continue;
+                }
+            };
+            let old_channel = resolve(self.old_root, old_channel);
+            if get(old_channel, "address") != get(new_channel, "address") {
+                self.push(ChangeLevel::Breaking, &location, String::from("address changed"));
+            }
+
+            //what the application receives on this channel
+            let reference = format!("#/channels/{}", channel.replace('~', "~0").replace('/', "~1"));
+            let direction = match new_operations.values().any(|operation| {
+                get(operation, "action").map(to_string).as_deref() == Some("receive")
+                    && get(operation, "channel").and_then(|channel| get(channel, "$ref")).map(to_string) == Some(reference.clone())
+            }) {
+                true => Direction::Request,
+                false => Direction::Response,
+            };
+
+            let old_messages = entries(get(old_channel, "messages")).into_iter().map(|(name, message)| (name, resolve(self.old_root, message))).collect();
+            let new_messages = entries(get(new_channel, "messages")).into_iter().map(|(name, message)| (name, resolve(self.new_root, message))).collect();
+            self.diff_messages(&location, &old_messages, &new_messages, direction);
+        }
+        for channel in new_channels.keys().filter(|channel| !old_channels.contains_key(*channel)) {
+            self.push(ChangeLevel::NonBreaking, &format!("channel {}", channel), String::from("channel added"));
+        }
+    }
+
+    fn diff_messages(&mut self, location: &str, old_messages: &BTreeMap<String, &'a Value>, new_messages: &BTreeMap<String, &'a Value>, direction: Direction) {
+        for (name, old_message) in old_messages {
+            let message_location = format!("{} > message {}", location, name);
+            match new_messages.get(name) {
+                Some(new_message) => {
+                    self.diff_annotations(&message_location, old_message, new_message, &["summary", "description", "title"]);
+                    if let (Some(old_payload), Some(new_payload)) = (get(old_message, "payload"), get(new_message, "payload")) {
+                        self.diff_schema(&message_location, "payload", old_payload, new_payload, direction, 0);
+                    }
+                    if let (Some(old_headers), Some(new_headers)) = (get(old_message, "headers"), get(new_message, "headers")) {
+                        self.diff_schema(&message_location, "headers", old_headers, new_headers, direction, 0);
+                    }
+                },
+                None => self.push(narrowed(direction), &message_location, String::from("message removed")),
+            };
+        }
+        for name in new_messages.keys().filter(|name| !old_messages.contains_key(*name)) {
+            self.push(widened(direction), &format!("{} > message {}", location, name), String::from("message added"));
+        }
+    }
+}
+
+/// level of a change which accepts more values (e.g. enum values added)
+fn widened(direction: Direction) -> ChangeLevel {
+    match direction {
+        Direction::Request => ChangeLevel::NonBreaking,
+        Direction::Response => ChangeLevel::Breaking,
+    }
+}
+
+/// level of a change which accepts less values (e.g. enum values removed)
+fn narrowed(direction: Direction) -> ChangeLevel {
+    match direction {
+        Direction::Request => ChangeLevel::Breaking,
+        Direction::Response => ChangeLevel::NonBreaking,
+    }
+}
+
+/// messages of an AsyncAPI 2 operation, per name (or `messageId`, or position in `oneOf`)
+fn get_messages<'a>(root: &'a Value, message: Option<&'a Value>) -> BTreeMap<String, &'a Value> {
+    let message = match message {
+        Some(message) => resolve(root, message),
+        None => return BTreeMap::new(),
+    };
+
+    let messages: Vec<&Value> = match get(message, "oneOf").and_then(Value::as_sequence) {
+        Some(messages) => messages.iter().map(|message| resolve(root, message)).collect(),
+        None => vec![message],
+    };
+    messages.into_iter().enumerate().map(|(index, message)| {
+        let name = get(message, "name").or_else(|| get(message, "messageId")).map(to_string).unwrap_or_else(|| format!("#{}", index));
+        (name, message)
+    }).collect()
+}
+
+/*
+ * yaml helpers - mapping keys may not be strings (e.g. `200:` response codes)
+ */
+
+pub(super) fn get<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    value.as_mapping()?.iter().find(|(entry_key, _entry_value)| to_string(entry_key) == key).map(|(_entry_key, entry_value)| entry_value)
+}
+
+pub(super) fn entries(value: Option<&Value>) -> BTreeMap<String, &Value> {
+    value.and_then(Value::as_mapping).map(|mapping| mapping.iter().map(|(key, value)| (to_string(key), value)).collect()).unwrap_or_default()
+}
+
+fn keys(value: &Value) -> Vec<String> {
+    value.as_mapping().map(|mapping| mapping.keys().map(to_string).collect()).unwrap_or_default()
+}
+
+pub(super) fn to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => String::from(value),
+        Value::Number(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Null => String::from("null"),
+        value => serde_yaml::to_string(value).map(|value| String::from(value.trim())).unwrap_or_default(),
+    }
+}
+
+fn is_true(value: Option<&Value>) -> bool {
+    value.and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn join(values: &BTreeSet<String>) -> String {
+    values.iter().cloned().collect::<Vec<String>>().join(", ")
+}
+
+fn join_name(name: &str, property: &str) -> String {
+    match name.is_empty() {
+        true => String::from(property),
+        false => format!("{}.{}", name, property),
+    }
+}
+
+fn get_types(schema: &Value) -> BTreeSet<String> {
+    match get(schema, "type") {
+        Some(Value::Sequence(types)) => types.iter().map(to_string).collect(),
+        Some(schema_type) => BTreeSet::from([to_string(schema_type)]),
+        None => BTreeSet::new(),
+    }
+}
+
+fn get_required(schema: &Value) -> BTreeSet<String> {
+    get(schema, "required").and_then(Value::as_sequence).map(|required| required.iter().map(to_string).collect()).unwrap_or_default()
+}
+
+/// follows the local `$ref`s (e.g. `#/components/schemas/Pet`), the value itself is returned if it cannot be resolved
+fn resolve<'a>(root: &'a Value, value: &'a Value) -> &'a Value {
+    let mut resolved = value;
+    for _hop in 0..MAX_REF_HOPS {
+        let reference = match get(resolved, "$ref").and_then(Value::as_str) {
+            Some(reference) if reference.starts_with("#/") => reference,
+            _ => return resolved,
+        };
+
+        let target = reference[2..].split('/').try_fold(root, |value, token| get(value, &token.replace("~1", "/").replace("~0", "~")));
+        match target {
+            Some(target) => resolved = target,
+            None => return resolved,
+        };
+    }
+
+    resolved
+}
+
+/// the properties and required properties of the `allOf` sub schemas are merged into the schema
+fn merge_all_of(root: &Value, schema: &Value) -> Value {
+    let sub_schemas = match get(schema, "allOf").and_then(Value::as_sequence) {
+        Some(sub_schemas) => sub_schemas,
+        None => return schema.clone(),
+    };
+
+    let mut merged = schema.as_mapping().cloned().unwrap_or_default();
+    merged.remove("allOf");
+    let mut properties = get(schema, "properties").and_then(Value::as_mapping).cloned().unwrap_or_default();
+    let mut required: Vec<Value> = get(schema, "required").and_then(Value::as_sequence).cloned().unwrap_or_default();
+    for sub_schema in sub_schemas {
+        let sub_schema = merge_all_of(root, resolve(root, sub_schema));
+        if let Some(sub_properties) = get(&sub_schema, "properties").and_then(Value::as_mapping) {
+            properties.extend(sub_properties.clone());
+        }
+        if let Some(sub_required) = get(&sub_schema, "required").and_then(Value::as_sequence) {
+            required.extend(sub_required.iter().cloned());
+        }
+        if let (None, Some(sub_type)) = (merged.get("type"), get(&sub_schema, "type")) {
+            merged.insert(Value::from("type"), sub_type.clone());
+        }
+    }
+    if !properties.is_empty() {
+        merged.insert(Value::from("properties"), Value::Mapping(properties));
+    }
+    if !required.is_empty() {
+        merged.insert(Value::from("required"), Value::Sequence(required));
+    }
+
+    Value::Mapping(merged)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::ChangeLevel;
+    use crate::app::dao::catalog::scm::tests::{commit_file, init_bare_repo};
+    use crate::app::dao::catalog::spec::from_str;
+    use crate::shared::settings::Catalog;
+
+    const PETS_V1: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.0.0
+paths:
+  /pets:
+    get:
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            type: integer
+      responses:
+        200:
+          description: the pets
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: '#/components/schemas/Pet'
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+      responses:
+        '201':
+          description: created
+  /owners:
+    get:
+      responses:
+        '200':
+          description: the owners
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [ name ]
+      properties:
+        name:
+          type: string
+        kind:
+          type: string
+          enum: [ cat, dog ]
+        tag:
+          type: string
+        parent:
+          $ref: '#/components/schemas/Pet'
+"#;
+
+    const PETS_V2: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 2.0.0
+paths:
+  /pets:
+    get:
+      parameters:
+        - name: limit
+          in: query
+          required: true
+          schema:
+            type: integer
+        - name: offset
+          in: query
+          schema:
+            type: integer
+      responses:
+        200:
+          description: all the pets
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: '#/components/schemas/Pet'
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+      responses:
+        '201':
+          description: created
+  /stores:
+    get:
+      responses:
+        '200':
+          description: the stores
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [ name, kind ]
+      properties:
+        name:
+          type: string
+        kind:
+          type: string
+          enum: [ cat, dog, bird ]
+        parent:
+          $ref: '#/components/schemas/Pet'
+"#;
+
+    fn get_spec(content: &str) -> crate::app::dao::catalog::spec::SpecItem {
+        from_str(String::from("/specs/pets-openapi.yaml"), String::from("catalog"), String::from("/specs"), content).unwrap()
+    }
+
+    fn find<'a>(changes: &'a [super::Change], location: &str, message: &str) -> Option<&'a super::Change> {
+        changes.iter().find(|change| change.location == location && change.message.starts_with(message))
+    }
+
+    #[test]
+    fn test_diff_openapi() {
+        let changes = super::diff(&get_spec(PETS_V1), &get_spec(PETS_V2)).unwrap();
+
+        assert_eq!(find(&changes, "/owners", "path removed").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "/stores", "path added").unwrap().level, ChangeLevel::NonBreaking);
+        assert_eq!(find(&changes, "GET /pets > parameter query.limit", "parameter became required").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "GET /pets > parameter query.offset", "parameter added").unwrap().level, ChangeLevel::NonBreaking);
+        assert_eq!(find(&changes, "GET /pets > response 200", "[description] changed").unwrap().level, ChangeLevel::Annotation);
+        //the response can return a new value, the request accepts it
+        assert_eq!(find(&changes, "GET /pets > response 200 > application/json > body[].kind", "enum values added").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "POST /pets > request body > application/json > body.kind", "enum values added").unwrap().level, ChangeLevel::NonBreaking);
+        assert_eq!(find(&changes, "GET /pets > response 200 > application/json > body[].tag", "property removed").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "POST /pets > request body > application/json > body.kind", "property became required").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "info", "version changed from [1.0.0] to [2.0.0]").unwrap().level, ChangeLevel::Annotation);
+        //the recursive schema is compared once
+        assert!(find(&changes, "POST /pets > request body > application/json > body.parent.tag", "property removed").is_none());
+
+        assert!(super::diff(&get_spec(PETS_V1), &get_spec(PETS_V1)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_asyncapi() {
+        let v1 = "asyncapi: 2.6.0\ninfo:\n  title: pets\n  version: 1.0.0\nchannels:\n  pets:\n    subscribe:\n      message:\n        name: PetCreated\n        payload:\n          type: object\n          properties:\n            name:\n              type: string\n    publish:\n      message:\n        name: CreatePet\n        payload:\n          type: object\n          properties:\n            name:\n              type: string\n";
+        let v2 = "asyncapi: 2.6.0\ninfo:\n  title: pets\n  version: 1.0.0\nchannels:\n  pets:\n    subscribe:\n      message:\n        name: PetCreated\n        payload:\n          type: object\n          properties:\n            name:\n              type: integer\n    publish:\n      message:\n        name: CreatePet\n        payload:\n          type: object\n          required: [ age ]\n          properties:\n            name:\n              type: string\n            age:\n              type: integer\n  owners:\n    subscribe:\n      message:\n        payload:\n          type: object\n";
+        let changes = super::diff(&get_spec(v1), &get_spec(v2)).unwrap();
+
+        assert_eq!(changes.len(), 3);
+        assert_eq!(find(&changes, "channel pets > subscribe > message PetCreated > payload.name", "type changed from [string] to [integer]").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "channel pets > publish > message CreatePet > payload.age", "new required property").unwrap().level, ChangeLevel::Breaking);
+        assert_eq!(find(&changes, "channel owners", "channel added").unwrap().level, ChangeLevel::NonBreaking);
+
+        //another grammar
+        let changes = super::diff(&get_spec(v1), &get_spec(PETS_V1)).unwrap();
+        assert_eq!(changes[0].level, ChangeLevel::Breaking);
+    }
+
+    #[test]
+    fn test_diff_refs() {
+        let (bare_path, bare) = init_bare_repo();
+        commit_file(&bare, "main", "cats-openapi.yaml", PETS_V1);
+        let from = commit_file(&bare, "main", "pets-openapi.yaml", PETS_V1);
+        commit_file(&bare, "main", "owners-openapi.yaml", PETS_V1);
+        commit_file(&bare, "main", "pets-openapi.yaml", PETS_V2);
+        commit_file(&bare, "main", "cats-openapi.yaml", "openapi: 3.0.0\ninfo: [\n");
+
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-diff-{}", uuid::Uuid::new_v4()));
+        let catalog = Catalog {
+            catalog_id: format!("{}", uuid::Uuid::new_v4()),
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_dir: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_include_glob_pattern: vec![String::from("**/*-openapi.yaml")],
+            catalog_scm_url: format!("file://{}", bare_path.to_str().unwrap()),
+            ..Default::default()
+        };
+        crate::app::dao::catalog::scm::sync(&catalog).unwrap();
+
+        let spec_diffs = super::diff_refs(&catalog, &from.to_string(), "HEAD").unwrap();
+        assert_eq!(spec_diffs.len(), 3);
+        //not reported as removed
+        assert_eq!(spec_diffs[0].spec_path, "/cats-openapi.yaml");
+        assert_eq!(spec_diffs[0].changes.len(), 1);
+        assert!(spec_diffs[0].changes[0].message.starts_with("spec unparsable at [HEAD]"));
+        assert_eq!(spec_diffs[0].changes[0].level, ChangeLevel::Breaking);
+        assert_eq!(spec_diffs[1].spec_path, "/owners-openapi.yaml");
+        assert_eq!(spec_diffs[1].changes[0].message, "spec added");
+        assert_eq!(spec_diffs[2].spec_path, "/pets-openapi.yaml");
+        assert!(spec_diffs[2].changes.iter().any(|change| change.level == ChangeLevel::Breaking));
+
+        //nor as added
+        let spec_diffs = super::diff_refs(&catalog, "HEAD", &from.to_string()).unwrap();
+        assert!(spec_diffs[0].changes[0].message.starts_with("spec unparsable at [HEAD], not compared"));
+
+        assert!(super::diff_refs(&catalog, "not-a-reference", "HEAD").is_err());
+
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+        std::fs::remove_dir_all(&bare_path).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use log::warn;
+use regex::Regex;
 
-use crate::app::dao::catalog::handlers::{SpecHandler, Method, Path};
+use crate::app::dao::catalog::handlers::{SpecFormat, SpecType, Method, Path};
 
 #[derive(Debug, Clone)]
 pub struct V2 {
@@ -8,6 +9,14 @@ pub struct V2 {
 }
 
 impl V2 {
+    pub fn format() -> SpecFormat {
+        SpecFormat {
+            spec_type: SpecType::AsyncAPIv2,
+            detect: |document| document.get_root_attribute("asyncapi").is_some_and(|version| version.starts_with("2.")),
+            build: |content| Ok( Box::new(V2::new(content)?) ),
+        }
+    }
+
     pub fn new(val: &str) -> Result<Self, String> {
         Ok( Self { spec: String::from(val) } )
     }
@@ -59,34 +68,49 @@ impl crate::app::dao::catalog::handlers::SpecHandler for V2{
         len  
     }
 
+    /// Each `channel` is exposed as a `Path` and its `publish` / `subscribe` operations as `Method`s
     fn get_paths(&self) -> Vec<crate::app::dao::catalog::handlers::Path> {
         let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
         let channels: &serde_yaml::Value = &spec_as_yaml["channels"];
 
+        let mut all_paths: Vec<Path> = Vec::new();
+
         match channels.as_mapping(){
             Some(val) => {
                 for (key, value) in val{
-                    println!("got {:?} - {:?}", key, value);
+                    let mut methods: Vec<Method> = Vec::new();
                     match value.as_mapping(){
                         Some(ope) => {
-                            for (key, value) in ope {
-                                println!("got after {:?} - {:?}", key, value);
+                            for (key_1, value_1) in ope {
+                                //to avoid having parameters, bindings or extensions
+                                let async_methods = ["publish", "subscribe"];
+                                if let Some(method_name) = key_1.as_str() {
+                                    if async_methods.contains(&method_name) {
+                                        let method_description = value_1.get("description").and_then(|val| val.as_str()).unwrap_or("");
+                                        let method_summary = value_1.get("summary").and_then(|val| val.as_str()).unwrap_or("");
+
+                                        methods.push(Method { method: method_name.to_string(), description: method_description.to_string(), summary: method_summary.to_string() })
+                                    }
+                                }
                             }
                         },
                         None => {
-                            println!("none")
+                            warn!("No operation found on channel {:?} for spec title {:?}", key, self.get_title());
                         }
                     };
-                }
 
+                    match key.as_str() {
+                        Some(channel) => all_paths.push(Path { path: channel.to_string(), methods }),
+                        None => warn!("Unable to read channel name {:?} for spec title {:?}", key, self.get_title()),
+                    }
+                }
+            }
+            None => {
+                warn!("No channels found for spec title {:?}", self.get_title());
             }
-            None => {}
         };
 
-
-
-
-        Vec::new()
+        all_paths
     }
 
     fn get_audience(&self) -> String {
@@ -151,11 +175,244 @@ impl crate::app::dao::catalog::handlers::SpecHandler for V2{
           systems
     }
 
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        serde_yaml::from_str(&self.spec).ok()
+    }
+
+    /// The domain can be set through the `x-domain` extension. If not, it is derived from the
+    /// url of the first of the (named) [`servers`](https://v2.asyncapi.com/docs/reference/specification/v2.6.0#serversObject),
+    /// without its protocol and host (e.g. `wss://events.company.com/v1/audit/trails` gives `/v1/audit/trails`).
+    fn get_domain(&self) -> String {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        if let Some(domain) = spec_as_yaml.get("x-domain").and_then(|val| val.as_str()) {
+            return domain.to_string();
+        }
+
+        let mut domain = String::from( "NA - servers attribute not specified" );
+        if let Some(servers) = spec_as_yaml.get("servers").and_then(|val| val.as_mapping()) {
+            //only get the first one
+            if let Some((_name, server)) = servers.iter().next() {
+                if let Some(url) = server.get("url").and_then(|val| val.as_str()) {
+                    lazy_static! {
+                        static ref RE: Regex = Regex::new(r"^([a-z0-9+.-]+://[^/]*)(.*)").unwrap();
+                    }
+
+                    domain = match RE.captures(url) {
+                        Some(cap) if !cap.get(2).unwrap().as_str().is_empty() => cap.get(2).unwrap().as_str().to_string(),
+                        _ => url.to_string(),
+                    };
+                }
+            }
+        }
+
+        domain
+    }
+}
+
+/// [AsyncAPI 3](https://www.asyncapi.com/docs/reference/specification/v3.0.0) splits `channels` and `operations`
+/// into two top-level objects. `operations` point to their `channel` through a `$ref` and hold a `send` or `receive` action.
+#[derive(Debug, Clone)]
+pub struct V3 {
+    spec: String,
+}
+
+impl V3 {
+    pub fn format() -> SpecFormat {
+        SpecFormat {
+            spec_type: SpecType::AsyncAPIv3,
+            detect: |document| document.get_root_attribute("asyncapi").is_some_and(|version| version.starts_with("3.")),
+            build: |content| Ok( Box::new(V3::new(content)?) ),
+        }
+    }
+
+    pub fn new(val: &str) -> Result<Self, String> {
+        Ok( Self { spec: String::from(val) } )
+    }
+}
+impl crate::app::dao::catalog::handlers::SpecHandler for V3{
+    fn get_version(&self) -> String{
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+
+        if let Some(version) = spec_as_yaml["info"]["version"].as_str() {
+            version.to_string()
+        } else {
+            "N/A".to_string()
+        }
+    }
+
+    fn get_title(&self) -> String {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let mut title = "";
+        if let Some(info) = spec_as_yaml.get("info") {
+            if let Some(val) = info.get("title"){
+                title = val.as_str().unwrap();
+            }
+        }
+
+        title.to_string()
+    }
+
+    fn get_description(&self) -> String {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let mut description = "";
+        if let Some(info) = spec_as_yaml.get("info") {
+            if let Some(desc) = info.get("description"){
+                description = desc.as_str().unwrap();
+            }
+        }
+
+        description.to_string()
+    }
+
+    fn get_paths_len(&self) -> usize {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let channels: &serde_yaml::Value = &spec_as_yaml["channels"];
+        let len = match channels.as_mapping(){
+            Some(val) => val.len(),
+            None => 0
+        };
+
+        len
+    }
+
+    /// Each `channel` is exposed as a `Path` (its `address` or, if not set, its key) and the `operations`
+    /// referencing it as `Method`s named after their action (`send` / `receive`)
+    fn get_paths(&self) -> Vec<crate::app::dao::catalog::handlers::Path> {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let channels: &serde_yaml::Value = &spec_as_yaml["channels"];
+        let operations: &serde_yaml::Value = &spec_as_yaml["operations"];
+
+        let mut all_paths: Vec<Path> = Vec::new();
+
+        match channels.as_mapping(){
+            Some(val) => {
+                for (key, value) in val{
+                    let channel_name = match key.as_str() {
+                        Some(channel_name) => channel_name,
+                        None => {
+                            warn!("Unable to read channel name {:?} for spec title {:?}", key, self.get_title());
+                            continue;
+                        }
+                    };
+                    let mut methods: Vec<Method> = Vec::new();
+                    if let Some(ope) = operations.as_mapping() {
+                        for (key_1, value_1) in ope {
+                            //the JSON pointer escapes `/` and `~` of the channel name
+                            let target = value_1.get("channel").and_then(|val| val.get("$ref")).and_then(|val| val.as_str())
+                                .and_then(|val| val.strip_prefix("#/channels/"))
+                                .map(|val| val.replace("~1", "/").replace("~0", "~"));
+                            if target.as_deref() != Some(channel_name) {
+                                continue;
+                            }
+
+                            match value_1.get("action").and_then(|val| val.as_str()) {
+                                Some(action) if action == "send" || action == "receive" => {
+                                    let method_description = value_1.get("description").and_then(|val| val.as_str()).unwrap_or("");
+                                    let method_summary = value_1.get("summary").and_then(|val| val.as_str()).unwrap_or("");
+
+                                    methods.push(Method { method: action.to_string(), description: method_description.to_string(), summary: method_summary.to_string() })
+                                },
+                                _ => warn!("Invalid or missing action on operation {:?} for spec title {:?}", key_1, self.get_title()),
+                            }
+                        }
+                    }
+
+                    let address = value.get("address").and_then(|val| val.as_str()).unwrap_or(channel_name);
+                    all_paths.push(Path { path: address.to_string(), methods });
+                }
+            }
+            None => {
+                warn!("No channels found for spec title {:?}", self.get_title());
+            }
+        };
+
+        all_paths
+    }
+
+    fn get_audience(&self) -> String {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let mut audience = crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER;
+        if let Some(info) = spec_as_yaml.get("info") {
+            if let Some(val) = info.get("x-audience"){
+                audience = val.as_str().unwrap();
+            }
+        }
+
+        audience.to_string()
+    }
+
+    fn get_api_id(&self) -> String {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let mut api_id = "0".to_string();
+        if let Some(info) = spec_as_yaml.get("info") {
+            if let Some(val) = info.get("x-api-id"){
+                if val.is_string() {
+                    api_id = String::from(val.as_str().unwrap());
+                }
+                if val.is_number() {
+                    api_id = val.as_u64().unwrap().to_string();
+                }
+            }
+        }
+
+        api_id
+    }
+
+    fn get_layer(&self) -> String {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let mut layer = String::from( crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER );
+        if let Some(info) = spec_as_yaml.get("x-layer") {
+            layer = String::from(info.as_str().unwrap());
+        }
+
+        layer
+    }
+
+    fn get_systems(&self) -> Vec<String> {
+        let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        let systems = match spec_as_yaml.get("x-systems"){
+            Some(systems) => {
+                let mut returned_systems: Vec<String> = Vec::new();
+                let list_of_systems = systems.as_sequence().unwrap();
+                for system in list_of_systems{
+                    returned_systems.push( String::from(system.as_str().unwrap()) );
+                }
+
+                returned_systems
+            },
+            None => {
+                vec![String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER)]
+            }
+        };
+
+        systems
+    }
+
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        serde_yaml::from_str(&self.spec).ok()
+    }
+
+    /// The domain can be set through the `x-domain` extension. If not, it is the `pathname` of the first of the
+    /// [`servers`](https://www.asyncapi.com/docs/reference/specification/v3.0.0#serverObject) (`host` and `pathname` are split in v3).
     fn get_domain(&self) -> String {
         let spec_as_yaml: serde_yaml::Value = serde_yaml::from_str(&self.spec).unwrap();
+        if let Some(domain) = spec_as_yaml.get("x-domain").and_then(|val| val.as_str()) {
+            return domain.to_string();
+        }
 
+        let mut domain = String::from( "NA - servers attribute not specified" );
+        if let Some(servers) = spec_as_yaml.get("servers").and_then(|val| val.as_mapping()) {
+            //only get the first one
+            if let Some((_name, server)) = servers.iter().next() {
+                if let Some(pathname) = server.get("pathname").and_then(|val| val.as_str()) {
+                    domain = pathname.to_string();
+                } else if let Some(host) = server.get("host").and_then(|val| val.as_str()) {
+                    domain = host.to_string();
+                }
+            }
+        }
 
-        "To Be Implemented".to_string()
+        domain
     }
 }
 
@@ -165,6 +422,14 @@ pub struct V1 {
 }
 
 impl V1 {
+    pub fn format() -> SpecFormat {
+        SpecFormat {
+            spec_type: SpecType::AsyncAPIv1,
+            detect: |document| document.get_root_attribute("asyncapi").is_some_and(|version| version.starts_with("1.")),
+            build: |content| Ok( Box::new(V1::new(content)?) ),
+        }
+    }
+
     pub fn new(val: &str) -> Result<Self, String> {
         Ok( Self { spec: String::from(val) } )
     }
@@ -319,6 +584,10 @@ impl crate::app::dao::catalog::handlers::SpecHandler for V1{
           systems
     }
 
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        serde_yaml::from_str(&self.spec).ok()
+    }
+
     /// As for OpenAPI, in Async.V1, (the `servers` are specified)[https://github.com/asyncapi/spec/tree/1.0.0#A2SServers]. 
     /// We use *for now¨ the first item to define the domain.
     /// likely to evolve. 
@@ -497,10 +766,166 @@ pub mod tests {
         assert_eq!(spec.get_title(), "Account Service");
         assert_eq!(spec.get_audience(), crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER);
         assert_eq!(spec.get_api_id(), "0");
+        assert_eq!(spec.get_domain(), "NA - servers attribute not specified");
 
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths.len(), 1);
+        assert_eq!("user/signedup", all_paths[0].path);
+        assert_eq!(2, all_paths[0].methods.len());
+        assert_eq!("subscribe", all_paths[0].methods[0].method);
+        assert_eq!("ff", all_paths[0].methods[0].summary);
+        assert_eq!("ggggg", all_paths[0].methods[0].description);
+        assert_eq!("publish", all_paths[0].methods[1].method);
+    }
 
-        spec.get_paths();
-        
+    #[test]
+    fn test_async_v2_domain(){
+        let asyncapi_spec = "
+        asyncapi: '2.6.0'
+        info:
+          title: Account Service
+          version: 1.2.0
+        servers:
+          production:
+            url: wss://events.company.com/v1/accounts/users
+            protocol: wss
+          dev:
+            url: localhost:5672
+            protocol: amqp
+        channels:
+          user/signedup:
+            parameters:
+              userId:
+                description: Id of the user.
+            subscribe:
+              operationId: onUserSignedUp
+            x-not-an-operation: true
+        ";
+
+        let spec = crate::app::dao::catalog::handlers::implem::asyncapi::V2::new(asyncapi_spec).unwrap();
+        assert_eq!(spec.get_domain(), "/v1/accounts/users");
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths[0].methods.len(), 1);
+        assert_eq!("", all_paths[0].methods[0].summary);
+
+        let asyncapi_spec = "
+        asyncapi: '2.6.0'
+        info:
+          title: Account Service
+          version: 1.2.0
+        x-domain: /v1/accounts
+        servers:
+          dev:
+            url: localhost:5672
+            protocol: amqp
+        channels: {}
+        ";
+
+        let spec = crate::app::dao::catalog::handlers::implem::asyncapi::V2::new(asyncapi_spec).unwrap();
+        assert_eq!(spec.get_domain(), "/v1/accounts");
+        assert_eq!(spec.get_paths().len(), 0);
+
+        let asyncapi_spec = "
+        asyncapi: '2.6.0'
+        info:
+          title: Account Service
+          version: 1.2.0
+        servers:
+          dev:
+            url: localhost:5672
+            protocol: amqp
+        channels: {}
+        ";
+
+        let spec = crate::app::dao::catalog::handlers::implem::asyncapi::V2::new(asyncapi_spec).unwrap();
+        assert_eq!(spec.get_domain(), "localhost:5672");
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_async_v3(){
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/catalog/async/messaging-3.0.0.yml");
+
+        let asyncapi_spec = std::fs::read_to_string(path.as_path()).unwrap();
+        let spec = crate::app::dao::catalog::handlers::implem::asyncapi::V3::new(&asyncapi_spec).unwrap();
+
+        assert_eq!(spec.get_version(), "2.0.0");
+        assert_eq!(spec.get_description(), "This service is in charge of processing user signups");
+        assert_eq!(spec.get_title(), "Account Service");
+        assert_eq!(spec.get_audience(), "corporate");
+        assert_eq!(spec.get_api_id(), "5b8f0a36-3c0e-4ab5-8a45-0e1b3a3f4b2c");
+        assert_eq!(spec.get_layer(), "infrastructure");
+        assert_eq!(spec.get_systems(), vec!["bpaas"]);
+        assert_eq!(spec.get_domain(), "/v1/accounts/users");
+        assert_eq!(spec.get_paths_len(), 2);
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths.len(), 2);
+        assert_eq!("user/signedup", all_paths[0].path);
+        assert_eq!(2, all_paths[0].methods.len());
+        assert_eq!("send", all_paths[0].methods[0].method);
+        assert_eq!("Notify a signup", all_paths[0].methods[0].summary);
+        assert_eq!("Sent once the user has confirmed its email", all_paths[0].methods[0].description);
+        assert_eq!("receive", all_paths[0].methods[1].method);
+        assert_eq!("user/deleted", all_paths[1].path);
+        assert_eq!(1, all_paths[1].methods.len());
+        assert_eq!("", all_paths[1].methods[0].summary);
+    }
+
+    #[test]
+    fn test_async_v3_channel_without_address(){
+        let asyncapi_spec = "
+        asyncapi: 3.0.0
+        info:
+          title: Account Service
+          version: 1.0.0
+        servers:
+          dev:
+            host: localhost:5672
+            protocol: amqp
+        channels:
+          userSignedUp:
+            messages: {}
+        operations:
+          onUserSignedUp:
+            action: publish
+            channel:
+              $ref: '#/channels/userSignedUp'
+        ";
+
+        let spec = crate::app::dao::catalog::handlers::implem::asyncapi::V3::new(asyncapi_spec).unwrap();
+        assert_eq!(spec.get_domain(), "localhost:5672");
+        assert_eq!(spec.get_systems(), vec![crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER]);
+
+        let all_paths = spec.get_paths();
+        assert_eq!("userSignedUp", all_paths[0].path);
+        assert_eq!(0, all_paths[0].methods.len());
+    }
+
+    #[test]
+    fn test_async_v3_escaped_channel_ref(){
+        let asyncapi_spec = "
+        asyncapi: 3.0.0
+        info:
+          title: Account Service
+          version: 1.0.0
+        channels:
+          user/signed~up:
+            messages: {}
+        operations:
+          onUserSignedUp:
+            action: send
+            channel:
+              $ref: '#/channels/user~1signed~0up'
+        ";
+
+        let spec = crate::app::dao::catalog::handlers::implem::asyncapi::V3::new(asyncapi_spec).unwrap();
+        let all_paths = spec.get_paths();
+        assert_eq!("user/signed~up", all_paths[0].path);
+        assert_eq!(1, all_paths[0].methods.len());
+        assert_eq!("send", all_paths[0].methods[0].method);
+    }
+
+}
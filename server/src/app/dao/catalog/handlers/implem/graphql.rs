@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+
+use graphql_parser::schema::{Definition, Directive, TypeDefinition, TypeExtension, Value};
+use log::warn;
+use regex::Regex;
+
+use crate::app::dao::catalog::handlers::{SpecHandler, SpecFormat, SpecType, Method, Path};
+
+/// Handler for GraphQL SDL (`.graphql`) schemas.
+///
+/// The fields of the root operation types (`Query`, `Mutation` and `Subscription` or the ones
+/// declared in the `schema` definition) are exposed as `Path`s, e.g. `Query.events`.
+///
+/// Catalog metadata are read from the directives of the `schema` definition, as an example
+/// `schema @audience(name: "company") @systems(names: ["bpaas"]) { query: Query }`, or from
+/// comments such as `# x-audience: company`. Directives take precedence over comments.
+/// Supported metadata are `audience`, `apiId`, `layer`, `systems`, `domain`, `version` and `title`.
+#[derive(Debug, Clone)]
+pub struct GraphQL {
+    metadata: HashMap<String, String>,
+    description: String,
+    operations: Vec<Operation>,
+}
+
+#[derive(Debug, Clone)]
+struct Operation {
+    kind: String,
+    type_name: String,
+    name: String,
+    description: String,
+}
+
+lazy_static! {
+    static ref METADATA_COMMENT: Regex = Regex::new(r"(?m)^\s*#\s*x-([a-z-]+)\s*:\s*(.*?)\s*$").unwrap();
+}
+
+impl GraphQL {
+    /// `.graphql` files, or any non yaml content defining a `schema` or a root operation type
+    pub fn format() -> SpecFormat {
+        lazy_static! {
+            static ref ROOT_TYPE: Regex = Regex::new(r"(?m)^\s*(extend\s+)?(schema|type\s+(Query|Mutation|Subscription))\b[^{]*\{").unwrap();
+        }
+
+        SpecFormat {
+            spec_type: SpecType::GraphQL,
+            detect: |document| document.root.is_none() && (document.has_extension(&["graphql", "graphqls", "gql"]) || ROOT_TYPE.is_match(document.content)),
+            build: |content| Ok( Box::new(GraphQL::new(content)?) ),
+        }
+    }
+
+    pub fn new(val: &str) -> Result<Self, String> {
+        let document = match graphql_parser::parse_schema::<String>(val) {
+            Ok(document) => document,
+            Err(why) => return Err( format!("Unable to parse content - {}", why) ),
+        };
+
+        let mut metadata = HashMap::new();
+        for cap in METADATA_COMMENT.captures_iter(val) {
+            metadata.insert(cap[1].replace('-', "_"), cap[2].to_string());
+        }
+
+        //root operation types default to Query, Mutation and Subscription
+        let mut root_types: Vec<(String, String)> = vec![
+            (String::from("query"), String::from("Query")),
+            (String::from("mutation"), String::from("Mutation")),
+            (String::from("subscription"), String::from("Subscription")),
+        ];
+        for definition in &document.definitions {
+            if let Definition::SchemaDefinition(schema) = definition {
+                let declared = [("query", &schema.query), ("mutation", &schema.mutation), ("subscription", &schema.subscription)];
+                root_types = declared.iter()
+                    .filter_map(|(kind, name)| name.as_ref().map(|name| (kind.to_string(), name.to_string())))
+                    .collect();
+
+                for directive in &schema.directives {
+                    read_directive(directive, &mut metadata);
+                }
+            }
+        }
+
+        let mut description = String::from("");
+        let mut operations = Vec::new();
+        for (kind, type_name) in &root_types {
+            for definition in &document.definitions {
+                let fields = match definition {
+                    Definition::TypeDefinition(TypeDefinition::Object(object)) if &object.name == type_name => {
+                        if kind == "query" {
+                            description = object.description.clone().unwrap_or_default();
+                        }
+                        &object.fields
+                    },
+                    Definition::TypeExtension(TypeExtension::Object(object)) if &object.name == type_name => &object.fields,
+                    _ => continue,
+                };
+
+                for field in fields {
+                    operations.push(Operation {
+                        kind: kind.to_string(),
+                        type_name: type_name.to_string(),
+                        name: field.name.to_string(),
+                        description: field.description.clone().unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        if let Some(val) = metadata.get("description") {
+            description = val.to_string();
+        }
+
+        Ok( Self { metadata, description, operations } )
+    }
+
+    fn get_metadata(&self, name: &str) -> Option<&String> {
+        self.metadata.get(name)
+    }
+}
+
+/// `@systems(names: ["a", "b"])` gives `a,b` - only the first argument of the directive is read
+fn read_directive(directive: &Directive<String>, metadata: &mut HashMap<String, String>) {
+    let key = match directive.name.as_str() {
+        "apiId" => "api_id",
+        "audience" | "layer" | "systems" | "domain" | "version" | "title" => directive.name.as_str(),
+        _ => return,
+    };
+
+    match directive.arguments.first() {
+        Some((_name, value)) => {
+            let value = match value {
+                Value::List(values) => values.iter().map(value_as_string).collect::<Vec<String>>().join(","),
+                value => value_as_string(value),
+            };
+            metadata.insert(String::from(key), value);
+        },
+        None => warn!("No argument found for directive {:?}", directive.name),
+    }
+}
+
+fn value_as_string(value: &Value<String>) -> String {
+    match value {
+        Value::String(val) | Value::Enum(val) => val.to_string(),
+        value => value.to_string(),
+    }
+}
+
+impl SpecHandler for GraphQL {
+
+    fn get_version(&self) -> String {
+        match self.get_metadata("version") {
+            Some(version) => version.to_string(),
+            None => "N/A".to_string(),
+        }
+    }
+
+    fn get_title(&self) -> String {
+        match self.get_metadata("title") {
+            Some(title) => title.to_string(),
+            None => "N/A".to_string(),
+        }
+    }
+
+    fn get_description(&self) -> String {
+        self.description.to_string()
+    }
+
+    fn get_paths_len(&self) -> usize {
+        self.operations.len()
+    }
+
+    fn get_paths(&self) -> Vec<Path> {
+        let mut all_paths = Vec::new();
+
+        for operation in &self.operations {
+            let summary = operation.description.lines().next().unwrap_or("").to_string();
+
+            all_paths.push(Path {
+                path: format!("{}.{}", operation.type_name, operation.name),
+                methods: vec![Method {
+                    method: operation.kind.to_string(),
+                    description: operation.description.to_string(),
+                    summary,
+                }],
+            });
+        }
+
+        all_paths
+    }
+
+    fn get_audience(&self) -> String {
+        match self.get_metadata("audience") {
+            Some(audience) => audience.to_string(),
+            None => String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER),
+        }
+    }
+
+    fn get_api_id(&self) -> String {
+        match self.get_metadata("api_id") {
+            Some(id) => id.to_string(),
+            None => String::from("0"),
+        }
+    }
+
+    fn get_layer(&self) -> String {
+        match self.get_metadata("layer") {
+            Some(layer) => layer.to_lowercase(),
+            None => String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER),
+        }
+    }
+
+    fn get_systems(&self) -> Vec<String> {
+        let systems: Vec<String> = match self.get_metadata("systems") {
+            Some(systems) => systems
+                .split(',')
+                .map(|system| system.trim().to_lowercase())
+                .filter(|system| !system.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        match systems.is_empty() {
+            true => vec![String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER)],
+            false => systems,
+        }
+    }
+
+    fn get_domain(&self) -> String {
+        match self.get_metadata("domain") {
+            Some(domain) => domain.to_string(),
+            None => String::from("NA - domain not specified"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::app::dao::catalog::handlers::SpecHandler;
+
+    #[test]
+    fn test_graphql_with_directives(){
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/catalog/graphql/audit-trails.graphql");
+
+        let schema = std::fs::read_to_string(path.as_path()).unwrap();
+        let spec = crate::app::dao::catalog::handlers::implem::graphql::GraphQL::new(&schema).unwrap();
+
+        assert_eq!(spec.get_title(), "Audit Trails");
+        assert_eq!(spec.get_version(), "1.1.0");
+        assert_eq!(spec.get_description(), "Read the audit trail events");
+        assert_eq!(spec.get_audience(), "company");
+        assert_eq!(spec.get_api_id(), "0");
+        assert_eq!(spec.get_layer(), "infrastructure");
+        assert_eq!(spec.get_systems(), vec!["bpaas", "audit"]);
+        assert_eq!(spec.get_domain(), "/v1/audit/trails");
+        assert_eq!(spec.get_paths_len(), 4);
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths[0].path, "AuditQuery.events");
+        assert_eq!(all_paths[0].methods[0].method, "query");
+        assert_eq!(all_paths[0].methods[0].summary, "List the events of an entity");
+        assert_eq!(all_paths[0].methods[0].description, "List the events of an entity\nmost recent first");
+        assert_eq!(all_paths[1].path, "AuditQuery.event");
+        assert_eq!(all_paths[2].path, "AuditMutation.pushEvent");
+        assert_eq!(all_paths[2].methods[0].method, "mutation");
+        assert_eq!(all_paths[3].path, "AuditMutation.purgeEvents");
+        assert_eq!(all_paths[3].methods[0].summary, "");
+    }
+
+    #[test]
+    fn test_graphql_with_comments_and_default_root_types(){
+        let schema = r#"
+        # x-audience: corporate
+        # x-layer: Application
+        # x-systems: bpaas
+        # x-title: Accounts
+
+        "Accounts of the users"
+        type Query {
+          account(id: ID!): Account
+        }
+
+        type Subscription {
+          "Notified on each signup"
+          accountCreated: Account
+        }
+
+        type Account {
+          id: ID!
+        }
+        "#;
+
+        let spec = crate::app::dao::catalog::handlers::implem::graphql::GraphQL::new(schema).unwrap();
+        assert_eq!(spec.get_title(), "Accounts");
+        assert_eq!(spec.get_version(), "N/A");
+        assert_eq!(spec.get_description(), "Accounts of the users");
+        assert_eq!(spec.get_audience(), "corporate");
+        assert_eq!(spec.get_layer(), "application");
+        assert_eq!(spec.get_systems(), vec!["bpaas"]);
+        assert_eq!(spec.get_domain(), "NA - domain not specified");
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths.len(), 2);
+        assert_eq!(all_paths[0].path, "Query.account");
+        assert_eq!(all_paths[1].path, "Subscription.accountCreated");
+        assert_eq!(all_paths[1].methods[0].method, "subscription");
+        assert_eq!(all_paths[1].methods[0].summary, "Notified on each signup");
+
+        assert!(crate::app::dao::catalog::handlers::implem::graphql::GraphQL::new("type Query {").is_err());
+    }
+}
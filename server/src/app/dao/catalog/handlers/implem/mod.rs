@@ -1,3 +1,5 @@
 pub mod opanapi;
 pub mod asyncapi;
-pub mod proto;
\ No newline at end of file
+pub mod proto;
+pub mod swagger;
+pub mod graphql;
\ No newline at end of file
@@ -1,7 +1,7 @@
 use openapiv3::OpenAPI;
 use regex::Regex;
 
-use crate::app::dao::catalog::handlers::{SpecHandler, Path, Method};
+use crate::app::dao::catalog::handlers::{SpecHandler, SpecFormat, SpecType, Path, Method, SecurityScheme, OperationSecurity};
 use log::{debug, info, warn, error};
 
 #[derive(Debug, Clone)]
@@ -9,6 +9,14 @@ pub struct V3 {
     pub spec: OpenAPI,
 }
 impl V3 {
+  pub fn format() -> SpecFormat {
+    SpecFormat {
+      spec_type: SpecType::OpenAPIv3,
+      detect: |document| document.get_root_attribute("openapi").is_some_and(|version| version.starts_with("3.0")),
+      build: |content| Ok( Box::new(V3::new(content)?) ),
+    }
+  }
+
   pub fn new(val: &str) -> Result<Self, String> {
     match serde_yaml::from_str::<OpenAPI>(val) {
       Ok(openapi) => {
@@ -142,6 +150,18 @@ impl SpecHandler for V3{
       systems
     }
 
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        serde_yaml::to_value(&self.spec).ok()
+    }
+
+    fn get_security_schemes(&self) -> Vec<SecurityScheme> {
+        self.get_document().map(|spec| get_security_schemes_from_yaml(&spec)).unwrap_or_default()
+    }
+
+    fn get_operations_security(&self) -> Vec<OperationSecurity> {
+        self.get_document().map(|spec| get_operations_security_from_yaml(&spec)).unwrap_or_default()
+    }
+
     fn get_domain(&self) -> String {
       let base_url = match self.spec.servers.is_empty() {
         true => "NA - servers attribute not specified",
@@ -166,6 +186,286 @@ impl SpecHandler for V3{
 
 }
 
+/// [OpenAPI 3.1](https://spec.openapis.org/oas/v3.1.0) documents (webhooks, JSON Schema 2020-12 types...) are not
+/// supported by the `openapiv3` crate, they are read as plain yaml.
+#[derive(Debug, Clone)]
+pub struct V31 {
+    pub spec: serde_yaml::Value,
+}
+impl V31 {
+  pub fn format() -> SpecFormat {
+    SpecFormat {
+      spec_type: SpecType::OpenAPIv31,
+      detect: |document| document.get_root_attribute("openapi").is_some_and(|version| version.starts_with("3.1")),
+      build: |content| Ok( Box::new(V31::new(content)?) ),
+    }
+  }
+
+  pub fn new(val: &str) -> Result<Self, String> {
+    match serde_yaml::from_str::<serde_yaml::Value>(val) {
+      Ok(spec) => {
+        match spec.get("info").and_then(|info| info.get("title")) {
+          Some(_) => Ok(Self { spec }),
+          None => Err( String::from("Unable to parse content - info attribute not specified") ),
+        }
+      }
+      Err(why) => {
+        Err( format!("Unable to parse content - {:?}", why) )
+      }
+    }
+  }
+}
+
+impl SpecHandler for V31{
+    fn get_version(&self) -> String {
+        get_info_attribute(&self.spec, "version", "N/A")
+    }
+
+    fn get_title(&self) -> String {
+        get_info_attribute(&self.spec, "title", "")
+    }
+
+    fn get_description(&self) -> String {
+        get_info_attribute(&self.spec, "description", "")
+    }
+
+    fn get_paths_len(&self) -> usize {
+        match self.spec.get("paths").and_then(|paths| paths.as_mapping()) {
+            Some(paths) => paths.len(),
+            None => 0,
+        }
+    }
+
+    fn get_paths(&self) -> Vec<Path> {
+        get_paths_from_yaml(&self.spec, self.get_title())
+    }
+
+    fn get_audience(&self) -> String {
+        get_info_attribute(&self.spec, "x-audience", crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER)
+    }
+
+    fn get_api_id(&self) -> String {
+        get_api_id_from_yaml(&self.spec)
+    }
+
+    fn get_layer(&self) -> String {
+        get_layer_from_yaml(&self.spec)
+    }
+
+    fn get_systems(&self) -> Vec<String> {
+        get_systems_from_yaml(&self.spec)
+    }
+
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        Some(self.spec.clone())
+    }
+
+    fn get_security_schemes(&self) -> Vec<SecurityScheme> {
+        get_security_schemes_from_yaml(&self.spec)
+    }
+
+    fn get_operations_security(&self) -> Vec<OperationSecurity> {
+        get_operations_security_from_yaml(&self.spec)
+    }
+
+    fn get_domain(&self) -> String {
+      let url = self.spec.get("servers")
+        .and_then(|servers| servers.as_sequence())
+        .and_then(|servers| servers.first())
+        .and_then(|server| server.get("url"))
+        .and_then(|url| url.as_str());
+
+      let base_url = match url {
+        None => "NA - servers attribute not specified",
+        Some(url) => {
+            //keep the path only, e.g. https://api.company.com/v1/audit/trails gives /v1/audit/trails
+            lazy_static! {
+                static ref RE: Regex = Regex::new(r"^(http[s]?://[^/]*)(.*)").unwrap();
+            }
+
+            match RE.captures(url) {
+                Some(cap) if !cap.get(2).unwrap().as_str().is_empty() => cap.get(2).unwrap().as_str(),
+                _ => url,
+            }
+        }
+      };
+
+      base_url.to_string()
+    }
+}
+
+// helpers shared with the yaml based handlers (OpenAPI 3.1, Swagger 2.0)
+
+pub(crate) fn get_info_attribute(spec: &serde_yaml::Value, attribute: &str, default: &str) -> String {
+    let value = spec.get("info")
+        .and_then(|info| info.get(attribute))
+        .and_then(|val| val.as_str())
+        .unwrap_or(default);
+
+    value.to_string()
+}
+
+pub(crate) fn get_paths_from_yaml(spec: &serde_yaml::Value, title: String) -> Vec<Path> {
+    let mut all_paths = Vec::new();
+
+    let paths = match spec.get("paths").and_then(|paths| paths.as_mapping()) {
+        Some(paths) => paths,
+        None => {
+            warn!("No paths found in spec title {:?}", title);
+            return all_paths;
+        }
+    };
+
+    for (path_value, path_item) in paths.iter() {
+        let path_value = match path_value.as_str() {
+            Some(path_value) => path_value,
+            None => {
+                warn!("Unable to read path {:?} in spec title {:?}", path_value, title);
+                continue;
+            }
+        };
+
+        let mut all_methods = Vec::new();
+        for method in ["GET", "POST", "PUT", "DELETE", "OPTIONS", "HEAD", "PATCH"] {
+            if let Some(ope) = path_item.get(method.to_lowercase()) {
+                let ope_summary = ope.get("summary").and_then(|val| val.as_str()).unwrap_or("N/A");
+                let ope_description = ope.get("description").and_then(|val| val.as_str()).unwrap_or("N/A");
+
+                all_methods.push(Method{
+                    method: String::from(method),
+                    description: String::from(ope_description),
+                    summary: String::from(ope_summary)
+                });
+            }
+        }
+
+        all_paths.push(Path { path: String::from(path_value), methods: all_methods })
+    }
+
+    all_paths
+}
+
+pub(crate) fn get_api_id_from_yaml(spec: &serde_yaml::Value) -> String {
+    let mut api_id = String::from("0");
+    if let Some(val) = spec.get("info").and_then(|info| info.get("x-api-id")) {
+        if val.is_string() {
+            api_id = String::from(val.as_str().unwrap());
+        }
+        if val.is_number() {
+            api_id = val.as_u64().unwrap().to_string();
+        }
+    }
+
+    api_id
+}
+
+pub(crate) fn get_layer_from_yaml(spec: &serde_yaml::Value) -> String {
+    let layer = spec.get("x-layer")
+        .and_then(|layer| layer.as_str())
+        .unwrap_or(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER);
+
+    layer.to_lowercase()
+}
+
+pub(crate) fn get_systems_from_yaml(spec: &serde_yaml::Value) -> Vec<String> {
+    match spec.get("x-systems").and_then(|systems| systems.as_sequence()) {
+        Some(systems) => {
+            systems.iter()
+                .filter_map(|system| system.as_str())
+                .map(|system| system.to_lowercase())
+                .collect()
+        },
+        None => vec![String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER)],
+    }
+}
+
+/// `components.securitySchemes` (OpenAPI) or `securityDefinitions` (Swagger 2.0)
+pub(crate) fn get_security_schemes_from_yaml(spec: &serde_yaml::Value) -> Vec<SecurityScheme> {
+    let schemes = spec.get("components").and_then(|components| components.get("securitySchemes"))
+        .or_else(|| spec.get("securityDefinitions"))
+        .and_then(|schemes| schemes.as_mapping());
+    let schemes = match schemes {
+        Some(schemes) => schemes,
+        None => return Vec::new(),
+    };
+
+    let attribute = |scheme: &serde_yaml::Value, attribute: &str| String::from(scheme.get(attribute).and_then(|val| val.as_str()).unwrap_or_default());
+    schemes.iter()
+        .filter_map(|(name, scheme)| {
+            let scheme_type = attribute(scheme, "type");
+            let detail = match scheme_type.as_str() {
+                "http" => attribute(scheme, "scheme"),
+                "apiKey" => format!("{} {}", attribute(scheme, "in"), attribute(scheme, "name")),
+                //flows (OpenAPI) or flow (Swagger 2.0)
+                "oauth2" => match scheme.get("flows").and_then(|flows| flows.as_mapping()) {
+                    Some(flows) => flows.keys().filter_map(|flow| flow.as_str()).collect::<Vec<&str>>().join(", "),
+                    None => attribute(scheme, "flow"),
+                },
+                "openIdConnect" => attribute(scheme, "openIdConnectUrl"),
+                _ => String::new(),
+            };
+
+            Some(SecurityScheme { name: String::from(name.as_str()?), scheme_type, detail })
+        })
+        .collect()
+}
+
+/// the `security` of the operations (the one of the spec otherwise, `[]` meaning no security) and their `x-has-authority`.
+/// The requirements of `security` are alternatives, the schemes of a requirement being all required
+pub(crate) fn get_operations_security_from_yaml(spec: &serde_yaml::Value) -> Vec<OperationSecurity> {
+    let mut operations = Vec::new();
+    let paths = match spec.get("paths").and_then(|paths| paths.as_mapping()) {
+        Some(paths) => paths,
+        None => return operations,
+    };
+
+    for (path, path_item) in paths.iter() {
+        let path = match path.as_str() {
+            Some(path) => path,
+            None => continue,
+        };
+
+        for method in crate::app::dao::catalog::diff::HTTP_METHODS {
+            let operation = match path_item.get(method) {
+                Some(operation) => operation,
+                None => continue,
+            };
+
+            let requirements = operation.get("security").or_else(|| spec.get("security"))
+                .and_then(|security| security.as_sequence())
+                .map(|security| {
+                    security.iter()
+                        .filter_map(|requirement| requirement.as_mapping())
+                        .map(|requirement| {
+                            requirement.iter()
+                                .filter_map(|(scheme, scopes)| Some((
+                                    String::from(scheme.as_str()?),
+                                    scopes.as_sequence().map(|scopes| scopes.iter().filter_map(|scope| scope.as_str()).map(String::from).collect()).unwrap_or_default(),
+                                )))
+                                .collect()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let authorities = match operation.get("x-has-authority").or_else(|| path_item.get("x-has-authority")) {
+                Some(serde_yaml::Value::String(authority)) => vec![ String::from(authority) ],
+                Some(serde_yaml::Value::Sequence(authorities)) => authorities.iter().filter_map(|authority| authority.as_str()).map(String::from).collect(),
+                _ => Vec::new(),
+            };
+
+            operations.push(OperationSecurity {
+                path: String::from(path),
+                method: method.to_uppercase(),
+                audience: operation.get("x-audience").and_then(|audience| audience.as_str()).map(String::from),
+                requirements,
+                authorities,
+            });
+        }
+    }
+
+    operations
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::app::dao::catalog::handlers::SpecHandler;
@@ -336,4 +636,183 @@ pub mod tests {
         let sut = spec.get_api_id();
         assert_eq!(sut, "0");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_openapi_v31(){
+        let openapi_spec = r#"
+        openapi: 3.1.0
+        info:
+          title: Webhook Example
+          version: 1.0.0
+          x-audience: company-internal
+          x-api-id: 42
+        x-layer: Application
+        x-systems:
+          - BPaaS
+        servers:
+          - url: https://api.company.com/v1/audit/trails
+        paths:
+          /events:
+            get:
+              summary: List events
+              responses:
+                '200':
+                  description: the events
+                  content:
+                    application/json:
+                      schema:
+                        type: [array, 'null']
+            post:
+              description: Push an event
+              responses:
+                '201':
+                  description: created
+        webhooks:
+          newEvent:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      type: object
+              responses:
+                '200':
+                  description: received
+        "#;
+
+        let spec = crate::app::dao::catalog::handlers::implem::opanapi::V31::new(openapi_spec).unwrap();
+        assert_eq!(spec.get_version(), "1.0.0");
+        assert_eq!(spec.get_title(), "Webhook Example");
+        assert_eq!(spec.get_description(), "");
+        assert_eq!(spec.get_audience(), "company-internal");
+        assert_eq!(spec.get_api_id(), "42");
+        assert_eq!(spec.get_layer(), "application");
+        assert_eq!(spec.get_systems(), vec!["bpaas"]);
+        assert_eq!(spec.get_domain(), "/v1/audit/trails");
+        assert_eq!(spec.get_paths_len(), 1);
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths[0].path, "/events");
+        assert_eq!(all_paths[0].methods.len(), 2);
+        assert_eq!(all_paths[0].methods[0].method, "GET");
+        assert_eq!(all_paths[0].methods[0].summary, "List events");
+        assert_eq!(all_paths[0].methods[1].method, "POST");
+        assert_eq!(all_paths[0].methods[1].summary, "N/A");
+        assert_eq!(all_paths[0].methods[1].description, "Push an event");
+    }
+
+    #[test]
+    fn test_openapi_v31_without_paths(){
+        let openapi_spec = r#"
+        openapi: 3.1.0
+        info:
+          title: Webhook Example
+          version: 1.0.0
+        webhooks: {}
+        "#;
+
+        let spec = crate::app::dao::catalog::handlers::implem::opanapi::V31::new(openapi_spec).unwrap();
+        assert_eq!(spec.get_paths_len(), 0);
+        assert_eq!(spec.get_paths().len(), 0);
+        assert_eq!(spec.get_domain(), "NA - servers attribute not specified");
+        assert_eq!(spec.get_layer(), crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER);
+        assert_eq!(spec.get_systems(), vec![crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER]);
+
+        assert!(crate::app::dao::catalog::handlers::implem::opanapi::V31::new("openapi: 3.1.0").is_err());
+    }
+
+    #[test]
+    fn test_openapi_security(){
+        let openapi_spec = r#"
+        openapi: 3.0.2
+        info:
+          title: Swagger Petstore
+          version: 1.0.17
+        security:
+          - bearer: []
+        components:
+          securitySchemes:
+            bearer:
+              type: http
+              scheme: bearer
+            key:
+              type: apiKey
+              in: header
+              name: X-API-KEY
+            oauth:
+              type: oauth2
+              flows:
+                clientCredentials:
+                  tokenUrl: https://auth/token
+                  scopes:
+                    pets.read: read the pets
+        paths:
+          /pets:
+            x-has-authority: pets.read
+            get:
+              responses:
+                '200':
+                  description: ok
+            post:
+              security:
+                - oauth: [ pets.read, pets.write ]
+                  key: []
+              x-has-authority: [ pets.write, pets.admin ]
+              responses:
+                '201':
+                  description: created
+          /health:
+            get:
+              security: []
+              x-audience: component-internal
+              responses:
+                '200':
+                  description: ok
+          /owners:
+            get:
+              security:
+                - bearer: []
+                - key: []
+              responses:
+                '200':
+                  description: ok
+            post:
+              security:
+                - bearer: []
+                - {}
+              responses:
+                '201':
+                  description: created
+        "#;
+
+        let spec = crate::app::dao::catalog::handlers::implem::opanapi::V3::new(openapi_spec).unwrap();
+        let schemes: Vec<(String, String, String)> = spec.get_security_schemes().into_iter().map(|scheme| (scheme.name, scheme.scheme_type, scheme.detail)).collect();
+        assert_eq!(schemes, vec![
+            (String::from("bearer"), String::from("http"), String::from("bearer")),
+            (String::from("key"), String::from("apiKey"), String::from("header X-API-KEY")),
+            (String::from("oauth"), String::from("oauth2"), String::from("clientCredentials")),
+        ]);
+
+        let operations = spec.get_operations_security();
+        assert_eq!(operations.len(), 5);
+        //inherited from the spec and the path
+        assert_eq!((operations[0].path.as_str(), operations[0].method.as_str()), ("/pets", "GET"));
+        assert_eq!(operations[0].requirements, vec![ vec![ (String::from("bearer"), Vec::new()) ] ]);
+        assert_eq!(operations[0].authorities, vec![ String::from("pets.read") ]);
+        assert_eq!(operations[1].requirements, vec![ vec![
+            (String::from("oauth"), vec![ String::from("pets.read"), String::from("pets.write") ]),
+            (String::from("key"), Vec::new()),
+        ] ]);
+        assert_eq!(operations[1].authorities, vec![ String::from("pets.write"), String::from("pets.admin") ]);
+        assert!(!operations[1].is_unsecured());
+        //security: [] disables the security of the spec
+        assert_eq!((operations[2].path.as_str(), operations[2].audience.as_deref()), ("/health", Some("component-internal")));
+        assert!(operations[2].is_unsecured());
+        //either of the schemes
+        assert_eq!(operations[3].requirements, vec![ vec![ (String::from("bearer"), Vec::new()) ], vec![ (String::from("key"), Vec::new()) ] ]);
+        assert!(!operations[3].is_unsecured());
+        //the empty alternative makes the security optional
+        assert_eq!(operations[4].requirements, vec![ vec![ (String::from("bearer"), Vec::new()) ], Vec::new() ]);
+        assert!(operations[4].is_unsecured());
+    }
+}
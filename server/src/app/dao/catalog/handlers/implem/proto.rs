@@ -1,55 +1,480 @@
+use std::collections::HashMap;
+
+use log::warn;
+use regex::Regex;
+
+use crate::app::dao::catalog::handlers::{SpecHandler, SpecFormat, SpecType, Method, Path};
+
+/// Handler for Protocol Buffers (`syntax = "proto3"`) files.
+///
+/// The file is parsed with a small tokenizer that only keeps what the catalog needs:
+/// the `package`, the file level `option`s and the `service` / `rpc` definitions
+/// (including their leading comments). `message`, `enum` or `extend` blocks are skipped.
+///
+/// Catalog metadata are read from (custom) file options, as an example
+/// `option (x_audience) = "company";`. Supported options are `x_audience`, `x_api_id`,
+/// `x_layer`, `x_systems` (comma separated), `x_domain`, `x_version` and `x_title`.
+#[derive(Debug, Clone)]
+pub struct Proto3 {
+    package: String,
+    options: HashMap<String, String>,
+    services: Vec<Service>,
+}
 
 #[derive(Debug, Clone)]
-pub struct  Proto3{
-    spec: String,
+struct Service {
+    name: String,
+    comment: String,
+    rpcs: Vec<Rpc>,
 }
+
+#[derive(Debug, Clone)]
+struct Rpc {
+    name: String,
+    comment: String,
+    client_streaming: bool,
+    server_streaming: bool,
+}
+
 impl Proto3 {
-    pub fn new(val: &str) -> Self {
-        Self {
-            spec: String::from(val),
+    /// `.proto` files, or any non yaml content declaring `syntax = "proto3";`
+    pub fn format() -> SpecFormat {
+        lazy_static! {
+            static ref SYNTAX: Regex = Regex::new(r#"(?m)^\s*syntax\s*=\s*["']proto3["']"#).unwrap();
+        }
+
+        SpecFormat {
+            spec_type: SpecType::Proto3,
+            detect: |document| document.root.is_none() && (document.has_extension(&["proto"]) || SYNTAX.is_match(document.content)),
+            build: |content| Ok( Box::new(Proto3::new(content)?) ),
+        }
+    }
+
+    pub fn new(val: &str) -> Result<Self, String> {
+        let tokens = tokenize(val)?;
+        let mut parser = Parser { tokens, position: 0 };
+
+        parser.parse()
+    }
+
+    fn get_option(&self, name: &str) -> Option<&String> {
+        self.options.get(name)
+    }
+
+    /// rpc are exposed (by gRPC) under `/{package}.{service}/{rpc}`
+    fn get_rpc_path(&self, service: &Service, rpc: &Rpc) -> String {
+        match self.package.is_empty() {
+            true => format!("/{}/{}", service.name, rpc.name),
+            false => format!("/{}.{}/{}", self.package, service.name, rpc.name),
         }
     }
 }
-impl crate::app::dao::catalog::handlers::SpecHandler for Proto3{
+
+impl SpecHandler for Proto3 {
 
     fn get_version(&self) -> String {
-        "you should use prost-types crate".to_string()
+        if let Some(version) = self.get_option("x_version") {
+            return version.to_string();
+        }
+
+        //packages are usually versioned, e.g. company.audit.trails.v1
+        let version = self.package.split('.').find(|segment| {
+            segment.len() > 1 && segment.starts_with('v') && segment[1..].chars().all(|c| c.is_ascii_digit())
+        });
+
+        match version {
+            Some(val) => val.to_string(),
+            None => "N/A".to_string(),
+        }
+    }
+
+    fn get_title(&self) -> String {
+        if let Some(title) = self.get_option("x_title") {
+            return title.to_string();
+        }
+
+        match self.services.first() {
+            Some(service) => service.name.to_string(),
+            None => self.package.to_string(),
+        }
     }
 
     fn get_description(&self) -> String {
-        "you should use prost-types crate".to_string()
+        match self.services.first() {
+            Some(service) => service.comment.to_string(),
+            None => "".to_string(),
+        }
     }
 
     fn get_paths_len(&self) -> usize {
-        2
+        self.services.iter().map(|service| service.rpcs.len()).sum()
     }
 
-    fn get_title(&self) -> String {
-        "you should use prost-types crate".to_string()
-    }
+    fn get_paths(&self) -> Vec<Path> {
+        let mut all_paths = Vec::new();
 
-    fn get_paths(&self) -> Vec<crate::app::dao::catalog::handlers::Path> {
-        Vec::new()
+        for service in &self.services {
+            if service.rpcs.is_empty() {
+                warn!("No rpc found for service {:?} in package {:?}", service.name, self.package);
+            }
+
+            for rpc in &service.rpcs {
+                let method = match (rpc.client_streaming, rpc.server_streaming) {
+                    (false, false) => "unary",
+                    (false, true) => "server_streaming",
+                    (true, false) => "client_streaming",
+                    (true, true) => "bidi_streaming",
+                };
+                let summary = rpc.comment.lines().next().unwrap_or("").to_string();
+
+                all_paths.push(Path {
+                    path: self.get_rpc_path(service, rpc),
+                    methods: vec![Method {
+                        method: String::from(method),
+                        description: rpc.comment.to_string(),
+                        summary,
+                    }],
+                });
+            }
+        }
+
+        all_paths
     }
 
     fn get_audience(&self) -> String {
-        todo!()
+        match self.get_option("x_audience") {
+            Some(audience) => audience.to_string(),
+            None => String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER),
+        }
     }
 
     fn get_api_id(&self) -> String {
-        todo!()
+        match self.get_option("x_api_id") {
+            Some(id) => id.to_string(),
+            None => String::from("0"),
+        }
     }
 
     fn get_layer(&self) -> String {
-        todo!()
+        match self.get_option("x_layer") {
+            Some(layer) => layer.to_lowercase(),
+            None => String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER),
+        }
     }
 
     fn get_systems(&self) -> Vec<String> {
-        todo!()
+        let systems: Vec<String> = match self.get_option("x_systems") {
+            Some(systems) => systems
+                .split(',')
+                .map(|system| system.trim().to_lowercase())
+                .filter(|system| !system.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        match systems.is_empty() {
+            true => vec![String::from(crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER)],
+            false => systems,
+        }
     }
 
+    /// Domain is either specified through the `x_domain` option or derived from the package,
+    /// e.g. `package v1.audit.trails;` gives `/v1/audit/trails`
     fn get_domain(&self) -> String {
-        todo!()
+        if let Some(domain) = self.get_option("x_domain") {
+            return domain.to_string();
+        }
+
+        match self.package.is_empty() {
+            true => String::from("NA - package not specified"),
+            false => format!("/{}", self.package.replace('.', "/")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident,
+    Str,
+    Symbol,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    value: String,
+    /// comment(s) written right before the token
+    comment: String,
+}
+
+fn tokenize(val: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = val.chars().collect();
+    let mut tokens = Vec::new();
+    let mut comments: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            let start = i + 2;
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            let line: String = chars[start..i].iter().collect();
+            comments.push(line.trim().to_string());
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start = i + 2;
+            i = start;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            if i + 1 >= chars.len() {
+                return Err(String::from("Unterminated block comment"));
+            }
+            let block: String = chars[start..i].iter().collect();
+            for line in block.lines() {
+                let line = line.trim().trim_start_matches('*').trim();
+                if !line.is_empty() {
+                    comments.push(line.to_string());
+                }
+            }
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            let mut value = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                }
+                value.push(chars[i]);
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(String::from("Unterminated string literal"));
+            }
+            i += 1;
+            tokens.push(Token { kind: TokenKind::Str, value, comment: comments.join("\n") });
+            comments.clear();
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '+' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.' || chars[i] == '-' || chars[i] == '+') {
+                i += 1;
+            }
+            tokens.push(Token { kind: TokenKind::Ident, value: chars[start..i].iter().collect(), comment: comments.join("\n") });
+            comments.clear();
+        } else {
+            tokens.push(Token { kind: TokenKind::Symbol, value: c.to_string(), comment: comments.join("\n") });
+            comments.clear();
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err(String::from("Unexpected end of file")),
+        }
+    }
+
+    fn expect(&mut self, value: &str) -> Result<Token, String> {
+        let token = self.next()?;
+        match token.value == value {
+            true => Ok(token),
+            false => Err(format!("Expected [{}] but got [{}]", value, token.value)),
+        }
+    }
+
+    fn parse(&mut self) -> Result<Proto3, String> {
+        let mut syntax = None;
+        let mut package = String::new();
+        let mut options = HashMap::new();
+        let mut services = Vec::new();
+
+        while let Some(token) = self.peek() {
+            match token.value.as_str() {
+                "syntax" => {
+                    self.next()?;
+                    self.expect("=")?;
+                    syntax = Some(self.next()?.value);
+                    self.expect(";")?;
+                }
+                "package" => {
+                    self.next()?;
+                    package = self.next()?.value;
+                    self.expect(";")?;
+                }
+                "option" => {
+                    let (name, value) = self.parse_option()?;
+                    options.insert(name, value);
+                }
+                "service" => {
+                    services.push(self.parse_service()?);
+                }
+                ";" => {
+                    self.next()?;
+                }
+                _ => {
+                    //import, message, enum, extend... are not relevant for the catalog
+                    self.skip_statement()?;
+                }
+            }
+        }
+
+        match syntax.as_deref() {
+            Some("proto3") => Ok(Proto3 { package, options, services }),
+            Some(other) => Err(format!("Unsupported syntax [{}] - only proto3 is supported", other)),
+            None => Err(String::from("No syntax statement found")),
+        }
+    }
+
+    /// parses `option (name).sub = value;` and returns the last segment of the name
+    /// as key (e.g. `(company.api.x_audience)` gives `x_audience`)
+    fn parse_option(&mut self) -> Result<(String, String), String> {
+        self.expect("option")?;
+
+        let mut name = String::new();
+        loop {
+            let token = self.next()?;
+            if token.value == "=" {
+                break;
+            }
+            name.push_str(&token.value);
+        }
+
+        let value = match self.peek() {
+            Some(token) if token.value == "{" => {
+                self.skip_block()?;
+                String::new()
+            }
+            _ => self.next()?.value,
+        };
+        self.expect(";")?;
+
+        let name = name.replace(['(', ')'], "");
+        let key = name.rsplit('.').next().unwrap_or("").to_string();
+
+        Ok((key, value))
+    }
+
+    fn parse_service(&mut self) -> Result<Service, String> {
+        let keyword = self.expect("service")?;
+        let name = self.next()?.value;
+        self.expect("{")?;
+
+        let mut rpcs = Vec::new();
+        loop {
+            let token = match self.peek() {
+                Some(token) => token.clone(),
+                None => return Err(format!("Unterminated service [{}]", name)),
+            };
+
+            match token.value.as_str() {
+                "}" => {
+                    self.next()?;
+                    break;
+                }
+                "rpc" => rpcs.push(self.parse_rpc()?),
+                "option" => {
+                    self.parse_option()?;
+                }
+                ";" => {
+                    self.next()?;
+                }
+                _ => self.skip_statement()?,
+            }
+        }
+
+        Ok(Service { name, comment: keyword.comment, rpcs })
+    }
+
+    fn parse_rpc(&mut self) -> Result<Rpc, String> {
+        let keyword = self.expect("rpc")?;
+        let name = self.next()?.value;
+
+        let client_streaming = self.parse_rpc_type()?;
+        self.expect("returns")?;
+        let server_streaming = self.parse_rpc_type()?;
+
+        //either `;` or a block of options
+        match self.peek() {
+            Some(token) if token.value == "{" => self.skip_block()?,
+            _ => {
+                self.expect(";")?;
+            }
+        };
+
+        Ok(Rpc {
+            name,
+            comment: keyword.comment,
+            client_streaming,
+            server_streaming,
+        })
+    }
+
+    /// parses `( [stream] Type )` and returns whether it is a stream
+    fn parse_rpc_type(&mut self) -> Result<bool, String> {
+        self.expect("(")?;
+        let mut token = self.next()?;
+        let is_stream = token.kind == TokenKind::Ident && token.value == "stream" && self.peek().map(|t| t.value != ")").unwrap_or(false);
+        if is_stream {
+            token = self.next()?;
+        }
+        if token.kind != TokenKind::Ident {
+            return Err(format!("Expected a message type but got [{}]", token.value));
+        }
+        self.expect(")")?;
+
+        Ok(is_stream)
+    }
+
+    fn skip_statement(&mut self) -> Result<(), String> {
+        loop {
+            let token = self.next()?;
+            match token.value.as_str() {
+                ";" => return Ok(()),
+                "{" => {
+                    self.position -= 1;
+                    return self.skip_block();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_block(&mut self) -> Result<(), String> {
+        self.expect("{")?;
+        let mut depth = 1;
+        while depth > 0 {
+            let token = self.next()?;
+            if token.kind == TokenKind::Symbol {
+                match token.value.as_str() {
+                    "{" => depth += 1,
+                    "}" => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -59,7 +484,6 @@ pub mod tests {
 
     #[test]
     fn test_play_with_trait(){
-        //TODO find a real one
         let proto_spec = "
         syntax=\"proto3\";
         // Enable custom Marshal method.
@@ -84,8 +508,97 @@ pub mod tests {
             }
         }";
 
-        let spec = crate::app::dao::catalog::handlers::implem::proto::Proto3::new(proto_spec);
-        assert_eq!(spec.get_version(), "you should use prost-types crate");
+        let spec = crate::app::dao::catalog::handlers::implem::proto::Proto3::new(proto_spec).unwrap();
+        assert_eq!(spec.get_version(), "N/A");
+        assert_eq!(spec.get_title(), "UserService");
+        assert_eq!(spec.get_paths_len(), 4);
+        assert_eq!(spec.get_audience(), crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER);
+        assert_eq!(spec.get_api_id(), "0");
+        assert_eq!(spec.get_layer(), crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER);
+        assert_eq!(spec.get_systems(), vec![crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER]);
+        assert_eq!(spec.get_domain(), "NA - package not specified");
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths[0].path, "/UserService/AddUser");
+        assert_eq!(all_paths[0].methods[0].method, "unary");
+        assert_eq!(all_paths[1].path, "/UserService/ListUsers");
+        assert_eq!(all_paths[1].methods[0].method, "server_streaming");
+    }
+
+    #[test]
+    fn test_proto3_with_package_and_options(){
+        let proto_spec = r#"
+        syntax = "proto3";
+
+        package v1.audit.trails;
+
+        import "google/protobuf/empty.proto";
+
+        option java_package = "com.company.audit.trails";
+        option (x_audience) = "company";
+        option (x_api_id) = "7c1c4e4a-1d1b-4d43-9c5e-6a6f0a6f1f2e";
+        option (x_layer) = "Application";
+        option (x_systems) = "bpaas, Audit";
+        option (x_version) = "1.2.0";
+
+        /* History Trail service
+         * exposes the audit events */
+        service HistoryTrail {
+            option (google.api.default_host) = "trails.company.com";
+
+            // Get the events.
+            // Events are sorted by creation date.
+            rpc GetEvents(GetEventsRequest) returns (stream Event) {
+                option (google.api.http) = { get: "/v1/audit/trails/events" };
+            }
+            rpc PushEvents(stream Event) returns (google.protobuf.Empty);
+            rpc Sync(stream Event) returns (stream Event);
+        }
+
+        message Event {
+            string id = 1;
+            map<string, string> attributes = 2;
+            oneof payload {
+                string text = 3;
+            }
+        }
+
+        enum Kind {
+            KIND_UNSPECIFIED = 0;
+        }
+        "#;
+
+        let spec = crate::app::dao::catalog::handlers::implem::proto::Proto3::new(proto_spec).unwrap();
+        assert_eq!(spec.get_version(), "1.2.0");
+        assert_eq!(spec.get_title(), "HistoryTrail");
+        assert_eq!(spec.get_description(), "History Trail service\nexposes the audit events");
+        assert_eq!(spec.get_audience(), "company");
+        assert_eq!(spec.get_api_id(), "7c1c4e4a-1d1b-4d43-9c5e-6a6f0a6f1f2e");
+        assert_eq!(spec.get_layer(), "application");
+        assert_eq!(spec.get_systems(), vec!["bpaas", "audit"]);
+        assert_eq!(spec.get_domain(), "/v1/audit/trails");
+        assert_eq!(spec.get_paths_len(), 3);
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths[0].path, "/v1.audit.trails.HistoryTrail/GetEvents");
+        assert_eq!(all_paths[0].methods[0].method, "server_streaming");
+        assert_eq!(all_paths[0].methods[0].summary, "Get the events.");
+        assert_eq!(all_paths[0].methods[0].description, "Get the events.\nEvents are sorted by creation date.");
+        assert_eq!(all_paths[1].methods[0].method, "client_streaming");
+        assert_eq!(all_paths[2].methods[0].method, "bidi_streaming");
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_proto3_version_from_package_and_errors(){
+        let spec = crate::app::dao::catalog::handlers::implem::proto::Proto3::new("syntax = \"proto3\"; package company.trails.v2;").unwrap();
+        assert_eq!(spec.get_version(), "v2");
+        assert_eq!(spec.get_paths_len(), 0);
+
+        let spec = crate::app::dao::catalog::handlers::implem::proto::Proto3::new("syntax = \"proto2\"; package a;");
+        assert!(spec.is_err());
+
+        let spec = crate::app::dao::catalog::handlers::implem::proto::Proto3::new("syntax = \"proto3\"; service A { rpc B(C) returns (D)");
+        assert!(spec.is_err());
+    }
+
+}
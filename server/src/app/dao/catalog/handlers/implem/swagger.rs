@@ -0,0 +1,162 @@
+use crate::app::dao::catalog::handlers::{SpecHandler, SpecFormat, SpecType, Path, SecurityScheme, OperationSecurity};
+use crate::app::dao::catalog::handlers::implem::opanapi::{get_info_attribute, get_paths_from_yaml, get_api_id_from_yaml, get_layer_from_yaml, get_systems_from_yaml, get_security_schemes_from_yaml, get_operations_security_from_yaml};
+
+/// Legacy [Swagger 2.0](https://swagger.io/specification/v2/) specs, read as plain yaml.
+#[derive(Debug, Clone)]
+pub struct V2 {
+    pub spec: serde_yaml::Value,
+}
+impl V2 {
+  pub fn format() -> SpecFormat {
+    SpecFormat {
+      spec_type: SpecType::Swagger2,
+      detect: |document| document.get_root_attribute("swagger").is_some_and(|version| version.starts_with('2')),
+      build: |content| Ok( Box::new(V2::new(content)?) ),
+    }
+  }
+
+  pub fn new(val: &str) -> Result<Self, String> {
+    match serde_yaml::from_str::<serde_yaml::Value>(val) {
+      Ok(spec) => {
+        match spec.get("info").and_then(|info| info.get("title")) {
+          Some(_) => Ok(Self { spec }),
+          None => Err( String::from("Unable to parse content - info attribute not specified") ),
+        }
+      }
+      Err(why) => {
+        Err( format!("Unable to parse content - {:?}", why) )
+      }
+    }
+  }
+}
+
+impl SpecHandler for V2{
+    fn get_version(&self) -> String {
+        get_info_attribute(&self.spec, "version", "N/A")
+    }
+
+    fn get_title(&self) -> String {
+        get_info_attribute(&self.spec, "title", "")
+    }
+
+    fn get_description(&self) -> String {
+        get_info_attribute(&self.spec, "description", "")
+    }
+
+    fn get_paths_len(&self) -> usize {
+        match self.spec.get("paths").and_then(|paths| paths.as_mapping()) {
+            Some(paths) => paths.len(),
+            None => 0,
+        }
+    }
+
+    fn get_paths(&self) -> Vec<Path> {
+        get_paths_from_yaml(&self.spec, self.get_title())
+    }
+
+    fn get_audience(&self) -> String {
+        get_info_attribute(&self.spec, "x-audience", crate::app::dao::catalog::DEFAULT_SYSTEM_LAYER)
+    }
+
+    fn get_api_id(&self) -> String {
+        get_api_id_from_yaml(&self.spec)
+    }
+
+    fn get_layer(&self) -> String {
+        get_layer_from_yaml(&self.spec)
+    }
+
+    fn get_systems(&self) -> Vec<String> {
+        get_systems_from_yaml(&self.spec)
+    }
+
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        Some(self.spec.clone())
+    }
+
+    fn get_security_schemes(&self) -> Vec<SecurityScheme> {
+        get_security_schemes_from_yaml(&self.spec)
+    }
+
+    fn get_operations_security(&self) -> Vec<OperationSecurity> {
+        get_operations_security_from_yaml(&self.spec)
+    }
+
+    /// There is no `servers` in Swagger 2.0, the domain is the `basePath` (`host` and `schemes` being environment specific)
+    fn get_domain(&self) -> String {
+      let base_path = self.spec.get("basePath")
+        .and_then(|base_path| base_path.as_str())
+        .unwrap_or("NA - basePath attribute not specified");
+
+      base_path.to_string()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::app::dao::catalog::handlers::SpecHandler;
+
+    #[test]
+    fn test_swagger_v2(){
+        let swagger_spec = r#"
+        swagger: "2.0"
+        info:
+          title: Swagger Petstore
+          description: This is a sample Pet Store....
+          version: 1.0.5
+          x-audience: external-public
+        x-layer: Functional
+        x-systems:
+          - PetSystem
+          - shop
+        host: petstore.swagger.io
+        basePath: /v2/pets
+        schemes:
+          - https
+        paths:
+          /pet:
+            put:
+              summary: Update an existing pet
+              responses:
+                '200':
+                  description: Successful operation
+            post:
+              summary: Add a new pet to the store
+              description: Add a new pet to the store
+              responses:
+                '405':
+                  description: Invalid input
+          /pet/{petId}:
+            parameters:
+              - name: petId
+                in: path
+                required: true
+                type: integer
+            get:
+              summary: Find pet by ID
+              responses:
+                '200':
+                  description: successful operation
+        "#;
+
+        let spec = crate::app::dao::catalog::handlers::implem::swagger::V2::new(swagger_spec).unwrap();
+        assert_eq!(spec.get_version(), "1.0.5");
+        assert_eq!(spec.get_title(), "Swagger Petstore");
+        assert_eq!(spec.get_description(), "This is a sample Pet Store....");
+        assert_eq!(spec.get_audience(), "external-public");
+        assert_eq!(spec.get_api_id(), "0");
+        assert_eq!(spec.get_layer(), "functional");
+        assert_eq!(spec.get_systems(), vec!["petsystem", "shop"]);
+        assert_eq!(spec.get_domain(), "/v2/pets");
+        assert_eq!(spec.get_paths_len(), 2);
+
+        let all_paths = spec.get_paths();
+        assert_eq!(all_paths[0].path, "/pet");
+        assert_eq!(all_paths[0].methods.len(), 2);
+        assert_eq!(all_paths[0].methods[0].method, "POST");
+        assert_eq!(all_paths[0].methods[1].method, "PUT");
+        assert_eq!(all_paths[1].path, "/pet/{petId}");
+        assert_eq!(all_paths[1].methods.len(), 1);
+        assert_eq!(all_paths[1].methods[0].summary, "Find pet by ID");
+    }
+}
@@ -24,6 +24,21 @@ pub trait SpecHandler: Sync + Send + SpecHandlerClone + Debug {
     fn get_systems(&self) -> Vec<String>;
 
     fn get_domain(&self) -> String;
+
+    /// the (yaml) document of the spec, for the grammars that have one
+    fn get_document(&self) -> Option<serde_yaml::Value> {
+        None
+    }
+
+    /// the security schemes declared by the spec, for the grammars that have some
+    fn get_security_schemes(&self) -> Vec<SecurityScheme> {
+        Vec::new()
+    }
+
+    /// the security requirements and the authorities (`x-has-authority`) of each operation
+    fn get_operations_security(&self) -> Vec<OperationSecurity> {
+        Vec::new()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
@@ -31,12 +46,77 @@ pub trait SpecHandler: Sync + Send + SpecHandlerClone + Debug {
 pub enum SpecType {
     #[strum(serialize = "OpenAPI.v3")]
     OpenAPIv3, 
+    #[strum(serialize = "OpenAPI.v3.1")]
+    OpenAPIv31,
+    #[strum(serialize = "Swagger.v2")]
+    Swagger2,
     #[strum(serialize = "AsyncAPI.v1")]
     AsyncAPIv1, 
     #[strum(serialize = "AsyncAPI.v2")]
     AsyncAPIv2,
+    #[strum(serialize = "AsyncAPI.v3")]
+    AsyncAPIv3,
     #[strum(serialize = "Proto3")]
     Proto3, 
+    #[strum(serialize = "GraphQL")]
+    GraphQL,
+}
+
+/// A spec file, as seen by the `SpecFormat`s when detecting its format.
+/// `root` is only set if the content is a yaml (or json) mapping.
+#[derive(Debug)]
+pub struct SpecDocument<'a> {
+    pub path: &'a str,
+    pub content: &'a str,
+    pub root: Option<serde_yaml::Value>,
+}
+
+impl<'a> SpecDocument<'a> {
+    pub fn new(path: &'a str, content: &'a str) -> Self {
+        let root = match serde_yaml::from_str::<serde_yaml::Value>(content) {
+            Ok(root) if root.is_mapping() => Some(root),
+            _ => None,
+        };
+
+        SpecDocument { path, content, root }
+    }
+
+    /// top-level attribute of the document, e.g. `openapi` or `asyncapi`. numbers are returned as string (`swagger: 2.0`)
+    pub fn get_root_attribute(&self, name: &str) -> Option<String> {
+        match self.root.as_ref()?.get(name)? {
+            serde_yaml::Value::String(val) => Some(val.to_string()),
+            serde_yaml::Value::Number(val) => Some(val.to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn has_extension(&self, extensions: &[&str]) -> bool {
+        match std::path::Path::new(self.path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => extensions.contains(&ext.to_lowercase().as_str()),
+            None => false,
+        }
+    }
+}
+
+/// Declares how a kind of spec is detected and how its `SpecHandler` is built.
+/// Each handler exposes its own `SpecFormat`, they are all registered in `SPEC_FORMATS`.
+pub struct SpecFormat {
+    pub spec_type: SpecType,
+    pub detect: fn(&SpecDocument) -> bool,
+    pub build: fn(&str) -> Result<Box<dyn SpecHandler>, String>,
+}
+
+lazy_static! {
+    pub static ref SPEC_FORMATS: Vec<SpecFormat> = vec![
+        implem::opanapi::V3::format(),
+        implem::opanapi::V31::format(),
+        implem::swagger::V2::format(),
+        implem::asyncapi::V1::format(),
+        implem::asyncapi::V2::format(),
+        implem::asyncapi::V3::format(),
+        implem::proto::Proto3::format(),
+        implem::graphql::GraphQL::format(),
+    ];
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +132,35 @@ pub struct Method {
     pub summary: String
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityScheme {
+    pub name: String,
+    /// e.g. `http`, `apiKey`, `oauth2` or `openIdConnect`
+    pub scheme_type: String,
+    /// e.g. `bearer`, `header X-API-KEY` or the oauth2 flows
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSecurity {
+    pub path: String,
+    pub method: String,
+    /// the `x-audience` of the operation, if it overrides the one of the spec
+    pub audience: Option<String>,
+    /// the alternatives (any of them) of the schemes (all of them) the operation requires, with their scopes,
+    /// either declared on the operation or inherited from the spec - e.g. `[{oauth: [pets.read], key: []}, {}]`
+    pub requirements: Vec<Vec<(String, Vec<String>)>>,
+    /// `x-has-authority` of the operation (or of its path)
+    pub authorities: Vec<String>,
+}
+
+impl OperationSecurity {
+    /// neither a security scheme nor an authority is required, an empty alternative (`{}`) making the schemes optional
+    pub fn is_unsecured(&self) -> bool {
+        (self.requirements.is_empty() || self.requirements.iter().any(|alternative| alternative.is_empty())) && self.authorities.is_empty()
+    }
+}
+
 // SpecItem struct will link to a handler of Box<dyn SpecHandler> *and* must be Clong
 // *but* Trait SpecHandler cannot have Clone (because of Object Safety).
 // Having this Trait kinda help. not fully understood tbh and deeply inspired by 
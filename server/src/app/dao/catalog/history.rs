@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, TimeZone, Utc};
+use git2::{Delta, DiffOptions, ObjectType, Oid, Repository, Sort, TreeWalkMode, TreeWalkResult};
+use log::{debug, info};
+
+use crate::shared::settings::Catalog;
+
+/// A commit which changed a spec file
+#[derive(Debug, Clone)]
+pub struct SpecRevision {
+    pub commit: String,
+    pub author: String,
+    pub date: DateTime<Utc>,
+    pub summary: String,
+    /// `added`, `modified` or `deleted`
+    pub change: String,
+    /// `info.version` before and after the commit
+    pub previous_version: Option<String>,
+    pub version: Option<String>,
+    pub endpoints_before: usize,
+    pub endpoints_after: usize,
+}
+
+impl SpecRevision {
+    pub fn is_version_bump(&self) -> bool {
+        match (&self.previous_version, &self.version) {
+            (Some(previous_version), Some(version)) => previous_version != version,
+            _ => false,
+        }
+    }
+}
+
+/// Revisions of the spec files of a catalog (most recent first), per path relative to `catalog_path`
+pub type CatalogHistory = HashMap<PathBuf, Vec<SpecRevision>>;
+
+lazy_static! {
+    /// the history of a catalog is walked again only once its HEAD moved
+    static ref HISTORIES: Mutex<HashMap<String, (Oid, Arc<CatalogHistory>)>> = Mutex::new(HashMap::new());
+}
+
+/// Walks the git history of the catalog and records, per spec file, every (non merge) commit which changed it.
+/// Specs are parsed as committed, the `$ref`s are not resolved.
+pub fn get_history(catalog: &Catalog) -> Result<Arc<CatalogHistory>, String> {
+    let repo = Repository::discover(&catalog.catalog_path).map_err(|why| format!("Catalog [{}] is not a git repository - {}", catalog.catalog_id, why.message()))?;
+    let head = repo.head().and_then(|head| head.peel_to_commit()).map_err(|why| format!("Unable to get HEAD of catalog [{}] - {}", catalog.catalog_id, why.message()))?;
+
+    if let Some((head_commit, history)) = HISTORIES.lock().unwrap().get(&catalog.catalog_id) {
+        if *head_commit == head.id() {
+            debug!("History of catalog [{:?}] is up to date with [{:?}]", catalog.catalog_id, head_commit);
+            return Ok(Arc::clone(history));
+        }
+    }
+
+    let now = std::time::Instant::now();
+    let history = Arc::new(walk(&repo, head.id(), catalog).map_err(|why| format!("Unable to walk the history of catalog [{}] - {}", catalog.catalog_id, why.message()))?);
+    info!("Walked the history of catalog [{:?}] - [{:?}] spec files in [{}] milli seconds", catalog.catalog_id, history.len(), now.elapsed().as_millis());
+
+    HISTORIES.lock().unwrap().insert(String::from(&catalog.catalog_id), (head.id(), Arc::clone(&history)));
+
+    Ok(history)
+}
+
+pub fn remove_history(catalog_id: &str) {
+    HISTORIES.lock().unwrap().remove(catalog_id);
+}
+
+/// Revisions of a spec of the catalog - `file_path` as returned by `SpecItem::get_file_path`
+pub fn get_spec_history(catalog: &Catalog, file_path: &str) -> Result<Vec<SpecRevision>, String> {
+    let history = get_history(catalog)?;
+
+    Ok(get_revisions(&history, catalog, file_path))
+}
+
+pub fn get_revisions(history: &CatalogHistory, catalog: &Catalog, file_path: &str) -> Vec<SpecRevision> {
+    let relative_path = Path::new(file_path).strip_prefix(&catalog.catalog_path).unwrap_or_else(|_| Path::new(file_path));
+
+    history.get(relative_path).cloned().unwrap_or_default()
+}
+
+/// Changes and version bumps of the specs of a domain
+#[derive(Debug, Clone)]
+pub struct DomainEvolution {
+    pub domain: String,
+    pub specs: usize,
+    pub changes: usize,
+    pub version_bumps: usize,
+    pub first_change: Option<DateTime<Utc>>,
+    pub last_version_bump: Option<DateTime<Utc>>,
+    /// per month (of 30 days) since the first change
+    pub version_bumps_per_month: f64,
+}
+
+/// Aggregates the revisions of the specs, given with their domain, per domain (sorted by name)
+pub fn get_evolutions_per_domain(specs: &[(String, Vec<SpecRevision>)], now: DateTime<Utc>) -> Vec<DomainEvolution> {
+    let mut evolutions: HashMap<&str, DomainEvolution> = HashMap::new();
+    for (domain, revisions) in specs {
+        let evolution = evolutions.entry(domain.as_str()).or_insert_with(|| DomainEvolution {
+            domain: String::from(domain),
+            specs: 0,
+            changes: 0,
+            version_bumps: 0,
+            first_change: None,
+            last_version_bump: None,
+            version_bumps_per_month: 0.0,
+        });
+        evolution.specs += 1;
+        evolution.changes += revisions.len();
+
+        for revision in revisions {
+            if evolution.first_change.map_or(true, |first_change| revision.date < first_change) {
+                evolution.first_change = Some(revision.date);
+            }
+            if revision.is_version_bump() {
+                evolution.version_bumps += 1;
+                if evolution.last_version_bump.map_or(true, |last_version_bump| revision.date > last_version_bump) {
+                    evolution.last_version_bump = Some(revision.date);
+                }
+            }
+        }
+    }
+
+    let mut evolutions: Vec<DomainEvolution> = evolutions.into_values().collect();
+    for evolution in evolutions.iter_mut() {
+        if let Some(first_change) = evolution.first_change {
+            let months = ((now - first_change).num_days() as f64 / 30.0).max(1.0);
+            evolution.version_bumps_per_month = evolution.version_bumps as f64 / months;
+        }
+    }
+    evolutions.sort_by(|a, b| a.domain.cmp(&b.domain));
+
+    evolutions
+}
+
+/// Writes the files of the catalog, as of `reference` (a branch, a tag or a commit), into `target`.
+/// The working copy of the catalog is left untouched. Returns the number of written files.
+pub fn checkout(catalog: &Catalog, reference: &str, target: &Path) -> Result<usize, String> {
+    let repo = Repository::discover(&catalog.catalog_path).map_err(|why| format!("Catalog [{}] is not a git repository - {}", catalog.catalog_id, why.message()))?;
+    let tree = repo.revparse_single(reference).and_then(|object| object.peel_to_tree())
+        .map_err(|why| format!("Unable to find [{}] in catalog [{}] - {}", reference, catalog.catalog_id, why.message()))?;
+    let prefix = get_prefix(&repo, catalog);
+
+    let mut files = 0;
+    let mut error = None;
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        let path = Path::new(root).join(entry.name().unwrap_or_default());
+        let relative_path = match path.strip_prefix(&prefix) {
+            Ok(relative_path) => relative_path,
+            Err(_) => return TreeWalkResult::Skip,
+        };
+
+        let file_path = target.join(relative_path);
+        let written = repo.find_blob(entry.id()).map_err(|why| String::from(why.message()))
+            .and_then(|blob| std::fs::create_dir_all(file_path.parent().unwrap()).and_then(|_| std::fs::write(&file_path, blob.content())).map_err(|why| why.to_string()));
+        match written {
+            Ok(_) => {
+                files += 1;
+                TreeWalkResult::Ok
+            },
+            Err(why) => {
+                error = Some(format!("Unable to write [{:?}] - {}", file_path, why));
+                TreeWalkResult::Abort
+            }
+        }
+    }).map_err(|why| error.clone().unwrap_or_else(|| String::from(why.message())))?;
+    debug!("Checked out [{:?}] files of catalog [{:?}] at [{:?}] into [{:?}]", files, catalog.catalog_id, reference, target);
+
+    match error {
+        Some(why) => Err(why),
+        None => Ok(files),
+    }
+}
+
+/// the catalog may be a sub directory of the repository
+fn get_prefix(repo: &Repository, catalog: &Catalog) -> PathBuf {
+    match (repo.workdir().and_then(|workdir| workdir.canonicalize().ok()), Path::new(&catalog.catalog_path).canonicalize()) {
+        (Some(workdir), Ok(catalog_path)) => catalog_path.strip_prefix(workdir).map(Path::to_path_buf).unwrap_or_default(),
+        _ => PathBuf::new(),
+    }
+}
+
+fn walk(repo: &Repository, head: Oid, catalog: &Catalog) -> Result<CatalogHistory, git2::Error> {
+    let prefix = get_prefix(repo, catalog);
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head)?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+    let mut history = CatalogHistory::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+
+        let parent_tree = match commit.parent_count() {
+            0 => None,
+            _ => Some(commit.parent(0)?.tree()?),
+        };
+        let mut options = DiffOptions::new();
+        if !prefix.as_os_str().is_empty() {
+            options.pathspec(&prefix);
+        }
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut options))?;
+
+        for delta in diff.deltas() {
+            let path = match delta.new_file().path().or_else(|| delta.old_file().path()) {
+                Some(path) => path,
+                None => continue,
+            };
+            let before = read_spec(repo, delta.old_file().id(), path);
+            let after = read_spec(repo, delta.new_file().id(), path);
+            //not a spec, before nor after the commit
+            if before.is_none() && after.is_none() {
+                continue;
+            }
+
+            let revision = SpecRevision {
+                commit: commit.id().to_string(),
+                author: String::from(commit.author().name().unwrap_or("")),
+                date: Utc.timestamp_opt(commit.time().seconds(), 0).single().unwrap_or_else(Utc::now),
+                summary: String::from(commit.summary().unwrap_or("")),
+                change: String::from(match delta.status() {
+                    Delta::Added => "added",
+                    Delta::Deleted => "deleted",
+                    _ => "modified",
+                }),
+                previous_version: before.as_ref().map(|(version, _endpoints)| String::from(version)),
+                version: after.as_ref().map(|(version, _endpoints)| String::from(version)),
+                endpoints_before: before.map(|(_version, endpoints)| endpoints).unwrap_or(0),
+                endpoints_after: after.map(|(_version, endpoints)| endpoints).unwrap_or(0),
+            };
+            let relative_path = path.strip_prefix(&prefix).unwrap_or(path).to_path_buf();
+            history.entry(relative_path).or_default().push(revision);
+        }
+    }
+
+    Ok(history)
+}
+
+/// version and number of endpoints of a committed spec
+fn read_spec(repo: &Repository, oid: Oid, path: &Path) -> Option<(String, usize)> {
+    if oid.is_zero() {
+        return None;
+    }
+
+    let blob = repo.find_blob(oid).ok()?;
+    let content = std::str::from_utf8(blob.content()).ok()?;
+    let spec = super::spec::from_str(String::from(path.to_str()?), String::new(), String::new(), super::spec::to_yaml(content).as_str()).ok()?;
+
+    Some((spec.get_version(), spec.get_paths_len()))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::app::dao::catalog::scm::tests::{commit_file, init_bare_repo};
+    use crate::shared::settings::Catalog;
+
+    fn get_spec(version: &str, paths: &[&str]) -> String {
+        let mut spec = format!("openapi: 3.0.0\ninfo:\n  version: {}\n  title: pets\nservers:\n  - url: https://company.com/v1/pets\npaths:\n", version);
+        for path in paths {
+            spec.push_str(&format!("  {}:\n    get:\n      responses:\n        '200':\n          description: ok\n", path));
+        }
+
+        spec
+    }
+
+    fn get_revision(days_ago: i64, previous_version: Option<&str>, version: &str) -> super::SpecRevision {
+        super::SpecRevision {
+            commit: String::from("commit"),
+            author: String::from("author"),
+            date: chrono::Utc::now() - chrono::Duration::days(days_ago),
+            summary: String::from("summary"),
+            change: String::from(if previous_version.is_some() { "modified" } else { "added" }),
+            previous_version: previous_version.map(String::from),
+            version: Some(String::from(version)),
+            endpoints_before: 0,
+            endpoints_after: 0,
+        }
+    }
+
+    #[test]
+    fn test_get_evolutions_per_domain() {
+        let specs = vec![
+            (String::from("/v1/pets"), vec![ get_revision(10, Some("1.0.0"), "1.1.0"), get_revision(50, Some("1.0.0"), "1.0.0"), get_revision(90, None, "1.0.0") ]),
+            (String::from("/v1/pets"), vec![ get_revision(20, Some("2.0.0"), "2.1.0"), get_revision(30, None, "2.0.0") ]),
+            (String::from("/v1/audit"), vec![]),
+        ];
+
+        let evolutions = super::get_evolutions_per_domain(&specs, chrono::Utc::now());
+        assert_eq!(evolutions.len(), 2);
+        assert_eq!(evolutions[0].domain, "/v1/audit");
+        assert_eq!((evolutions[0].specs, evolutions[0].changes, evolutions[0].version_bumps), (1, 0, 0));
+        assert!(evolutions[0].first_change.is_none());
+
+        let pets = &evolutions[1];
+        assert_eq!((pets.specs, pets.changes, pets.version_bumps), (2, 5, 2));
+        assert_eq!(pets.first_change, Some(specs[0].1[2].date));
+        assert_eq!(pets.last_version_bump, Some(specs[0].1[0].date));
+        assert_eq!(pets.version_bumps_per_month, 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_get_history() {
+        let (bare_path, bare) = init_bare_repo();
+        commit_file(&bare, "main", "pets-openapi.yaml", &get_spec("1.0.0", &["/pets"]));
+        commit_file(&bare, "main", "README.md", "# pets");
+        commit_file(&bare, "main", "pets-openapi.yaml", &get_spec("1.0.0", &["/pets", "/pets/{id}"]));
+        let head = commit_file(&bare, "main", "pets-openapi.yaml", &get_spec("1.1.0", &["/pets", "/pets/{id}", "/owners"]));
+
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-history-{}", uuid::Uuid::new_v4()));
+        let catalog = Catalog {
+            catalog_id: format!("{}", uuid::Uuid::new_v4()),
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_scm_url: format!("file://{}", bare_path.to_str().unwrap()),
+            ..Default::default()
+        };
+        crate::app::dao::catalog::scm::sync(&catalog).unwrap();
+
+        let file_path = catalog_path.join("pets-openapi.yaml");
+        let revisions = super::get_spec_history(&catalog, file_path.to_str().unwrap()).unwrap();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].commit, head.to_string());
+        assert_eq!((revisions[0].previous_version.as_deref(), revisions[0].version.as_deref()), (Some("1.0.0"), Some("1.1.0")));
+        assert_eq!((revisions[0].endpoints_before, revisions[0].endpoints_after), (2, 3));
+        assert!(revisions[0].is_version_bump());
+        assert!(!revisions[1].is_version_bump());
+        assert_eq!(revisions[2].change, "added");
+        assert_eq!((revisions[2].endpoints_before, revisions[2].endpoints_after), (0, 1));
+        assert!(!revisions[2].is_version_bump());
+
+        //not a spec
+        assert!(!super::get_history(&catalog).unwrap().contains_key(std::path::Path::new("README.md")));
+
+        let not_a_repo = Catalog { catalog_id: String::from("not-a-repo"), catalog_path: String::from("/"), ..Default::default() };
+        assert!(super::get_history(&not_a_repo).is_err());
+
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+        std::fs::remove_dir_all(&bare_path).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use log::{debug, warn};
+use regex::Regex;
+use serde_yaml::Value;
+use strum_macros::{Display, EnumString};
+
+use super::diff::{entries, get, to_string, HTTP_METHODS};
+use super::handlers::SpecType;
+use super::spec::SpecItem;
+use crate::shared::settings::Lint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(EnumString, Display)]
+pub enum Severity {
+    #[strum(serialize = "error")]
+    Error,
+    #[strum(serialize = "warning")]
+    Warning,
+    #[strum(serialize = "info")]
+    Info,
+    #[strum(serialize = "hint")]
+    Hint,
+}
+
+/// A guideline a spec does not follow
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule_id: String,
+    pub severity: Severity,
+    /// JSON pointer to the offending node, e.g. `/paths/~1pets~1{id}/get/responses/404`
+    pub pointer: String,
+    pub message: String,
+}
+
+/// A built-in rule: `check` returns the JSON pointer and message of each violation
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+    pub check: fn(&Value, &Lint) -> Vec<(String, String)>,
+}
+
+lazy_static! {
+    pub static ref RULES: Vec<Rule> = vec![
+        Rule { id: "kebab-case-paths", severity: Severity::Warning, description: "path segments are kebab-case", check: check_kebab_case_paths },
+        Rule { id: "property-names-case", severity: Severity::Warning, description: "property names follow the configured case", check: check_property_names_case },
+        Rule { id: "required-x-api-id", severity: Severity::Error, description: "info declares x-api-id", check: check_x_api_id },
+        Rule { id: "required-x-audience", severity: Severity::Error, description: "info declares a valid x-audience", check: check_x_audience },
+        Rule { id: "problem-json-errors", severity: Severity::Warning, description: "error responses are application/problem+json", check: check_problem_json_errors },
+        Rule { id: "versioned-base-path", severity: Severity::Error, description: "the base path of the servers is versioned", check: check_versioned_base_path },
+    ];
+
+    static ref KEBAB_CASE: Regex = Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap();
+    static ref CAMEL_CASE: Regex = Regex::new(r"^[a-z][a-zA-Z0-9]*$").unwrap();
+    static ref SNAKE_CASE: Regex = Regex::new(r"^[a-z][a-z0-9]*(_[a-z0-9]+)*$").unwrap();
+}
+
+const DEFAULT_BASE_PATH_PATTERN: &str = r"^/v[0-9]+(/|$)";
+const AUDIENCES: [&str; 5] = ["component-internal", "business-unit-internal", "company-internal", "external-partner", "external-public"];
+
+/// Evaluates the OpenAPI and Swagger specs against the ruleset, the other specs are not linted
+pub fn lint(spec: &SpecItem, settings: &Lint) -> Vec<Violation> {
+    match spec.get_spec_type() {
+        SpecType::OpenAPIv3 | SpecType::OpenAPIv31 | SpecType::Swagger2 => {},
+        spec_type => {
+            debug!("[{}] specs are not linted - skip spec [{:?}]", spec_type, spec.get_file_path());
+            return Vec::new();
+        }
+    };
+
+    match spec.get_document() {
+        Some(document) => lint_document(&document, settings),
+        None => Vec::new(),
+    }
+}
+
+pub fn lint_document(document: &Value, settings: &Lint) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for rule in RULES.iter() {
+        let severity = match get_severity(rule, settings) {
+            Some(severity) => severity,
+            None => continue,
+        };
+
+        violations.extend((rule.check)(document, settings).into_iter().map(|(pointer, message)| Violation {
+            rule_id: String::from(rule.id),
+            severity,
+            pointer,
+            message,
+        }));
+    }
+
+    violations
+}
+
+/// severity of the rule as configured, none if the rule is `off`
+pub fn get_severity(rule: &Rule, settings: &Lint) -> Option<Severity> {
+    match settings.rules.get(rule.id).map(String::as_str) {
+        Some("off") => None,
+        Some(severity) => Some(severity.parse::<Severity>().unwrap_or_else(|_why| {
+            warn!("Unknown severity [{:?}] for rule [{:?}] - use [{}]", severity, rule.id, rule.severity);
+            rule.severity
+        })),
+        None => Some(rule.severity),
+    }
+}
+
+/// number of violations per rule of all the specs, as stored in the metrics
+pub fn get_violations_per_rule(all_specs: &[SpecItem], settings: &Lint) -> HashMap<String, usize> {
+    let mut stats = HashMap::new();
+    for spec in all_specs {
+        for violation in lint(spec, settings) {
+            *stats.entry(violation.rule_id).or_insert(0) += 1;
+        }
+    }
+
+    stats
+}
+
+/// JSON pointer of the given tokens (RFC 6901)
+pub fn pointer(tokens: &[&str]) -> String {
+    tokens.iter().map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+fn check_kebab_case_paths(document: &Value, _settings: &Lint) -> Vec<(String, String)> {
+    entries(get(document, "paths")).keys()
+        .filter_map(|path| {
+            let segment = path.split('/').find(|segment| !segment.is_empty() && !segment.starts_with('{') && !KEBAB_CASE.is_match(segment))?;
+            Some((pointer(&["paths", path]), format!("segment [{}] of path [{}] is not kebab-case", segment, path)))
+        })
+        .collect()
+}
+
+fn check_property_names_case(document: &Value, settings: &Lint) -> Vec<(String, String)> {
+    let (case, regex) = match settings.property_case.as_str() {
+        "snake_case" => ("snake_case", &*SNAKE_CASE),
+        _ => ("camelCase", &*CAMEL_CASE),
+    };
+
+    let mut violations = Vec::new();
+    //OpenAPI 3 and Swagger 2 named schemas
+    let schemas = [(vec!["components", "schemas"], get(document, "components").and_then(|components| get(components, "schemas"))), (vec!["definitions"], get(document, "definitions"))];
+    for (tokens, schemas) in schemas.iter() {
+        for (name, schema) in entries(*schemas) {
+            let mut schema_tokens: Vec<String> = tokens.iter().map(|token| String::from(*token)).collect();
+            schema_tokens.push(name);
+            check_schema_properties(schema, &mut schema_tokens, case, regex, &mut violations);
+        }
+    }
+
+    violations
+}
+
+fn check_schema_properties(schema: &Value, tokens: &mut Vec<String>, case: &str, regex: &Regex, violations: &mut Vec<(String, String)>) {
+    for (property, property_schema) in entries(get(schema, "properties")) {
+        tokens.push(String::from("properties"));
+        tokens.push(property.clone());
+        if !regex.is_match(&property) {
+            violations.push((pointer(&tokens.iter().map(String::as_str).collect::<Vec<&str>>()), format!("property [{}] is not {}", property, case)));
+        }
+        check_schema_properties(property_schema, tokens, case, regex, violations);
+        tokens.truncate(tokens.len() - 2);
+    }
+
+    if let Some(items) = get(schema, "items") {
+        tokens.push(String::from("items"));
+        check_schema_properties(items, tokens, case, regex, violations);
+        tokens.pop();
+    }
+}
+
+fn check_x_api_id(document: &Value, _settings: &Lint) -> Vec<(String, String)> {
+    match get(document, "info").and_then(|info| get(info, "x-api-id")) {
+        Some(api_id) if !to_string(api_id).trim().is_empty() => Vec::new(),
+        _ => vec![ (pointer(&["info"]), String::from("x-api-id is missing")) ],
+    }
+}
+
+fn check_x_audience(document: &Value, _settings: &Lint) -> Vec<(String, String)> {
+    match get(document, "info").and_then(|info| get(info, "x-audience")).map(to_string) {
+        Some(audience) if AUDIENCES.contains(&audience.as_str()) => Vec::new(),
+        Some(audience) => vec![ (pointer(&["info", "x-audience"]), format!("x-audience [{}] is not one of [{}]", audience, AUDIENCES.join(", "))) ],
+        None => vec![ (pointer(&["info"]), String::from("x-audience is missing")) ],
+    }
+}
+
+fn check_problem_json_errors(document: &Value, _settings: &Lint) -> Vec<(String, String)> {
+    let mut violations = Vec::new();
+    for (path, item) in entries(get(document, "paths")) {
+        for method in HTTP_METHODS {
+            let responses = entries(get(item, method).and_then(|operation| get(operation, "responses")));
+            for (code, response) in responses {
+                if !(code.starts_with('4') || code.starts_with('5') || code == "default") {
+                    continue;
+                }
+                //Swagger 2 responses and references to shared responses have no content
+                let media_types = match get(response, "content") {
+                    Some(content) => entries(Some(content)),
+                    None => continue,
+                };
+                if !media_types.is_empty() && !media_types.contains_key("application/problem+json") {
+                    violations.push((pointer(&["paths", &path, method, "responses", &code]), format!("error response [{}] of [{} {}] is not application/problem+json", code, method.to_uppercase(), path)));
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+fn check_versioned_base_path(document: &Value, settings: &Lint) -> Vec<(String, String)> {
+    let pattern = match settings.base_path_pattern.is_empty() {
+        true => DEFAULT_BASE_PATH_PATTERN,
+        false => settings.base_path_pattern.as_str(),
+    };
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(why) => {
+            warn!("Invalid base path pattern [{:?}] - [{:?}]", pattern, why);
+            return Vec::new();
+        }
+    };
+
+    //Swagger 2
+    if let Some(base_path) = get(document, "basePath").map(to_string) {
+        return match regex.is_match(&base_path) {
+            true => Vec::new(),
+            false => vec![ (pointer(&["basePath"]), format!("base path [{}] does not match [{}]", base_path, pattern)) ],
+        };
+    }
+
+    let servers = get(document, "servers").and_then(Value::as_sequence).cloned().unwrap_or_default();
+    if servers.is_empty() {
+        return vec![ (pointer(&["servers"]), String::from("no server declares the base path")) ];
+    }
+    servers.iter().enumerate()
+        .filter_map(|(index, server)| {
+            let url = get(server, "url").map(to_string).unwrap_or_default();
+            let base_path = match url.find("://") {
+                Some(scheme_end) => url[scheme_end + 3..].find('/').map(|host_end| String::from(&url[scheme_end + 3 + host_end..])).unwrap_or_default(),
+                None => url.clone(),
+            };
+            match regex.is_match(&base_path) {
+                true => None,
+                false => Some((pointer(&["servers", &index.to_string(), "url"]), format!("base path of [{}] does not match [{}]", url, pattern))),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::Severity;
+    use crate::shared::settings::Lint;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.0.0
+  x-audience: everyone
+servers:
+  - url: https://company.com/v1/pets
+  - url: https://company.com/pets
+paths:
+  /pets/{petId}/medicalRecords:
+    get:
+      responses:
+        '200':
+          description: ok
+        '404':
+          description: not found
+          content:
+            application/json:
+              schema:
+                type: object
+        '500':
+          description: error
+          content:
+            application/problem+json:
+              schema:
+                type: object
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+        owners:
+          type: array
+          items:
+            type: object
+            properties:
+              last_name:
+                type: string
+"#;
+
+    fn lint(settings: &Lint) -> Vec<(String, Severity, String)> {
+        let document = serde_yaml::from_str(SPEC).unwrap();
+        super::lint_document(&document, settings).into_iter().map(|violation| (violation.rule_id, violation.severity, violation.pointer)).collect()
+    }
+
+    #[test]
+    fn test_lint_document() {
+        let violations = lint(&Lint::default());
+
+        assert_eq!(violations, vec![
+            (String::from("kebab-case-paths"), Severity::Warning, String::from("/paths/~1pets~1{petId}~1medicalRecords")),
+            (String::from("property-names-case"), Severity::Warning, String::from("/components/schemas/Pet/properties/owners/items/properties/last_name")),
+            (String::from("required-x-api-id"), Severity::Error, String::from("/info")),
+            (String::from("required-x-audience"), Severity::Error, String::from("/info/x-audience")),
+            (String::from("problem-json-errors"), Severity::Warning, String::from("/paths/~1pets~1{petId}~1medicalRecords/get/responses/404")),
+            (String::from("versioned-base-path"), Severity::Error, String::from("/servers/1/url")),
+        ]);
+    }
+
+    #[test]
+    fn test_lint_document_with_settings() {
+        let settings = Lint {
+            rules: [("kebab-case-paths", "error"), ("required-x-api-id", "off"), ("problem-json-errors", "unknown")].iter()
+                .map(|(rule, severity)| (String::from(*rule), String::from(*severity))).collect(),
+            base_path_pattern: String::from("^/(v[0-9]+/)?pets"),
+            property_case: String::from("snake_case"),
+        };
+        let violations = lint(&settings);
+
+        assert_eq!(violations, vec![
+            (String::from("kebab-case-paths"), Severity::Error, String::from("/paths/~1pets~1{petId}~1medicalRecords")),
+            (String::from("required-x-audience"), Severity::Error, String::from("/info/x-audience")),
+            (String::from("problem-json-errors"), Severity::Warning, String::from("/paths/~1pets~1{petId}~1medicalRecords/get/responses/404")),
+        ]);
+    }
+}
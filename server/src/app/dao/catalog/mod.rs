@@ -1,100 +1,372 @@
 ///sub module declaration
 pub mod handlers;
 pub mod spec;
+pub mod refs;
+pub mod review;
+pub mod conflicts;
+pub mod ruleset;
+pub mod diff;
+pub mod history;
+pub mod lint;
+pub mod scm;
+pub mod source;
+pub mod watch;
 
 ///import
 use log::{debug, info, warn, error};
-extern crate yaml_rust;
-use yaml_rust::{Yaml, YamlLoader};
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use rayon::prelude::*;
 use std::vec::Vec;
 use cmd_lib::run_cmd;
-use crate::shared::settings::{Catalog, SETTINGS};
+use crate::shared::settings::Catalog;
 
 use self::spec::SpecItem;
+use self::handlers::SpecType;
 
 #[derive(Debug, Clone)]
 pub struct SpecInError {
     pub file_path: String, 
     pub reason: String,
+    pub category: ErrorCategory,
+}
+
+/// What an error of the catalog is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(strum_macros::EnumString, strum_macros::Display)]
+pub enum ErrorCategory {
+    /// the file is not a valid spec, or one of its `$ref` cannot be resolved
+    #[strum(serialize = "parse")]
+    Parse,
+    /// the spec violates the ruleset of the catalog (refer to `catalog_ruleset_path`)
+    #[strum(serialize = "ruleset")]
+    Ruleset,
+    /// the spec conflicts with other specs, possibly of other catalogs (refer to `conflicts::list_conflicts`)
+    #[strum(serialize = "conflict")]
+    Conflict,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogLoadStats {
+    pub catalog_id: String,
+    pub files: usize,
+    /// files successfully parsed as specs
+    pub specs: usize,
+    pub parsed: usize,
+    pub from_cache: usize,
+    pub errors: usize,
+    pub duration_ms: u128,
+    pub loaded_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A parsed spec file, kept across refreshes while its content does not change
+#[derive(Debug, Clone)]
+struct ParsedFile {
+    path: std::path::PathBuf,
+    catalog_id: String,
+    hash: u64,
+    /// files referenced through `$ref`
+    dependencies: Vec<std::path::PathBuf>,
+    spec: Result<SpecItem, String>,
+    unresolved: Vec<SpecInError>,
 }
 
 const DEFAULT_SYSTEM_LAYER: &str = "default";
 
 pub fn list_specs(catalogs: &Vec<Catalog>) -> Vec<SpecItem> {
-    let specs = match CACHE.cache.get(&String::from("all")) {
-        Some(val) => {
-            info!("got [{:?}] specs from cache ", &val.len() );
-            val
+    let mut specs = Vec::new();
+    for catalog in catalogs {
+        specs.extend(list_catalog_specs(catalog));
+    }
+    info!("OAI specs # from all catalogs - [{:?}]", &specs.len());
+
+    specs
+}
+
+pub fn list_catalog_specs(catalog: &Catalog) -> Vec<SpecItem> {
+    let (specs, _errors) = get_catalog_content(catalog);
+
+    specs
+}
+
+/// The specs and errors of a catalog, loaded if not in cache (each catalog is cached on its own)
+fn get_catalog_content(catalog: &Catalog) -> (Vec<SpecItem>, Vec<SpecInError>) {
+    match (CACHE.cache.get(&catalog.catalog_id), CACHE.errors.get(&catalog.catalog_id)) {
+        (Some(specs), Some(errors)) => {
+            debug!("got [{:?}] specs of catalog [{:?}] from cache", &specs.len(), &catalog.catalog_id);
+            (specs, errors)
         },
-        None => {
-            info!("no specs from cache - will load the catalogs");
+        _ => {
+            info!("no specs from cache - will load catalog [{:?}]", &catalog.catalog_id);
 
-            let mut specs = Vec::new();
-            let mut last_len = 0;
-            let mut specs_in_error: Vec<SpecInError> = Vec::new();
-        
-            for catalog in catalogs{
-                let path = catalog.catalog_path.as_str();
-        
-                info!("Is loading OAI specs files from catalog [{:?}] - [{:?}] with glob pattern {:?}", &catalog.catalog_id, path, &catalog.catalog_include_glob_pattern);
-                //let pattern = format!("{}{}", path, "/**/*.yaml");
-
-                let walker = globwalk::GlobWalkerBuilder::from_patterns(
-                    path,
-                    &catalog.catalog_include_glob_pattern //&["**/*.{yml,yaml}", "!**/{test,tests}/*"],
-                    )
-                    .build()
-                    .unwrap()
-                    .into_iter()
-                    .filter_map(Result::ok);
-
-                for entry in walker {
-                    let file_path: &std::path::Path = entry.path();
-                    debug!("getting spec file [{:?}]", &file_path);
-
-                    let file_content = std::fs::read_to_string(&file_path).unwrap();
-                    let path = String::from(file_path.to_str().unwrap());
-                    let catalog_id = String::from(&catalog.catalog_id);
-                    let catalog_dir = String::from(&catalog.catalog_dir);
-                
-                    match crate::app::dao::catalog::spec::from_str(path, catalog_id, catalog_dir, file_content.as_str()) {
-                        Ok(spec) => {
-                            specs.push(spec);
-                        }
-                        Err(why) => {
-                            specs_in_error.push(SpecInError { file_path: format!("{:?}", file_path) , reason: format!("{:?}", why) })
-                        }
-                    }
-                }
-                
-                debug!("OAI specs # from catalog [{:?}] - [{:?}] is [{:?}]", &catalog.catalog_id, path, specs.len() - last_len);
-                last_len = specs.len();
+            let (specs, specs_in_error, stats) = load_catalog(catalog);
+            CACHE.loads.lock().unwrap().insert(String::from(&catalog.catalog_id), stats);
+            CACHE.cache.insert(String::from(&catalog.catalog_id), specs.to_vec());
+            CACHE.errors.insert(String::from(&catalog.catalog_id), specs_in_error.to_vec());
+
+            (specs, specs_in_error)
+        }
+    }
+}
+
+/// Single-document view of a spec, where the relative `$ref`s have been inlined.
+/// `spec_path` is the path of the spec relative to the catalog directory (as exposed by `/v1/specs`)
+pub fn get_bundled_spec(catalog: &Catalog, spec_path: &str) -> Option<String> {
+    let all_specs = list_catalog_specs(catalog);
+    let spec = all_specs.iter().find(|spec| spec.get_spec_short_path() == spec_path)?;
+
+    match std::fs::read_to_string(spec.get_file_path()) {
+        Ok(content) => Some(self::refs::bundle(spec.get_file_path(), &catalog.catalog_path, self::spec::to_yaml(&content).as_str()).content),
+        Err(why) => {
+            error!("Unable to read spec [{:?}] - [{:?}]", spec.get_file_path(), why);
+            None
+        }
+    }
+}
+
+/// Loads all the specs of a catalog. Files are parsed in parallel, unchanged ones being taken from the per-file cache
+fn load_catalog(catalog: &Catalog) -> (Vec<SpecItem>, Vec<SpecInError>, CatalogLoadStats) {
+    let mut specs = Vec::new();
+    let mut specs_in_error: Vec<SpecInError> = Vec::new();
+
+    let path = catalog.catalog_path.as_str();
+    let now = std::time::Instant::now();
+
+    info!("Is loading OAI specs files from catalog [{:?}] - [{:?}] with glob pattern {:?}", &catalog.catalog_id, path, &catalog.catalog_include_glob_pattern);
+
+    let walker = globwalk::GlobWalkerBuilder::from_patterns(
+        path,
+        &catalog.catalog_include_glob_pattern //&["**/*.{yml,yaml}", "!**/{test,tests}/*"],
+        )
+        .build()
+        .unwrap()
+        .filter_map(Result::ok);
+    let file_paths: Vec<std::path::PathBuf> = walker.map(|entry| entry.into_path()).filter(|file_path| !self::ruleset::is_ruleset_file(catalog, file_path)).collect();
+
+    let parsed_files: Vec<(ParsedFile, bool)> = file_paths.par_iter().map(|file_path| load_spec_file(catalog, file_path)).collect();
+
+    let mut stats = CatalogLoadStats {
+        catalog_id: String::from(&catalog.catalog_id),
+        files: parsed_files.len(),
+        specs: 0,
+        parsed: 0,
+        from_cache: 0,
+        errors: 0,
+        duration_ms: 0,
+        loaded_at: chrono::Utc::now(),
+    };
+    for (parsed_file, from_cache) in parsed_files {
+        match from_cache {
+            true => stats.from_cache += 1,
+            false => stats.parsed += 1,
+        };
+        stats.errors += parsed_file.unresolved.len();
+        specs_in_error.extend(parsed_file.unresolved);
+
+        match parsed_file.spec {
+            Ok(spec) => specs.push(spec),
+            Err(why) => {
+                stats.errors += 1;
+                specs_in_error.push(SpecInError { file_path: format!("{:?}", parsed_file.path) , reason: format!("{:?}", why), category: ErrorCategory::Parse })
             }
-        
-            info!("OAI specs # from all catalogs - [{:?}]", &specs.len());
-        
-            CACHE.cache.insert(String::from("all"), specs.to_vec());
-            CACHE.errors.insert(String::from("all"), specs_in_error.to_vec());
+        }
+    }
+    stats.specs = specs.len();
+    specs_in_error.extend(self::ruleset::evaluate_catalog(catalog, &specs));
 
-            specs
+    //forget the files that have been removed from the catalog
+    let known_paths: std::collections::HashSet<&std::path::PathBuf> = file_paths.iter().collect();
+    CACHE.files.lock().unwrap().retain(|file_path, parsed_file| parsed_file.catalog_id != catalog.catalog_id || known_paths.contains(file_path));
+
+    stats.duration_ms = now.elapsed().as_millis();
+    info!("Loaded catalog [{:?}] - [{:?}] files ([{:?}] parsed, [{:?}] from cache) in [{}] milli seconds", 
+        &stats.catalog_id, stats.files, stats.parsed, stats.from_cache, stats.duration_ms);
+
+    (specs, specs_in_error, stats)
+}
+
+/// Reloads the given files of a catalog (created, modified or deleted), as well as the specs referencing them.
+/// The cached specs and errors are updated in place. Returns the paths of the files which changed and their (new) specs.
+pub fn reload_files(catalog: &Catalog, changed_files: &[std::path::PathBuf]) -> (Vec<String>, Vec<SpecItem>) {
+    //to match the paths built while walking the catalog
+    let catalog_path = std::fs::canonicalize(&catalog.catalog_path).unwrap_or_else(|_| std::path::PathBuf::from(&catalog.catalog_path));
+    let changed_files: Vec<(std::path::PathBuf, std::path::PathBuf)> = changed_files.iter()
+        .map(|file_path| std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf()))
+        .filter_map(|file_path| {
+            let relative_path = file_path.strip_prefix(&catalog_path).ok()?.to_path_buf();
+            Some((std::path::Path::new(&catalog.catalog_path).join(relative_path), file_path))
+        })
+        .collect();
+
+    //all the specs of the catalog are evaluated against the new ruleset
+    let ruleset_changed = self::ruleset::get_ruleset_file(catalog).is_some_and(|ruleset_file| {
+        let canonical_ruleset = std::fs::canonicalize(&ruleset_file).ok();
+        changed_files.iter().any(|(file_path, canonical_path)| *file_path == ruleset_file || canonical_ruleset.as_ref() == Some(canonical_path))
+    });
+
+    let file_paths: std::collections::HashSet<std::path::PathBuf> = globwalk::GlobWalkerBuilder::from_patterns(
+        &catalog.catalog_path,
+        &catalog.catalog_include_glob_pattern
+        )
+        .build()
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .filter(|file_path| !self::ruleset::is_ruleset_file(catalog, file_path))
+        .collect();
+
+    //the changed files, the specs referencing them and the removed ones
+    let mut affected_files: Vec<std::path::PathBuf> = Vec::new();
+    {
+        let mut parsed_files = CACHE.files.lock().unwrap();
+        for (file_path, parsed_file) in parsed_files.iter() {
+            if parsed_file.catalog_id != catalog.catalog_id {
+                continue;
+            }
+            let referenced = parsed_file.dependencies.iter().any(|dependency| changed_files.iter().any(|(_file_path, canonical_path)| dependency == canonical_path));
+            if referenced || !file_paths.contains(file_path) {
+                affected_files.push(file_path.clone());
+            }
+        }
+        parsed_files.retain(|file_path, parsed_file| parsed_file.catalog_id != catalog.catalog_id || file_paths.contains(file_path));
+    }
+    for (changed_file, _canonical_path) in changed_files {
+        if file_paths.contains(&changed_file) && !affected_files.contains(&changed_file) {
+            affected_files.push(changed_file);
+        }
+    }
 
+    let mut specs = Vec::new();
+    let mut specs_in_error = Vec::new();
+    for file_path in affected_files.iter().filter(|file_path| file_paths.contains(*file_path)) {
+        let (parsed_file, _from_cache) = load_spec_file(catalog, file_path);
+        specs_in_error.extend(parsed_file.unresolved);
+        match parsed_file.spec {
+            Ok(spec) => specs.push(spec),
+            Err(why) => specs_in_error.push(SpecInError { file_path: format!("{:?}", parsed_file.path) , reason: format!("{:?}", why), category: ErrorCategory::Parse }),
         }
+    }
+    if !ruleset_changed {
+        specs_in_error.extend(self::ruleset::evaluate_catalog(catalog, &specs));
+    }
+    info!("Reloaded [{:?}] files of catalog [{:?}] - got [{:?}] specs and [{:?}] errors", affected_files.len(), &catalog.catalog_id, specs.len(), specs_in_error.len());
+
+    let affected_files: Vec<String> = affected_files.iter().map(|file_path| String::from(file_path.to_str().unwrap())).collect();
+    let mut affected_errors: Vec<String> = affected_files.iter().map(|file_path| format!("{:?}", std::path::Path::new(file_path))).collect();
+    //the ruleset has been loaded again
+    affected_errors.extend(self::ruleset::get_ruleset_file(catalog).map(|ruleset_file| format!("{:?}", ruleset_file)));
+
+    match (ruleset_changed, CACHE.cache.get(&catalog.catalog_id), CACHE.errors.get(&catalog.catalog_id)) {
+        (false, Some(mut catalog_specs), Some(mut catalog_errors)) => {
+            catalog_specs.retain(|spec| !affected_files.iter().any(|file_path| file_path == spec.get_file_path()));
+            catalog_specs.extend(specs.to_vec());
+            CACHE.cache.insert(String::from(&catalog.catalog_id), catalog_specs);
+
+            catalog_errors.retain(|error| !affected_errors.contains(&error.file_path));
+            catalog_errors.extend(specs_in_error);
+            CACHE.errors.insert(String::from(&catalog.catalog_id), catalog_errors);
+        },
+        //if not in cache, or if the ruleset changed, the whole catalog will be loaded (mostly from the per-file cache) next time
+        _ => CACHE.invalidate(&catalog.catalog_id),
     };
-    
 
-    specs
+    (affected_files, specs)
 }
 
-pub fn list_all_errors() -> Vec<SpecInError> {
-    let errors = match CACHE.errors.get(&String::from("all")) {
-        Some(val) => val,
-        None => {
-            error!("Unable to get all errors from cache");
-            Vec::new()
+/// Reads and parses a spec file, unless its content and the content of the files it references
+/// did not change since the last time it has been parsed. The returned boolean is true if taken from the cache.
+fn load_spec_file(catalog: &Catalog, file_path: &std::path::Path) -> (ParsedFile, bool) {
+    debug!("getting spec file [{:?}]", &file_path);
+
+    //e.g. removed since the catalog has been walked, or not UTF-8
+    let file_content = match std::fs::read_to_string(file_path) {
+        Ok(content) => self::spec::to_yaml(&content),
+        Err(why) => {
+            warn!("Unable to read spec file [{:?}] - [{:?}]", &file_path, why);
+            let parsed_file = ParsedFile {
+                path: file_path.to_path_buf(),
+                catalog_id: String::from(&catalog.catalog_id),
+                hash: 0,
+                dependencies: Vec::new(),
+                spec: Err( format!("Unable to read spec file [{:?}] - {}", &file_path, why) ),
+                unresolved: Vec::new(),
+            };
+            return (parsed_file, false);
         }
     };
 
+    let cached = CACHE.files.lock().unwrap().get(file_path).cloned();
+    if let Some(cached) = cached {
+        if cached.catalog_id == catalog.catalog_id && cached.hash == hash_content(&file_content, &cached.dependencies) {
+            debug!("spec file [{:?}] did not change - got it from cache", &file_path);
+            return (cached, true);
+        }
+    }
+
+    let path = String::from(file_path.to_str().unwrap());
+    let catalog_id = String::from(&catalog.catalog_id);
+    let catalog_dir = String::from(&catalog.catalog_dir);
+
+    //inline the relative $ref (to other files of the catalog)
+    let bundle = self::refs::bundle(&path, &catalog.catalog_path, file_content.as_str());
+    let mut unresolved_refs = Vec::new();
+    for unresolved in bundle.unresolved {
+        debug!("unresolved $ref [{:?}] in spec [{:?}]", &unresolved.reference, &file_path);
+        unresolved_refs.push(SpecInError { file_path: format!("{:?}", file_path) , reason: unresolved.reason, category: ErrorCategory::Parse })
+    }
+
+    let parsed_file = ParsedFile {
+        path: file_path.to_path_buf(),
+        catalog_id: String::from(&catalog.catalog_id),
+        hash: hash_content(&file_content, &bundle.files),
+        dependencies: bundle.files,
+        spec: crate::app::dao::catalog::spec::from_str(path, catalog_id, catalog_dir, bundle.content.as_str()),
+        unresolved: unresolved_refs,
+    };
+    CACHE.files.lock().unwrap().insert(file_path.to_path_buf(), parsed_file.clone());
+
+    (parsed_file, false)
+}
+
+/// Reads and parses a spec file (its relative `$ref`s being inlined), bypassing the cache - e.g. for files checked out of the catalog
+pub fn read_spec_file(catalog: &Catalog, file_path: &std::path::Path) -> Result<SpecItem, String> {
+    let path = String::from(file_path.to_str().unwrap());
+    let content = std::fs::read_to_string(file_path).map_err(|why| format!("Unable to read spec [{}] - {}", path, why))?;
+    let bundle = self::refs::bundle(&path, &catalog.catalog_path, self::spec::to_yaml(&content).as_str());
+
+    self::spec::from_str(path, String::from(&catalog.catalog_id), String::from(&catalog.catalog_dir), bundle.content.as_str())
+}
+
+/// hash of the content of a spec and of the files it references
+fn hash_content(content: &str, dependencies: &Vec<std::path::PathBuf>) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    for dependency in dependencies {
+        //a missing file differs from an empty one
+        std::fs::read(dependency).ok().hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+/// Timing and figures of the last load of a catalog
+pub fn get_catalog_load_stats(catalog_id: &str) -> Option<CatalogLoadStats> {
+    CACHE.loads.lock().unwrap().get(catalog_id).cloned()
+}
+
+pub fn list_errors(catalogs: &Vec<Catalog>) -> Vec<SpecInError> {
+    let mut errors = Vec::new();
+    for catalog in catalogs {
+        errors.extend(list_catalog_errors(catalog));
+    }
+
+    errors
+}
+
+pub fn list_catalog_errors(catalog: &Catalog) -> Vec<SpecInError> {
+    let (_specs, errors) = get_catalog_content(catalog);
+
     errors
 }
 
@@ -102,188 +374,288 @@ pub fn list_all_errors() -> Vec<SpecInError> {
 pub fn refresh_catalogs(catalogs: &Vec<Catalog>, init: bool) {
 
     for catalog in catalogs {
-        match init {
+        //errors have already been logged
+        let _ = pull_catalog(catalog, init);
+    }
+
+    reload_catalogs(catalogs);
+}
+
+/// Clones (on `init`, if `catalog_scm_clone`) or pulls the repository of the catalog.
+/// Archives and HTTP indexes are fetched whatever `init`.
+pub fn pull_catalog(catalog: &Catalog, init: bool) -> Result<(), String> {
+    match source::SourceType::parse(&catalog.catalog_source_type) {
+        Ok(source::SourceType::Git) => {},
+        //errors are also kept in the source status of the catalog
+        Ok(source_type) => return source::fetch(&crate::shared::settings::SETTINGS.database, catalog, source_type).map(|_checksum| ()),
+        Err(why) => {
+            error!("Catalog [{:?}] cannot be pulled - {}", catalog.catalog_id, why);
+            return Err(why);
+        }
+    };
+
+    //native git integration
+    if !catalog.catalog_scm_url.is_empty() {
+        return match init && !catalog.catalog_scm_clone {
             true => {
+                warn!("Catalog [{:?}] - [{:?}] will not be cloned", catalog.catalog_id, catalog.catalog_name);
+                Ok(())
+            },
+            //errors are also kept in the scm status of the catalog
+            false => scm::sync(catalog).map(|_head_commit| ()),
+        };
+    }
 
-                match catalog.catalog_scm_clone {
-                    true => {
-                        let catalog_scm_cmd = catalog.catalog_scm_clone_cmd.to_owned();
-                        let catalog_path = catalog.catalog_path.to_owned();
-        
-                        let cmd = format!("{catalog_scm_cmd} {catalog_path}" );
-                        cmd_lib::set_debug(true);
-                        match run_cmd!{ 
-                            //var a considered as String here; bash -c will make it work (refer to man bash)
-                            bash -c ${cmd}; 
-                        }
-                        {
-                            Ok(val) => {
-                                info!("Clone Git Repo [{:?}] into [{:?}] - got [{:?}]", catalog_scm_cmd, catalog_path, val);
-                            }, 
-                            Err(e) => {
-                                error!("Error while cloning Git Repo [{:?}] into [{:?}] - [{:?}]", catalog_scm_cmd, catalog_path, e);
-                            }
+    match init {
+        true => {
+
+            match catalog.catalog_scm_clone {
+                true => {
+                    let catalog_scm_cmd = catalog.catalog_scm_clone_cmd.to_owned();
+                    let catalog_path = catalog.catalog_path.to_owned();
+    
+                    let cmd = format!("{catalog_scm_cmd} {catalog_path}" );
+                    cmd_lib::set_debug(true);
+                    match run_cmd!{ 
+                        //var a considered as String here; bash -c will make it work (refer to man bash)
+                        bash -c ${cmd}; 
+                    }
+                    {
+                        Ok(val) => {
+                            info!("Clone Git Repo [{:?}] into [{:?}] - got [{:?}]", catalog_scm_cmd, catalog_path, val);
+                            Ok(())
+                        }, 
+                        Err(e) => {
+                            error!("Error while cloning Git Repo [{:?}] into [{:?}] - [{:?}]", catalog_scm_cmd, catalog_path, e);
+                            Err( format!("Error while cloning Git Repo [{}] into [{}] - {}", catalog_scm_cmd, catalog_path, e) )
                         }
-                    }, 
-                    false => {
-                        warn!("Catalog [{:?}] - [{:?}] will not be cloned", catalog.catalog_id, catalog.catalog_name);
                     }
+                }, 
+                false => {
+                    warn!("Catalog [{:?}] - [{:?}] will not be cloned", catalog.catalog_id, catalog.catalog_name);
+                    Ok(())
                 }
-            }, 
-            false => {
-                let catalog_scm_cmd = catalog.catalog_scm_pull_cmd.as_str();
-                let catalog_path = catalog.catalog_path.as_str();
-                
-                cmd_lib::set_debug(true);
-
-                match run_cmd!{ cd ${catalog_path}; bash -c ${catalog_scm_cmd} }{
-                    Ok(val) => {
-                        info!("Refresh Git Repo [{:?}] into [{:?}] - got [{:?}]", catalog_scm_cmd, catalog_path, val);
-                    }, 
-                    Err(e) => {
-                        error!("Error while refreshing Git Repo [{:?}] into [{:?}] - [{:?}]", catalog_scm_cmd, catalog_path, e);
-                    }
+            }
+        }, 
+        false => {
+            let catalog_scm_cmd = catalog.catalog_scm_pull_cmd.as_str();
+            let catalog_path = catalog.catalog_path.as_str();
+            
+            cmd_lib::set_debug(true);
+
+            match run_cmd!{ cd ${catalog_path}; bash -c ${catalog_scm_cmd} }{
+                Ok(val) => {
+                    info!("Refresh Git Repo [{:?}] into [{:?}] - got [{:?}]", catalog_scm_cmd, catalog_path, val);
+                    Ok(())
+                }, 
+                Err(e) => {
+                    error!("Error while refreshing Git Repo [{:?}] into [{:?}] - [{:?}]", catalog_scm_cmd, catalog_path, e);
+                    Err( format!("Error while refreshing Git Repo [{}] into [{}] - {}", catalog_scm_cmd, catalog_path, e) )
                 }
             }
-        };
+        }
     }
+}
 
+/// Reloads the specs of all the catalogs (unchanged files are still taken from the per-file cache)
+pub fn reload_catalogs(catalogs: &Vec<Catalog>) -> Vec<SpecItem> {
     //will force data back in cache
-    let _ = &CACHE.invalidate_all();
+    for catalog in catalogs {
+        CACHE.invalidate(&catalog.catalog_id);
+    }
     
-    self::list_specs(&SETTINGS.catalogs);
+    self::list_specs(catalogs)
+}
+
+/// Reloads the specs of a single catalog, the other ones are left untouched.
+/// Returns the paths of the files known before the reload and the (new) specs of the catalog.
+pub fn reload_catalog(catalog: &Catalog) -> (Vec<String>, Vec<SpecItem>) {
+    let previous_files: Vec<String> = CACHE.files.lock().unwrap().iter()
+        .filter(|(_file_path, parsed_file)| parsed_file.catalog_id == catalog.catalog_id)
+        .map(|(file_path, _parsed_file)| String::from(file_path.to_str().unwrap()))
+        .collect();
+    CACHE.invalidate(&catalog.catalog_id);
+
+    (previous_files, list_catalog_specs(catalog))
+}
+
+/// Forgets the specs, errors and stats of a (removed) catalog. Returns the paths of its spec files
+pub fn purge_catalog(catalog_id: &str) -> Vec<String> {
+    let mut file_paths: Vec<std::path::PathBuf> = Vec::new();
+    CACHE.files.lock().unwrap().retain(|file_path, parsed_file| {
+        if parsed_file.catalog_id == catalog_id {
+            file_paths.push(file_path.clone());
+        }
+        parsed_file.catalog_id != catalog_id
+    });
+    CACHE.loads.lock().unwrap().remove(catalog_id);
+
+    CACHE.invalidate(catalog_id);
+    info!("Purged catalog [{:?}] - [{:?}] files", catalog_id, file_paths.len());
+
+    file_paths.iter().map(|file_path| String::from(file_path.to_str().unwrap())).collect()
+}
+
+/// Where a rule is suppressed by `x-zally-ignore`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(strum_macros::EnumString, strum_macros::Display)]
+pub enum IgnoreScope {
+    /// at the root of the spec, i.e. on all its paths
+    #[strum(serialize = "global")]
+    Global,
+    #[strum(serialize = "path")]
+    Path,
+    #[strum(serialize = "operation")]
+    Operation,
+}
+
+/// A rule suppressed by a spec
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZallyIgnore {
+    /// e.g. `134` or `M010`
+    pub rule: String,
+    pub scope: IgnoreScope,
+    /// as named by `/v1/specs`
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub domain: String,
+    /// none for the global ignores
+    pub path: Option<String>,
+    /// e.g. `GET /pets`, for the ignores on operations
+    pub operation: Option<String>,
 }
 
-pub fn get_zally_ignore(all_specs: &Vec<SpecItem>) -> std::collections::HashMap<i64, usize> {
+/// The `x-zally-ignore` of the OpenAPI (and Swagger) specs
+pub fn list_zally_ignores(all_specs: &[SpecItem]) -> Vec<ZallyIgnore> {
+    let mut ignores = Vec::new();
+
+    for spec in all_specs.iter().filter(|spec| has_zally_ignore(spec)) {
+        let document = match read_yaml_document(spec.get_file_path()) {
+            Some(document) => document,
+            None => continue,
+        };
+
+        let domain = spec.get_domain();
+        ignores.extend(get_zally_ignores(&document).into_iter().map(|(rule, scope, path, operation)| ZallyIgnore {
+            rule,
+            scope,
+            spec_path: String::from(spec.get_spec_short_path()),
+            catalog_id: String::from(spec.get_catalog_id()),
+            domain: String::from(&domain),
+            path,
+            operation,
+        }));
+    }
+
+    ignores
+}
+
+/// The number of paths each rule is suppressed on
+pub fn get_zally_ignore(all_specs: &[SpecItem]) -> std::collections::HashMap<String, usize> {
     let mut merged_stats = std::collections::HashMap::new();
 
-    // let specs = list_specs(path);
-    for spec in all_specs.iter() {
+    for spec in all_specs.iter().filter(|spec| has_zally_ignore(spec)) {
         //need to load the yaml file as OpenAPI crate will remove the x-zally-ignore...
-        let yaml_spec_as_string = std::fs::read_to_string(spec.get_file_path()).unwrap();
+        let yaml_spec_as_string = match std::fs::read_to_string(spec.get_file_path()) {
+            Ok(content) => self::spec::to_yaml(&content),
+            Err(why) => {
+                warn!("Unable to read spec [{:?}] - reason [{:?}]", spec.get_file_path(), why);
+                continue;
+            }
+        };
         let stats = get_zally_ignore_metrics(yaml_spec_as_string.as_str(), spec.get_file_path());
 
-        //some the maps
-        for (key, val) in stats.iter() {
-            match merged_stats.get(key) {
-                Some(known_val) => {
-                    merged_stats.insert(*key, val + known_val);
-                }
-                None => {
-                    merged_stats.insert(*key, *val);
-                }
-            }
+        //sum the maps
+        for (key, val) in stats {
+            *merged_stats.entry(key).or_insert(0) += val;
         }
     }
     merged_stats
 }
 
-fn get_zally_ignore_metrics(spec: &str, spec_name: &str) -> std::collections::HashMap<i64, usize> {
-    debug!(
-        "get_zally_ignore_metrics is called for spec {:?}",
-        spec_name
-    );
+fn has_zally_ignore(spec: &SpecItem) -> bool {
+    match spec.get_spec_type() {
+        SpecType::OpenAPIv3 => true,
+        SpecType::OpenAPIv31 | SpecType::Swagger2 if spec.get_paths_len() > 0 => true,
+        _ => {
+            debug!("zally-ignore only apply to OpenAPI specs with paths - skip spec [{:?}]", spec.get_file_path());
+            false
+        }
+    }
+}
 
-    let docs = match YamlLoader::load_from_str(spec) {
-        Ok(docs) => docs,
+fn read_yaml_document(file_path: &str) -> Option<serde_yaml::Value> {
+    let content = match std::fs::read_to_string(file_path) {
+        Ok(content) => content,
         Err(why) => {
-            panic!("Error while parsing spec {} - :{:?}", spec, why);
+            warn!("Unable to read spec [{:?}] - reason [{:?}]", file_path, why);
+            return None;
         }
-    }; // Result<Vec<Yaml>, ScanError>
-    let doc = docs[0].as_hash().unwrap(); //Option<&Hash> et LinkedHashMap<Yaml, Yaml>;
-
-    let mut stats = std::collections::HashMap::new();
-    //get global zally-ignore
-    {
-        match doc.get(&Yaml::String(String::from("x-zally-ignore"))) {
-            Some(val) => {
-                info!("x-zally-ignore {:?}", val);
-
-                let paths = doc
-                    .get(&Yaml::String(String::from("paths")))
-                    .unwrap()
-                    .as_hash()
-                    .unwrap();
-
-                info!("x-paths {:?}", paths);
-
-                for elt in val.as_vec().unwrap() {
-                    match elt.as_i64() {
-                        Some(val) => {
-                            stats.insert(val, paths.len());
-                        }, 
-                        None => {
-                            //some zally ignore are String . as exple - tt String("M010")
-                            warn!("unable to parse zally-ignore {:?}", elt);
-                        },
-                    };
+    };
 
-                    
-                }
-            }
-            None => info!("no global zally-ignore for spec {:?}", spec_name),
-        };
+    match serde_yaml::from_str(&self::spec::to_yaml(&content)) {
+        Ok(document) => Some(document),
+        Err(why) => {
+            warn!("Unable to parse spec [{:?}] - reason [{:?}]", file_path, why);
+            None
+        }
     }
+}
 
-    //get zally-ignore per path
-    let mut stats_per_path: HashMap<i64, usize> = std::collections::HashMap::new();
-    {
-        let paths = doc
-            .get(&Yaml::String(String::from("paths")))
-            .unwrap()
-            .as_hash()
-            .unwrap();
-
-        for path in paths.iter() {
-            // println!("{:?}", path.0);
-            // println!("{:?}", path.1);
-            let zally = path
-                .1
-                .as_hash()
-                .unwrap()
-                .get(&Yaml::String(String::from("x-zally-ignore")));
-
-            match zally {
-                Some(val) => {
-                    for elt in val.as_vec().unwrap() {
-                        let elt = match elt.as_i64() {
-                            Some(val) => val,
-                            None => {
-                                warn!("Got zally-ignore [{:?}]", elt);
-                                -1
-                            }
-                        };
-                        let stat = stats_per_path.get(&elt).cloned();
-                        match stat {
-                            Some(val) => {
-                                stats_per_path.insert(elt, val + 1);
-                            }
-                            None => {
-                                stats_per_path.insert(elt, 1);
-                            }
-                        }
-                    }
-                }
-                None => {
-                    info!(
-                        "no zally-ignore on paths for spec {:?} and path {:?}",
-                        spec_name, path.0
-                    );
-                }
+/// rule, scope, path and operation of the `x-zally-ignore` of a spec
+fn get_zally_ignores(document: &serde_yaml::Value) -> Vec<(String, IgnoreScope, Option<String>, Option<String>)> {
+    use self::diff::{entries, get, HTTP_METHODS};
+    use self::review::get_zally_rules;
+
+    let mut ignores: Vec<(String, IgnoreScope, Option<String>, Option<String>)> = get_zally_rules(document).into_iter()
+        .map(|rule| (rule, IgnoreScope::Global, None, None))
+        .collect();
+
+    for (path, item) in entries(get(document, "paths")) {
+        ignores.extend(get_zally_rules(item).into_iter().map(|rule| (rule, IgnoreScope::Path, Some(String::from(&path)), None)));
+
+        for method in HTTP_METHODS {
+            if let Some(operation) = get(item, method) {
+                let operation_name = format!("{} {}", method.to_uppercase(), path);
+                ignores.extend(get_zally_rules(operation).into_iter().map(|rule| (rule, IgnoreScope::Operation, Some(String::from(&path)), Some(String::from(&operation_name)))));
             }
         }
-        // println!("stats_per_path {:?}", stats_per_path);
-        // println!("len {:?}", paths.len());
     }
 
-    //merge both maps
-    for stat in stats_per_path.iter() {
-        //check if stat already exist in global, if not add it to stats
-        if stats.contains_key(stat.0) {
-            debug!("stats {:?} already in global stats", stat.0);
-        } else {
-            stats.insert(*stat.0, *stat.1);
+    ignores
+}
+
+/// The number of paths each rule is suppressed on - a global ignore suppresses the rule on all the paths,
+/// an ignore on an operation on its path
+fn get_zally_ignore_metrics(spec: &str, spec_name: &str) -> std::collections::HashMap<String, usize> {
+    debug!(
+        "get_zally_ignore_metrics is called for spec {:?}",
+        spec_name
+    );
+
+    let document: serde_yaml::Value = match serde_yaml::from_str(spec) {
+        Ok(document) => document,
+        Err(why) => {
+            warn!("Unable to parse spec [{:?}] - reason [{:?}]", spec_name, why);
+            return std::collections::HashMap::new();
         }
+    };
+
+    let ignores = get_zally_ignores(&document);
+    let paths = self::diff::entries(self::diff::get(&document, "paths"));
+
+    let mut stats = std::collections::HashMap::new();
+    for (rule, scope, _path, _operation) in ignores.iter().filter(|(_rule, scope, _path, _operation)| *scope == IgnoreScope::Global) {
+        debug!("[{:?}] x-zally-ignore [{:?}] for spec [{:?}]", scope, rule, spec_name);
+        stats.insert(String::from(rule), paths.len());
+    }
+    //a path is counted once per rule, whatever the number of its operations suppressing it
+    let ignored_paths: std::collections::BTreeSet<(&String, &String)> = ignores.iter()
+        .filter(|(rule, _scope, _path, _operation)| !stats.contains_key(rule))
+        .filter_map(|(rule, _scope, path, _operation)| path.as_ref().map(|path| (rule, path)))
+        .collect();
+    for (rule, _path) in ignored_paths {
+        *stats.entry(String::from(rule)).or_insert(0) += 1;
     }
 
     stats
@@ -295,12 +667,23 @@ pub fn get_endpoints_num_per_audience(
     let mut merged_stats = std::collections::HashMap::new();
 
     for spec in all_specs.iter() {
-        //need to load the yaml file as OpenAPI crate will remove the x-zally-ignore...
-        let yaml_spec_as_string = std::fs::read_to_string(spec.get_file_path()).unwrap();
-        let stats = get_endpoints_num_per_audience_metrics(
-            yaml_spec_as_string.as_str(),
-            spec.get_file_path(),
-        );
+        let stats = match spec.get_spec_type() {
+            SpecType::OpenAPIv3 => match spec.get_document() {
+                //the loaded spec, the file may have changed since
+                Some(document) => get_endpoints_num_per_audience_metrics(&document, spec.get_file_path()),
+                None => {
+                    warn!("Unable to get the document of spec [{:?}], skipped", spec.get_file_path());
+                    continue;
+                }
+            },
+            _ => {
+                //no paths attribute (AsyncAPI channels, proto rpc...), rely on the handler
+                let mut stats = HashMap::new();
+                stats.insert(spec.get_audience(), spec.get_paths_len());
+
+                stats
+            }
+        };
 
         //sum the maps
         for (key, val) in stats.iter() {
@@ -318,7 +701,7 @@ pub fn get_endpoints_num_per_audience(
 }
 
 fn get_endpoints_num_per_audience_metrics(
-    spec: &str,
+    document: &serde_yaml::Value,
     spec_name: &str,
 ) -> std::collections::HashMap<String, usize> {
     debug!(
@@ -326,46 +709,21 @@ fn get_endpoints_num_per_audience_metrics(
         spec_name
     );
 
-    let docs = match YamlLoader::load_from_str(spec) {
-        Ok(docs) => docs,
-        Err(why) => {
-            panic!("Error while parsing spec {} - :{:?}", spec, why);
-        }
-    }; // Result<Vec<Yaml>, ScanError>
-    let doc = docs[0].as_hash().unwrap(); //Option<&Hash> et LinkedHashMap<Yaml, Yaml>;
-    let doc_info_tag = doc
-        .get(&Yaml::String(String::from("info")))
-        .unwrap()
-        .as_hash()
-        .unwrap(); //Option<&Hash> et LinkedHashMap<Yaml, Yaml>;
-
-    let paths = doc
-        .get(&Yaml::String(String::from("paths")))
-        .unwrap()
-        .as_hash()
-        .unwrap();
-    let num_of_endpoints = paths.len();
+    let num_of_endpoints = self::diff::entries(self::diff::get(document, "paths")).len();
+    let audience = self::diff::get(document, "info")
+        .and_then(|info| self::diff::get(info, "x-audience"))
+        .and_then(|audience| audience.as_str());
 
     let mut stats = std::collections::HashMap::new();
-    {
-        match doc_info_tag.get(&Yaml::String(String::from("x-audience"))) {
-            Some(val) => {
-                info!("found audience [{:?}] for spec [{:?}]", val, spec_name);
-                let audience_name = String::from(val.as_str().unwrap());
-                match stats.get(&audience_name) {
-                    Some(val) => {
-                        stats.insert(audience_name, val + num_of_endpoints);
-                    }
-                    None => {
-                        stats.insert(audience_name, num_of_endpoints);
-                    }
-                };
-            }
-            None => {
-                info!("no audience for spec [{:?}]", spec_name);
-                stats.insert(String::from("no audience"), num_of_endpoints);
-            }
-        };
+    match audience {
+        Some(audience_name) => {
+            info!("found audience [{:?}] for spec [{:?}]", audience_name, spec_name);
+            stats.insert(String::from(audience_name), num_of_endpoints);
+        }
+        None => {
+            info!("no audience for spec [{:?}]", spec_name);
+            stats.insert(String::from("no audience"), num_of_endpoints);
+        }
     };
 
     stats
@@ -393,8 +751,14 @@ struct Cache {
     //TODO there is likely a way to have a Cache that can Store Any - but I am struggling with + Send + Sync
     cache: quick_cache::sync::Cache<String, Vec<SpecItem>>,
     errors: quick_cache::sync::Cache<String, Vec<SpecInError>>,
+    /// parsed spec files per path, not invalidated on refresh
+    files: Mutex<HashMap<std::path::PathBuf, ParsedFile>>,
+    loads: Mutex<HashMap<String, CatalogLoadStats>>,
 }
 
+/// catalogs whose specs and errors are kept in cache
+const CACHE_CAPACITY: usize = 256;
+
 lazy_static! {
     static ref CACHE: Cache = Cache::new();
 }
@@ -402,16 +766,19 @@ lazy_static! {
 impl Cache {
     fn new() -> Self {
         let cache = Cache{
-            cache: quick_cache::sync::Cache::new(2),
-            errors: quick_cache::sync::Cache::new(2),
+            cache: quick_cache::sync::Cache::new(CACHE_CAPACITY),
+            errors: quick_cache::sync::Cache::new(CACHE_CAPACITY),
+            files: Mutex::new(HashMap::new()),
+            loads: Mutex::new(HashMap::new()),
         };
 
         cache
     }
 
-    fn invalidate_all(&self){
-        let _ = self.cache.remove("all");
-        let _ = self.errors.remove("all");
+    /// specs and errors of the catalog
+    fn invalidate(&self, catalog_id: &str){
+        let _ = self.cache.remove(catalog_id);
+        let _ = self.errors.remove(catalog_id);
     } 
 }
 
@@ -593,9 +960,9 @@ pub mod tests {
 
         println!("*** results : {:?}", results);
 
-        assert_eq!(results.get(&134i64).unwrap(), &3usize);
-        assert_eq!(results.get(&120i64).unwrap(), &3usize);
-        assert_eq!(results.get(&164i64).unwrap(), &2usize);
+        assert_eq!(results.get("134").unwrap(), &3usize);
+        assert_eq!(results.get("120").unwrap(), &3usize);
+        assert_eq!(results.get("164").unwrap(), &2usize);
     }
 
     #[test]
@@ -636,8 +1003,63 @@ pub mod tests {
 
         println!("*** results : {:?}", results);
 
-        assert_eq!(results.get(&164i64).unwrap(), &1usize);
-        assert_eq!(results.get(&-1i64).unwrap(), &1usize);
+        assert_eq!(results.get("164").unwrap(), &1usize);
+        assert_eq!(results.get("M10").unwrap(), &1usize);
+    }
+
+    #[test]
+    fn test_get_zally_ignores() {
+        let spec = "
+        openapi: \"3.0.0\"
+        info:
+          version: 1.0.0
+          title: an API ...
+        x-zally-ignore: [ M010, 134 ]
+        paths:
+          /v1/a/b:
+            x-zally-ignore: 164
+            get:
+              x-zally-ignore: [ 164, 101 ]
+              responses:
+                '200':
+                  description: returns...
+            post:
+              x-zally-ignore: [ 101 ]
+              responses:
+                '200':
+                  description: returns...
+          /v1/a/c:
+            get:
+              x-zally-ignore: [ M010 ]
+              responses:
+                '200':
+                  description: returns...
+        ";
+
+        let document: serde_yaml::Value = serde_yaml::from_str(spec).unwrap();
+        let ignores = super::get_zally_ignores(&document);
+        let summary: Vec<(&str, super::IgnoreScope, Option<&str>, Option<&str>)> = ignores.iter()
+            .map(|(rule, scope, path, operation)| (rule.as_str(), *scope, path.as_deref(), operation.as_deref()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("134", super::IgnoreScope::Global, None, None),
+            ("M010", super::IgnoreScope::Global, None, None),
+            ("164", super::IgnoreScope::Path, Some("/v1/a/b"), None),
+            ("101", super::IgnoreScope::Operation, Some("/v1/a/b"), Some("GET /v1/a/b")),
+            ("164", super::IgnoreScope::Operation, Some("/v1/a/b"), Some("GET /v1/a/b")),
+            ("101", super::IgnoreScope::Operation, Some("/v1/a/b"), Some("POST /v1/a/b")),
+            ("M010", super::IgnoreScope::Operation, Some("/v1/a/c"), Some("GET /v1/a/c")),
+        ]);
+
+        let results = super::get_zally_ignore_metrics(spec, "name");
+        assert_eq!(results.len(), 4);
+        assert_eq!(results.get("M010").unwrap(), &2usize);
+        assert_eq!(results.get("134").unwrap(), &2usize);
+        assert_eq!(results.get("164").unwrap(), &1usize);
+        assert_eq!(results.get("101").unwrap(), &1usize);
+
+        //not a yaml document
+        assert!(super::get_zally_ignore_metrics("paths: [ /v1/a/b", "name").is_empty());
     }
 
     #[test]
@@ -665,11 +1087,277 @@ pub mod tests {
                   description: returns...  
         ";
 
-        let results = super::get_endpoints_num_per_audience_metrics(spec, "name");
+        let document: serde_yaml::Value = serde_yaml::from_str(spec).unwrap();
+        let results = super::get_endpoints_num_per_audience_metrics(&document, "name");
 
         assert_eq!(results.get("an audience").unwrap(), &2usize);
     }
 
+    #[test]
+    fn test_get_endpoints_num_per_audience_for_asyncapi() {
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/catalog/async/messaging-2.6.0.yml");
+
+        let content = std::fs::read_to_string(path.as_path()).unwrap();
+        let spec = super::spec::from_str(String::from(path.to_str().unwrap()), String::from("not used here"), String::from("not used here"), content.as_str()).unwrap();
+
+        let results = super::get_endpoints_num_per_audience(&vec![spec.clone()]);
+        assert_eq!(results.get("corporate").unwrap(), &1usize);
+
+        let results = super::get_zally_ignore(&vec![spec]);
+        assert_eq!(results.len(), 0);
+    }
+
+    #[test]
+    fn test_metrics_for_json_spec() {
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/json/time-series-openapi.json");
+
+        let content = super::spec::to_yaml( &std::fs::read_to_string(path.as_path()).unwrap() );
+        let spec = super::spec::from_str(String::from(path.to_str().unwrap()), String::from("not used here"), String::from("not used here"), content.as_str()).unwrap();
+        assert_eq!(spec.get_spec_type(), super::SpecType::OpenAPIv3);
+        assert_eq!(spec.get_domain(), "/v1/analytics/time-series");
+
+        let results = super::get_endpoints_num_per_audience(&vec![spec.clone()]);
+        assert_eq!(results.get("company").unwrap(), &2usize);
+
+        let results = super::get_zally_ignore(std::slice::from_ref(&spec));
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.get("134").unwrap(), &2usize);
+        assert_eq!(results.get("101").unwrap(), &1usize);
+        assert_eq!(results.get("104").unwrap(), &1usize);
+
+        let ignores = super::list_zally_ignores(&[spec]);
+        assert_eq!(ignores.len(), 3);
+        assert!(ignores.iter().all(|ignore| ignore.domain == "/v1/analytics/time-series"));
+        assert_eq!(ignores[1].scope, super::IgnoreScope::Path);
+        assert_eq!(ignores[1].path.as_deref(), Some("/series"));
+    }
+
+    #[test]
+    fn test_load_catalog_only_reparses_changed_files() {
+        let mut fixtures = std::path::PathBuf::new();
+        fixtures.push(env!("CARGO_MANIFEST_DIR"));
+        fixtures.push("./tests/data/refs");
+
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-load-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(catalog_path.join("paths")).unwrap();
+        std::fs::create_dir_all(catalog_path.join("common")).unwrap();
+        for file in ["pets-openapi.yaml", "paths/pets.yaml", "common/pet.yaml"] {
+            std::fs::copy(fixtures.join(file), catalog_path.join(file)).unwrap();
+        }
+
+        let catalog = Catalog{
+            catalog_id: String::from("load-uuid"),
+            catalog_name: String::from("name"), 
+            catalog_dir: String::from("not used here"),
+            catalog_include_glob_pattern: vec![ String::from("*-openapi.yaml") ],
+            catalog_scm_clone_cmd: String::from("not used here"), 
+            catalog_scm_pull_cmd: String::from("not used here"), 
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_scm_clone: false,
+            catalog_http_base_uri: String::from("not used here"),
+            ..Default::default()
+        };
+
+        let (specs, errors, stats) = super::load_catalog(&catalog);
+        assert_eq!(specs.len(), 1);
+        assert_eq!(errors.len(), 0);
+        assert_eq!((stats.files, stats.parsed, stats.from_cache), (1, 1, 0));
+
+        let (specs, _errors, stats) = super::load_catalog(&catalog);
+        assert_eq!(specs.len(), 1);
+        assert_eq!((stats.files, stats.parsed, stats.from_cache), (1, 0, 1));
+
+        //a referenced file changed
+        let pet = std::fs::read_to_string(catalog_path.join("common/pet.yaml")).unwrap();
+        std::fs::write(catalog_path.join("common/pet.yaml"), pet.replace("Category:", "Family:")).unwrap();
+        let (_specs, errors, stats) = super::load_catalog(&catalog);
+        assert_eq!((stats.files, stats.parsed, stats.from_cache), (1, 1, 0));
+        //Category is referenced by both /pets/{petId} and Pet
+        assert_eq!(errors.len(), 2);
+        assert_eq!(stats.errors, 2);
+
+        std::fs::write(catalog_path.join("not-a-spec-openapi.yaml"), "info: {}").unwrap();
+        let (specs, errors, stats) = super::load_catalog(&catalog);
+        assert_eq!(specs.len(), 1);
+        assert_eq!(errors.len(), 3);
+        assert_eq!((stats.files, stats.parsed, stats.from_cache), (2, 1, 1));
+
+        std::fs::remove_file(catalog_path.join("not-a-spec-openapi.yaml")).unwrap();
+        let (_specs, _errors, stats) = super::load_catalog(&catalog);
+        assert_eq!((stats.files, stats.parsed, stats.from_cache), (1, 0, 1));
+        assert!(!super::CACHE.files.lock().unwrap().contains_key(&catalog_path.join("not-a-spec-openapi.yaml")));
+
+        //a file which cannot be read is reported, not loaded
+        std::fs::write(catalog_path.join("binary-openapi.yaml"), [0xff, 0xfe, 0x00]).unwrap();
+        let (specs, errors, stats) = super::load_catalog(&catalog);
+        assert_eq!(specs.len(), 1);
+        assert_eq!((stats.files, stats.parsed, stats.from_cache), (2, 1, 1));
+        assert!(errors.iter().any(|error| error.category == super::ErrorCategory::Parse && error.reason.contains("Unable to read spec file")));
+
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+    }
+
+    #[test]
+    fn test_reload_files_of_a_catalog() {
+        let mut fixtures = std::path::PathBuf::new();
+        fixtures.push(env!("CARGO_MANIFEST_DIR"));
+        fixtures.push("./tests/data/refs");
+
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-reload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(catalog_path.join("paths")).unwrap();
+        std::fs::create_dir_all(catalog_path.join("common")).unwrap();
+        for file in ["pets-openapi.yaml", "paths/pets.yaml", "common/pet.yaml"] {
+            std::fs::copy(fixtures.join(file), catalog_path.join(file)).unwrap();
+        }
+
+        let catalog = Catalog{
+            catalog_id: String::from("reload-uuid"),
+            catalog_include_glob_pattern: vec![ String::from("*-openapi.yaml") ],
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            ..Default::default()
+        };
+        let (specs, _errors, _stats) = super::load_catalog(&catalog);
+        assert_eq!(specs.len(), 1);
+
+        //a referenced file changed, the spec is reloaded
+        let (reloaded_files, specs) = super::reload_files(&catalog, &[catalog_path.join("common/pet.yaml")]);
+        assert_eq!(reloaded_files, vec![String::from(catalog_path.join("pets-openapi.yaml").to_str().unwrap())]);
+        assert_eq!(specs.len(), 1);
+
+        //a new spec
+        std::fs::copy(fixtures.join("pets-openapi.yaml"), catalog_path.join("other-openapi.yaml")).unwrap();
+        let (reloaded_files, specs) = super::reload_files(&catalog, &[catalog_path.join("other-openapi.yaml")]);
+        assert_eq!(reloaded_files, vec![String::from(catalog_path.join("other-openapi.yaml").to_str().unwrap())]);
+        assert_eq!(specs[0].get_file_path(), catalog_path.join("other-openapi.yaml").to_str().unwrap());
+
+        //a file which does not match the glob pattern
+        let (reloaded_files, _specs) = super::reload_files(&catalog, &[catalog_path.join("paths/other.yaml")]);
+        assert_eq!(reloaded_files.len(), 0);
+
+        //a removed spec
+        std::fs::remove_file(catalog_path.join("other-openapi.yaml")).unwrap();
+        let (reloaded_files, specs) = super::reload_files(&catalog, &[catalog_path.join("other-openapi.yaml")]);
+        assert_eq!(reloaded_files, vec![String::from(catalog_path.join("other-openapi.yaml").to_str().unwrap())]);
+        assert_eq!(specs.len(), 0);
+        assert!(!super::CACHE.files.lock().unwrap().contains_key(&catalog_path.join("other-openapi.yaml")));
+
+        //the spec is reloaded once the file it references is created
+        let owners = "openapi: 3.0.0\ninfo:\n  title: owners\n  version: 1.0.0\npaths: {}\ncomponents:\n  schemas:\n    Owner:\n      $ref: './common/owner.yaml#/Owner'\n";
+        std::fs::write(catalog_path.join("owners-openapi.yaml"), owners).unwrap();
+        let (reloaded_files, _specs) = super::reload_files(&catalog, &[catalog_path.join("owners-openapi.yaml")]);
+        assert_eq!(reloaded_files.len(), 1);
+        std::fs::write(catalog_path.join("common/owner.yaml"), "Owner:\n  type: object\n").unwrap();
+        let (reloaded_files, _specs) = super::reload_files(&catalog, &[catalog_path.join("common/owner.yaml")]);
+        assert_eq!(reloaded_files, vec![String::from(catalog_path.join("owners-openapi.yaml").to_str().unwrap())]);
+        let (_specs, errors, stats) = super::load_catalog(&catalog);
+        assert_eq!(stats.from_cache, 2);
+        assert!(!errors.iter().any(|error| error.reason.contains("owner.yaml")));
+
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+    }
+
+    #[test]
+    fn test_reload_ruleset_of_a_catalog() {
+        let mut fixtures = std::path::PathBuf::new();
+        fixtures.push(env!("CARGO_MANIFEST_DIR"));
+        fixtures.push("./tests/data/refs");
+
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-ruleset-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(catalog_path.join("paths")).unwrap();
+        std::fs::create_dir_all(catalog_path.join("common")).unwrap();
+        for file in ["pets-openapi.yaml", "paths/pets.yaml", "common/pet.yaml"] {
+            std::fs::copy(fixtures.join(file), catalog_path.join(file)).unwrap();
+        }
+        let ruleset = "rules:\n  - id: owner\n    given: $.info\n    then:\n      field: x-owner\n      function: exists\n";
+        std::fs::write(catalog_path.join("ruleset.yaml"), ruleset).unwrap();
+
+        let catalog = Catalog{
+            catalog_id: String::from("reload-ruleset-uuid"),
+            catalog_include_glob_pattern: vec![ String::from("*-openapi.yaml") ],
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_ruleset_path: String::from("ruleset.yaml"),
+            ..Default::default()
+        };
+        let count_violations = || super::list_catalog_errors(&catalog).iter().filter(|error| error.category == super::ErrorCategory::Ruleset).count();
+        assert_eq!(count_violations(), 1);
+
+        //the unchanged spec is evaluated against the new ruleset
+        std::fs::write(catalog_path.join("ruleset.yaml"), "rules: []\n").unwrap();
+        let (reloaded_files, _specs) = super::reload_files(&catalog, &[catalog_path.join("ruleset.yaml")]);
+        assert_eq!(reloaded_files.len(), 0);
+        assert_eq!(count_violations(), 0);
+        assert_eq!(super::list_catalog_specs(&catalog).len(), 1);
+
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+    }
+
+    #[test]
+    fn test_purge_catalog() {
+        let mut fixtures = std::path::PathBuf::new();
+        fixtures.push(env!("CARGO_MANIFEST_DIR"));
+        fixtures.push("./tests/data/refs");
+
+        let catalog = Catalog{
+            catalog_id: String::from("purge-uuid"),
+            catalog_include_glob_pattern: vec![ String::from("*-openapi.yaml") ],
+            catalog_path: fixtures.clone().into_os_string().into_string().unwrap(),
+            ..Default::default()
+        };
+        let (specs, _errors, _stats) = super::load_catalog(&catalog);
+        assert_eq!(specs.len(), 2);
+
+        let mut removed_files = super::purge_catalog("purge-uuid");
+        removed_files.sort();
+        assert_eq!(removed_files, vec![
+            String::from(fixtures.join("broken-openapi.yaml").to_str().unwrap()),
+            String::from(fixtures.join("pets-openapi.yaml").to_str().unwrap()),
+        ]);
+        assert!(!super::CACHE.files.lock().unwrap().values().any(|parsed_file| parsed_file.catalog_id == "purge-uuid"));
+        assert_eq!(super::purge_catalog("purge-uuid").len(), 0);
+    }
+
+    #[test]
+    fn test_catalogs_are_cached_and_reloaded_separately() {
+        let mut fixtures = std::path::PathBuf::new();
+        fixtures.push(env!("CARGO_MANIFEST_DIR"));
+        fixtures.push("./tests/data");
+
+        let refs = Catalog{
+            catalog_id: String::from("separate-refs-uuid"),
+            catalog_include_glob_pattern: vec![ String::from("*-openapi.yaml") ],
+            catalog_path: fixtures.join("refs").into_os_string().into_string().unwrap(),
+            ..Default::default()
+        };
+        let catalog = Catalog{
+            catalog_id: String::from("separate-catalog-uuid"),
+            catalog_include_glob_pattern: vec![ String::from("**/*.{yml,yaml}"), String::from("!**/{test,tests}/*") ],
+            catalog_path: fixtures.join("catalog").into_os_string().into_string().unwrap(),
+            ..Default::default()
+        };
+        let catalogs = vec![refs.clone(), catalog.clone()];
+
+        assert_eq!(super::list_specs(&catalogs).len(), 7);
+        assert_eq!(super::list_catalog_specs(&refs).len(), 2);
+        assert!(super::list_catalog_specs(&refs).iter().all(|spec| spec.get_catalog_id() == "separate-refs-uuid"));
+        assert_eq!(super::list_catalog_specs(&catalog).len(), 5);
+        assert_eq!(super::list_errors(&catalogs).len(), super::list_catalog_errors(&refs).len() + super::list_catalog_errors(&catalog).len());
+
+        //only the reloaded catalog is loaded again
+        let loaded_at = super::get_catalog_load_stats("separate-catalog-uuid").unwrap().loaded_at;
+        let (previous_files, specs) = super::reload_catalog(&refs);
+        assert_eq!(previous_files.len(), 2);
+        assert_eq!(specs.len(), 2);
+        assert_eq!(super::get_catalog_load_stats("separate-refs-uuid").unwrap().from_cache, 2);
+        assert_eq!(super::get_catalog_load_stats("separate-catalog-uuid").unwrap().loaded_at, loaded_at);
+
+        super::purge_catalog("separate-refs-uuid");
+        super::purge_catalog("separate-catalog-uuid");
+    }
+
     #[test]
     fn test_list_all_specs() {
         let mut path = std::path::PathBuf::new();
@@ -685,13 +1373,14 @@ pub mod tests {
             catalog_scm_pull_cmd: String::from("not used here"), 
             catalog_path: path.into_os_string().into_string().unwrap(),
             catalog_scm_clone: false,
-            catalog_http_base_uri: String::from("not used here")
+            catalog_http_base_uri: String::from("not used here"),
+            ..Default::default()
         };
         let mut catalogs = Vec::new();
         catalogs.push(catalog);
 
         let results = super::list_specs(&catalogs);
-        assert_eq!(results.len(), 4); //this include also asyncAPI ones
+        assert_eq!(results.len(), 5); //this include also asyncAPI ones
         //
         for spec in results.iter(){
             if spec.get_file_path().contains("analytics-time-series.yaml"){   
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use log::{debug, warn};
+use serde_yaml::Value;
+
+/// Result of the bundling of a spec: its content, where relative (external) `$ref`s
+/// have been replaced by what they point to, and the references that could not be resolved.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub content: String,
+    pub unresolved: Vec<UnresolvedRef>,
+    /// (external) files the spec references, including the missing ones of the catalog directory
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnresolvedRef {
+    pub reference: String,
+    pub reason: String,
+}
+
+/// Resolves the `$ref: ./common/foo.yaml#/...` of a (yaml) spec. Referenced files must live in the catalog directory.
+///
+/// `$ref`s internal to the spec (`#/components/...`) are kept as is, remote ones (`http://...`) are ignored.
+/// The content is returned untouched if nothing was inlined (or if it is not yaml, e.g. proto or graphql files).
+pub fn bundle(spec_path: &str, catalog_path: &str, content: &str) -> Bundle {
+    let untouched = Bundle { content: String::from(content), unresolved: Vec::new(), files: Vec::new() };
+    if !content.contains("$ref") {
+        return untouched;
+    }
+
+    let mut root = match serde_yaml::from_str::<Value>(content) {
+        Ok(root) if root.is_mapping() => root,
+        _ => return untouched,
+    };
+
+    let catalog_path = match std::fs::canonicalize(catalog_path) {
+        Ok(path) => path,
+        Err(why) => {
+            warn!("Unable to bundle spec [{:?}] - catalog path [{:?}] not found - [{:?}]", spec_path, catalog_path, why);
+            return untouched;
+        }
+    };
+    let spec_path = std::fs::canonicalize(spec_path).unwrap_or_else(|_| PathBuf::from(spec_path));
+
+    let mut bundler = Bundler {
+        spec_path: spec_path.clone(),
+        catalog_path,
+        documents: HashMap::new(),
+        stack: Vec::new(),
+        unresolved: Vec::new(),
+        missing: Vec::new(),
+        inlined: false,
+    };
+    bundler.resolve(&mut root, &spec_path, true);
+
+    let content = match bundler.inlined {
+        true => match serde_yaml::to_string(&root) {
+            Ok(content) => content,
+            Err(why) => {
+                warn!("Unable to serialize bundled spec [{:?}] - [{:?}]", spec_path, why);
+                String::from(content)
+            }
+        },
+        false => String::from(content),
+    };
+
+    let mut files: Vec<PathBuf> = bundler.documents.into_keys().filter(|file| file != &spec_path).collect();
+    //the spec has to be bundled again once they are created
+    files.extend(bundler.missing);
+    files.sort();
+    files.dedup();
+
+    Bundle { content, unresolved: bundler.unresolved, files }
+}
+
+struct Bundler {
+    spec_path: PathBuf,
+    catalog_path: PathBuf,
+    /// already loaded (external) files
+    documents: HashMap<PathBuf, Result<Value, String>>,
+    /// references being inlined, to detect circular ones
+    stack: Vec<(PathBuf, String)>,
+    unresolved: Vec<UnresolvedRef>,
+    /// referenced files not found in the catalog directory
+    missing: Vec<PathBuf>,
+    inlined: bool,
+}
+
+impl Bundler {
+    /// `base` is the file `value` comes from, relative `$ref`s are resolved against it
+    fn resolve(&mut self, value: &mut Value, base: &Path, is_root: bool) {
+        let reference = match value.get("$ref").and_then(|val| val.as_str()) {
+            Some(reference) => String::from(reference),
+            None => {
+                match value {
+                    Value::Mapping(mapping) => {
+                        for (_key, val) in mapping.iter_mut() {
+                            self.resolve(val, base, is_root);
+                        }
+                    },
+                    Value::Sequence(sequence) => {
+                        for val in sequence.iter_mut() {
+                            self.resolve(val, base, is_root);
+                        }
+                    },
+                    _ => {}
+                };
+                return;
+            }
+        };
+
+        if (is_root && reference.starts_with('#')) || reference.contains("://") {
+            return;
+        }
+
+        let (file, pointer) = match reference.split_once('#') {
+            Some((file, pointer)) => (file, pointer),
+            None => (reference.as_str(), ""),
+        };
+        let file = match file.is_empty() {
+            true => base.to_path_buf(),
+            false => base.parent().unwrap_or_else(|| Path::new("")).join(file),
+        };
+
+        let file = match std::fs::canonicalize(&file) {
+            Ok(file) => file,
+            Err(_) => {
+                let missing = normalize(&file);
+                if missing.starts_with(&self.catalog_path) {
+                    self.missing.push(missing);
+                }
+                self.add_unresolved(&reference, format!("file [{:?}] not found", file));
+                return;
+            }
+        };
+        if !file.starts_with(&self.catalog_path) {
+            self.add_unresolved(&reference, format!("file [{:?}] is outside of the catalog directory", file));
+            return;
+        }
+
+        let key = (file.clone(), String::from(pointer));
+        if self.stack.contains(&key) {
+            warn!("Circular $ref [{:?}] in spec [{:?}] - will be kept as is", reference, self.spec_path);
+            return;
+        }
+
+        let mut resolved = match self.load(&file) {
+            Ok(document) => match get_by_pointer(document, pointer) {
+                Some(resolved) => resolved.clone(),
+                None => {
+                    self.add_unresolved(&reference, format!("[{}] not found in file [{:?}]", pointer, file));
+                    return;
+                }
+            },
+            Err(why) => {
+                self.add_unresolved(&reference, why);
+                return;
+            }
+        };
+
+        debug!("Inlining $ref [{:?}] in spec [{:?}]", reference, self.spec_path);
+        self.stack.push(key);
+        self.resolve(&mut resolved, &file, false);
+        self.stack.pop();
+
+        *value = resolved;
+        self.inlined = true;
+    }
+
+    fn load(&mut self, file: &Path) -> Result<&Value, String> {
+        if !self.documents.contains_key(file) {
+            let document = match std::fs::read_to_string(file) {
+                Ok(content) => serde_yaml::from_str::<Value>(&super::spec::to_yaml(&content)).map_err(|why| format!("unable to parse file [{:?}] - {}", file, why)),
+                Err(why) => Err( format!("unable to read file [{:?}] - {}", file, why) ),
+            };
+            self.documents.insert(file.to_path_buf(), document);
+        }
+
+        match self.documents.get(file).unwrap() {
+            Ok(document) => Ok(document),
+            Err(why) => Err(why.clone()),
+        }
+    }
+
+    fn add_unresolved(&mut self, reference: &str, reason: String) {
+        warn!("Unable to resolve $ref [{:?}] in spec [{:?}] - {}", reference, self.spec_path, reason);
+
+        self.unresolved.push(UnresolvedRef {
+            reference: String::from(reference),
+            reason: format!("Unable to resolve $ref [{}] - {}", reference, reason),
+        });
+    }
+}
+
+/// `..` and `.` are removed without touching the file system, the file may not exist
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            component => normalized.push(component),
+        };
+    }
+
+    normalized
+}
+
+/// JSON pointer as defined by [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901), e.g. `/components/schemas/Pet`
+fn get_by_pointer<'a>(document: &'a Value, pointer: &str) -> Option<&'a Value> {
+    let mut current = document;
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        current = match current {
+            Value::Sequence(sequence) => sequence.get(token.parse::<usize>().ok()?)?,
+            _ => current.get(token.as_str())?,
+        };
+    }
+
+    Some(current)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::app::dao::catalog::handlers::SpecHandler;
+
+    fn get_refs_dir() -> String {
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/refs");
+
+        path.into_os_string().into_string().unwrap()
+    }
+
+    #[test]
+    fn test_bundle_resolves_relative_refs() {
+        let spec_path = format!("{}/pets-openapi.yaml", get_refs_dir());
+        let content = std::fs::read_to_string(&spec_path).unwrap();
+
+        let bundle = super::bundle(&spec_path, &get_refs_dir(), &content);
+        assert_eq!(bundle.unresolved.len(), 0);
+        assert!(!bundle.content.contains("./"));
+
+        let spec = crate::app::dao::catalog::handlers::implem::opanapi::V3::new(&bundle.content).unwrap();
+        assert_eq!(spec.get_paths_len(), 2);
+        assert_eq!(spec.get_paths()[1].path, "/pets/{petId}");
+        assert_eq!(spec.get_paths()[1].methods[0].summary, "Get a pet");
+
+        let bundled: serde_yaml::Value = serde_yaml::from_str(&bundle.content).unwrap();
+        let pet = &bundled["components"]["schemas"]["Pet"];
+        assert_eq!(pet["properties"]["category"]["properties"]["name"]["type"], "string");
+        //circular references are kept as is
+        assert_eq!(pet["properties"]["parent"]["$ref"], "#/components/schemas/Pet");
+        //internal references are kept as is
+        assert_eq!(bundled["paths"]["/pets"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]["items"]["$ref"], "#/components/schemas/Pet");
+    }
+
+    #[test]
+    fn test_bundle_reports_unresolved_refs() {
+        let spec_path = format!("{}/broken-openapi.yaml", get_refs_dir());
+        let content = std::fs::read_to_string(&spec_path).unwrap();
+
+        let bundle = super::bundle(&spec_path, &get_refs_dir(), &content);
+        assert_eq!(bundle.unresolved.len(), 3);
+        assert_eq!(bundle.unresolved[0].reference, "./common/missing.yaml#/components/schemas/Missing");
+        assert!(bundle.unresolved[0].reason.contains("not found"));
+        assert_eq!(bundle.unresolved[1].reference, "./common/pet.yaml#/components/schemas/Unknown");
+        assert!(bundle.unresolved[1].reason.contains("[/components/schemas/Unknown] not found in file"));
+        assert_eq!(bundle.unresolved[2].reference, "../api_spec.yaml#/info");
+        assert!(bundle.unresolved[2].reason.contains("outside of the catalog directory"));
+        //the missing file of the catalog is a dependency, unlike the ones outside of it
+        let refs_dir = std::fs::canonicalize(get_refs_dir()).unwrap();
+        assert_eq!(bundle.files, vec![ refs_dir.join("common/missing.yaml"), refs_dir.join("common/pet.yaml") ]);
+
+        //the spec is still readable
+        let spec = crate::app::dao::catalog::handlers::implem::opanapi::V3::new(&bundle.content).unwrap();
+        assert_eq!(spec.get_paths_len(), 1);
+    }
+
+    #[test]
+    fn test_bundle_keeps_content_without_external_refs() {
+        let content = "syntax = \"proto3\";";
+        let bundle = super::bundle("not used here", &get_refs_dir(), content);
+        assert_eq!(bundle.content, content);
+
+        let content = "openapi: 3.0.0\ninfo:\n  title: t\n  version: v\npaths: {}\ncomponents:\n  schemas:\n    A:\n      $ref: '#/components/schemas/B'\n";
+        let bundle = super::bundle("not used here", &get_refs_dir(), content);
+        assert_eq!(bundle.content, content);
+        assert_eq!(bundle.unresolved.len(), 0);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeSet;
+
+use serde_yaml::Value;
+use strum_macros::{Display, EnumString};
+
+use super::diff::{entries, get, to_string, HTTP_METHODS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(EnumString, Display)]
+pub enum DeltaType {
+    #[strum(serialize = "ADDED")]
+    Added,
+    #[strum(serialize = "REMOVED")]
+    Removed,
+    #[strum(serialize = "MODIFIED")]
+    Modified,
+}
+
+/// What a review has to look at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaKind {
+    Path,
+    Operation,
+    /// `x-zally-ignore` rules
+    Zally,
+    /// `x-audience`
+    Audience,
+    /// `x-has-authority`
+    Permission,
+}
+
+/// A structured change of a spec file in a pull request
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpecDelta {
+    pub typ: DeltaType,
+    pub kind: DeltaKind,
+    /// the path of the spec file in the repository
+    pub spec_path: String,
+    /// e.g. `/pets/{id}`, none for the changes at the root of the spec
+    pub path: Option<String>,
+    /// e.g. `GET /pets/{id}`
+    pub operation: Option<String>,
+    /// e.g. `x-audience: company-internal`
+    pub detail: String,
+}
+
+/// Compares the content of a spec file before and after a pull request (`None` if the file is added / deleted).
+/// The file cannot be reviewed if either content is not a YAML (or JSON) document - it would otherwise look emptied (or created).
+pub fn review(spec_path: &str, before: Option<&str>, after: Option<&str>) -> Result<Vec<SpecDelta>, String> {
+    let parse = |content: Option<&str>| -> Result<Value, String> {
+        match content {
+            Some(content) => serde_yaml::from_str(content).map_err(|why| format!("Unable to parse [{}] for review - reason [{}]", spec_path, why)),
+            None => Ok(Value::Null),
+        }
+    };
+    let (before, after) = (parse(before)?, parse(after)?);
+
+    let mut reviewer = Reviewer { spec_path, deltas: Vec::new() };
+    reviewer.review_extensions(None, None, &before, &after);
+    if let (Some(before_info), Some(after_info)) = (get(&before, "info"), get(&after, "info")) {
+        reviewer.review_extensions(None, None, before_info, after_info);
+    }
+
+    let before_paths = entries(get(&before, "paths"));
+    let after_paths = entries(get(&after, "paths"));
+    for (path, before_item) in &before_paths {
+        match after_paths.get(path) {
+            Some(after_item) => reviewer.review_path(path, before_item, after_item),
+            None => {
+                reviewer.push(DeltaType::Removed, DeltaKind::Path, Some(path), None, String::from(path));
+                reviewer.review_path(path, before_item, &Value::Null);
+            },
+        };
+    }
+    for (path, after_item) in after_paths.iter().filter(|(path, _item)| !before_paths.contains_key(*path)) {
+        reviewer.push(DeltaType::Added, DeltaKind::Path, Some(path), None, String::from(path));
+        reviewer.review_path(path, &Value::Null, after_item);
+    }
+
+    Ok(reviewer.deltas)
+}
+
+struct Reviewer<'a> {
+    spec_path: &'a str,
+    deltas: Vec<SpecDelta>,
+}
+
+impl<'a> Reviewer<'a> {
+    fn push(&mut self, typ: DeltaType, kind: DeltaKind, path: Option<&str>, operation: Option<&str>, detail: String) {
+        self.deltas.push(SpecDelta {
+            typ,
+            kind,
+            spec_path: String::from(self.spec_path),
+            path: path.map(String::from),
+            operation: operation.map(String::from),
+            detail,
+        });
+    }
+
+    fn review_path(&mut self, path: &str, before_item: &Value, after_item: &Value) {
+        self.review_extensions(Some(path), None, before_item, after_item);
+
+        for method in HTTP_METHODS {
+            let operation = format!("{} {}", method.to_uppercase(), path);
+            match (get(before_item, method), get(after_item, method)) {
+                (Some(before_operation), Some(after_operation)) => self.review_extensions(Some(path), Some(&operation), before_operation, after_operation),
+                (Some(_), None) => self.push(DeltaType::Removed, DeltaKind::Operation, Some(path), Some(&operation), String::from(&operation)),
+                (None, Some(after_operation)) => {
+                    self.push(DeltaType::Added, DeltaKind::Operation, Some(path), Some(&operation), String::from(&operation));
+                    //what comes with the new operation has to be reviewed as well
+                    self.review_extensions(Some(path), Some(&operation), &Value::Null, after_operation);
+                },
+                (None, None) => {},
+            };
+        }
+    }
+
+    fn review_extensions(&mut self, path: Option<&str>, operation: Option<&str>, before: &Value, after: &Value) {
+        let (before_rules, after_rules) = (get_zally_rules(before), get_zally_rules(after));
+        for rule in after_rules.difference(&before_rules) {
+            self.push(DeltaType::Added, DeltaKind::Zally, path, operation, format!("x-zally-ignore: {}", rule));
+        }
+        for rule in before_rules.difference(&after_rules) {
+            self.push(DeltaType::Removed, DeltaKind::Zally, path, operation, format!("x-zally-ignore: {}", rule));
+        }
+
+        for (extension, kind) in [("x-audience", DeltaKind::Audience), ("x-has-authority", DeltaKind::Permission)] {
+            match (get(before, extension).map(to_string), get(after, extension).map(to_string)) {
+                (Some(before_value), Some(after_value)) if before_value != after_value => {
+                    self.push(DeltaType::Modified, kind, path, operation, format!("{}: {} -> {}", extension, before_value, after_value));
+                },
+                (Some(before_value), None) => self.push(DeltaType::Removed, kind, path, operation, format!("{}: {}", extension, before_value)),
+                (None, Some(after_value)) => self.push(DeltaType::Added, kind, path, operation, format!("{}: {}", extension, after_value)),
+                _ => {},
+            };
+        }
+    }
+}
+
+/// rules of `x-zally-ignore`, either a list or a single rule
+pub(super) fn get_zally_rules(value: &Value) -> BTreeSet<String> {
+    match get(value, "x-zally-ignore") {
+        Some(Value::Sequence(rules)) => rules.iter().map(to_string).collect(),
+        Some(rule) => BTreeSet::from([to_string(rule)]),
+        None => BTreeSet::new(),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{DeltaKind, DeltaType};
+
+    type Summary<'a> = (DeltaType, DeltaKind, Option<&'a str>, Option<&'a str>, &'a str);
+
+    const BEFORE: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.0.0
+  x-audience: company-internal
+x-zally-ignore: [ 101 ]
+paths:
+  /pets:
+    get:
+      description: "/not/a/path"
+      x-has-authority: pets.read
+      responses:
+        '200':
+          description: ok
+  /owners:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    const AFTER: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.1.0
+  x-audience: external-public
+x-zally-ignore: [ 101, 104 ]
+paths:
+  /pets:
+    get:
+      description: "/still/not/a/path"
+      x-has-authority: pets.admin
+      responses:
+        '200':
+          description: ok
+    post:
+      x-zally-ignore: 176
+      x-has-authority: pets.write
+      responses:
+        '201':
+          description: created
+"#;
+
+    #[test]
+    fn test_review() {
+        let deltas = super::review("specs/pets-openapi.yaml", Some(BEFORE), Some(AFTER)).unwrap();
+        let summary: Vec<Summary> = deltas.iter()
+            .map(|delta| (delta.typ, delta.kind, delta.path.as_deref(), delta.operation.as_deref(), delta.detail.as_str()))
+            .collect();
+
+        assert_eq!(summary, vec![
+            (DeltaType::Added, DeltaKind::Zally, None, None, "x-zally-ignore: 104"),
+            (DeltaType::Modified, DeltaKind::Audience, None, None, "x-audience: company-internal -> external-public"),
+            (DeltaType::Removed, DeltaKind::Path, Some("/owners"), None, "/owners"),
+            (DeltaType::Removed, DeltaKind::Operation, Some("/owners"), Some("GET /owners"), "GET /owners"),
+            (DeltaType::Modified, DeltaKind::Permission, Some("/pets"), Some("GET /pets"), "x-has-authority: pets.read -> pets.admin"),
+            (DeltaType::Added, DeltaKind::Operation, Some("/pets"), Some("POST /pets"), "POST /pets"),
+            (DeltaType::Added, DeltaKind::Zally, Some("/pets"), Some("POST /pets"), "x-zally-ignore: 176"),
+            (DeltaType::Added, DeltaKind::Permission, Some("/pets"), Some("POST /pets"), "x-has-authority: pets.write"),
+        ]);
+        assert!(deltas.iter().all(|delta| delta.spec_path == "specs/pets-openapi.yaml"));
+
+        //added file
+        let deltas = super::review("specs/pets-openapi.yaml", None, Some(AFTER)).unwrap();
+        assert_eq!(deltas.iter().filter(|delta| delta.kind == DeltaKind::Path).count(), 1);
+        assert_eq!(deltas.iter().filter(|delta| delta.kind == DeltaKind::Operation).count(), 2);
+        //not a yaml document, on either side
+        assert!(super::review("specs/pets-openapi.yaml", Some("# pets\n: -"), Some(AFTER)).unwrap_err().contains("Unable to parse"));
+        assert!(super::review("specs/pets-openapi.yaml", Some(BEFORE), Some("paths:\n  /pets: [")).is_err());
+    }
+}
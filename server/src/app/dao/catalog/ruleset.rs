@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+use regex::Regex;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use super::diff::{get, to_string};
+use super::lint::{pointer, Severity, Violation};
+use super::spec::SpecItem;
+use super::{ErrorCategory, SpecInError};
+use crate::shared::settings::Catalog;
+
+/// Governance rules kept in the repository of a catalog, e.g.
+/// ```yaml
+/// rules:
+///   - id: contact-email
+///     description: the owners of the API can be reached
+///     severity: error
+///     given: $.info.contact
+///     then:
+///       field: email
+///       function: pattern
+///       match: "@company.com$"
+///     scope:
+///       audiences: [ external-partner, external-public ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ruleset {
+    #[serde(default)]
+    pub rules: Vec<CustomRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomRule {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    /// `error`, `warning` (the default), `info` or `hint`
+    #[serde(default)]
+    pub severity: String,
+    /// JSONPath (e.g. `$.paths[*][*].responses`) or JSON pointer (e.g. `/info/contact`) of the checked nodes
+    pub given: String,
+    pub then: Condition,
+    #[serde(default)]
+    pub scope: Scope,
+}
+
+/// What the selected nodes (or their `field`) must satisfy
+#[derive(Debug, Clone, Deserialize)]
+pub struct Condition {
+    #[serde(default)]
+    pub field: Option<String>,
+    /// `exists`, `pattern` (with `match`), `enum` (with `values`) or `length` (with `min` and / or `max`)
+    pub function: String,
+    #[serde(default, rename = "match")]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub values: Vec<String>,
+    #[serde(default)]
+    pub min: Option<usize>,
+    #[serde(default)]
+    pub max: Option<usize>,
+}
+
+/// The specs a rule applies to, all of them if empty
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Scope {
+    /// `x-layer` of the specs
+    #[serde(default)]
+    pub layers: Vec<String>,
+    /// `x-audience` of the specs
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// tier of the API the spec belongs to (refer to `x-api-id`)
+    #[serde(default)]
+    pub tiers: Vec<String>,
+}
+
+/// A step of a JSONPath
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Child(String),
+    Index(usize),
+    Wildcard,
+    /// `..`, followed by the step applied to all the descendants
+    Descendants(Box<Step>),
+}
+
+pub fn get_ruleset_file(catalog: &Catalog) -> Option<PathBuf> {
+    match catalog.catalog_ruleset_path.is_empty() {
+        true => None,
+        false => Some(Path::new(&catalog.catalog_path).join(&catalog.catalog_ruleset_path)),
+    }
+}
+
+/// the ruleset is not a spec of the catalog
+pub fn is_ruleset_file(catalog: &Catalog, file_path: &Path) -> bool {
+    get_ruleset_file(catalog).is_some_and(|ruleset_file| ruleset_file == file_path)
+}
+
+pub fn load_ruleset(file_path: &Path) -> Result<Ruleset, String> {
+    let content = std::fs::read_to_string(file_path).map_err(|why| format!("Unable to read ruleset [{:?}] - {}", file_path, why))?;
+    let ruleset: Ruleset = serde_yaml::from_str(&content).map_err(|why| format!("Unable to parse ruleset [{:?}] - {}", file_path, why))?;
+
+    for rule in &ruleset.rules {
+        parse_selector(&rule.given).map_err(|why| format!("Invalid selector of rule [{}] - {}", rule.id, why))?;
+        match rule.then.function.as_str() {
+            "exists" | "enum" | "length" => {},
+            "pattern" => {
+                Regex::new(rule.then.pattern.as_deref().unwrap_or_default()).map_err(|why| format!("Invalid pattern of rule [{}] - {}", rule.id, why))?;
+            },
+            function => return Err( format!("Unknown function [{}] of rule [{}]", function, rule.id) ),
+        };
+    }
+
+    Ok(ruleset)
+}
+
+/// Evaluates the ruleset of the catalog (if any) against its specs. The violations, and the ruleset
+/// if it cannot be loaded, are reported as errors of the catalog
+pub fn evaluate_catalog(catalog: &Catalog, specs: &[SpecItem]) -> Vec<SpecInError> {
+    let ruleset_file = match get_ruleset_file(catalog) {
+        Some(ruleset_file) => ruleset_file,
+        None => return Vec::new(),
+    };
+    let ruleset = match load_ruleset(&ruleset_file) {
+        Ok(ruleset) => ruleset,
+        Err(why) => {
+            warn!("{}", why);
+            return vec![ SpecInError { file_path: format!("{:?}", ruleset_file), reason: why, category: ErrorCategory::Ruleset } ];
+        }
+    };
+
+    let tiers = match ruleset.rules.iter().any(|rule| !rule.scope.tiers.is_empty()) {
+        true => get_tiers_per_api(),
+        false => HashMap::new(),
+    };
+    let mut errors = Vec::new();
+    for spec in specs {
+        let tier = tiers.get(&spec.get_api_id()).map(String::as_str);
+        errors.extend(evaluate(&ruleset, spec, tier).into_iter().map(|violation| SpecInError {
+            file_path: format!("{:?}", Path::new(spec.get_file_path())),
+            reason: format!("[{}] {} - {} at [{}]", violation.severity, violation.rule_id, violation.message, violation.pointer),
+            category: ErrorCategory::Ruleset,
+        }));
+    }
+    info!("Evaluated [{:?}] rules against [{:?}] specs of catalog [{:?}] - got [{:?}] violations", ruleset.rules.len(), specs.len(), &catalog.catalog_id, errors.len());
+
+    errors
+}
+
+/// tier names per API id
+fn get_tiers_per_api() -> HashMap<String, String> {
+    match crate::app::dao::repo_apis::list_all_apis(&crate::shared::settings::SETTINGS.database) {
+        Ok(apis) => apis.into_iter().map(|api| (api.id.to_string(), api.tier.name)).collect(),
+        Err(why) => {
+            warn!("Unable to get the tiers of the APIs - [{:?}]", why);
+            HashMap::new()
+        }
+    }
+}
+
+/// Evaluates the rules in scope of the spec, `tier` being the tier of its API if known
+pub fn evaluate(ruleset: &Ruleset, spec: &SpecItem, tier: Option<&str>) -> Vec<Violation> {
+    let document = match spec.get_document() {
+        Some(document) => document,
+        None => {
+            debug!("[{}] specs are not evaluated - skip spec [{:?}]", spec.get_spec_type(), spec.get_file_path());
+            return Vec::new();
+        }
+    };
+
+    let (layer, audience) = (spec.get_layer(), spec.get_audience());
+    ruleset.rules.iter()
+        .filter(|rule| {
+            let in_scope = |values: &Vec<String>, value: Option<&str>| values.is_empty() || value.is_some_and(|value| values.iter().any(|scoped| scoped == value));
+            in_scope(&rule.scope.layers, Some(&layer)) && in_scope(&rule.scope.audiences, Some(&audience)) && in_scope(&rule.scope.tiers, tier)
+        })
+        .flat_map(|rule| evaluate_rule(rule, &document))
+        .collect()
+}
+
+fn evaluate_rule(rule: &CustomRule, document: &Value) -> Vec<Violation> {
+    let severity = match rule.severity.is_empty() {
+        true => Severity::Warning,
+        false => rule.severity.parse::<Severity>().unwrap_or(Severity::Warning),
+    };
+    let violation = |tokens: &[String], message: String| Violation {
+        rule_id: String::from(&rule.id),
+        severity,
+        pointer: pointer(&tokens.iter().map(String::as_str).collect::<Vec<&str>>()),
+        message: match rule.description.is_empty() {
+            true => message,
+            false => format!("{} ({})", rule.description, message),
+        },
+    };
+
+    let nodes = match parse_selector(&rule.given) {
+        Ok(steps) => select(document, &steps),
+        Err(_why) => return Vec::new(),
+    };
+    let condition = &rule.then;
+    if condition.function == "exists" && condition.field.is_none() {
+        return match nodes.is_empty() {
+            true => vec![ violation(&[], format!("[{}] not found", rule.given)) ],
+            false => Vec::new(),
+        };
+    }
+
+    let mut violations = Vec::new();
+    for (mut tokens, node) in nodes {
+        let value = match &condition.field {
+            Some(field) => {
+                tokens.push(String::from(field));
+                get(node, field).filter(|value| !value.is_null())
+            },
+            None => Some(node),
+        };
+
+        let message = match (condition.function.as_str(), value) {
+            ("exists", None) => Some(format!("[{}] is missing", condition.field.as_deref().unwrap_or_default())),
+            ("pattern", Some(value)) => {
+                let pattern = condition.pattern.as_deref().unwrap_or_default();
+                let matches = Regex::new(pattern).map(|regex| regex.is_match(&to_string(value))).unwrap_or(true);
+                (!matches).then(|| format!("[{}] does not match [{}]", to_string(value), pattern))
+            },
+            ("enum", Some(value)) => (!condition.values.contains(&to_string(value))).then(|| format!("[{}] is not one of [{}]", to_string(value), condition.values.join(", "))),
+            ("length", Some(value)) => {
+                let length = match value {
+                    Value::String(value) => value.chars().count(),
+                    Value::Sequence(values) => values.len(),
+                    Value::Mapping(values) => values.len(),
+                    value => to_string(value).chars().count(),
+                };
+                let too_short = condition.min.is_some_and(|min| length < min);
+                let too_long = condition.max.is_some_and(|max| length > max);
+                (too_short || too_long).then(|| format!("length [{}] is not within [{}..{}]", length,
+                    condition.min.map(|min| min.to_string()).unwrap_or_default(), condition.max.map(|max| max.to_string()).unwrap_or_default()))
+            },
+            //nothing to check
+            _ => None,
+        };
+        if let Some(message) = message {
+            violations.push(violation(&tokens, message));
+        }
+    }
+
+    violations
+}
+
+/// Parses a JSON pointer (`/info/contact`) or a subset of JSONPath: `$`, `.name`, `['name']`, `[0]`, `*`, `[*]` and `..`
+fn parse_selector(selector: &str) -> Result<Vec<Step>, String> {
+    if selector.is_empty() || selector.starts_with('/') {
+        return Ok(selector.split('/').skip(1).map(|token| Step::Child(token.replace("~1", "/").replace("~0", "~"))).collect());
+    }
+
+    let mut chars = selector.strip_prefix('$').ok_or_else(|| format!("[{}] is neither a JSONPath nor a JSON pointer", selector))?.chars().peekable();
+    let mut steps = Vec::new();
+    while let Some(c) = chars.next() {
+        let descendants = c == '.' && chars.peek() == Some(&'.');
+        if descendants {
+            chars.next();
+        }
+        let step = match (c, chars.peek()) {
+            ('.', Some('[')) if descendants => {
+                chars.next();
+                parse_bracket(&mut chars, selector)?
+            },
+            ('.', Some('*')) => {
+                chars.next();
+                Step::Wildcard
+            },
+            ('.', Some(_)) => {
+                let mut name = String::new();
+                while let Some(c) = chars.peek().filter(|c| **c != '.' && **c != '[') {
+                    name.push(*c);
+                    chars.next();
+                }
+                Step::Child(name)
+            },
+            ('[', _) => parse_bracket(&mut chars, selector)?,
+            _ => return Err( format!("Unexpected [{}] in [{}]", c, selector) ),
+        };
+
+        steps.push(match descendants {
+            true => Step::Descendants(Box::new(step)),
+            false => step,
+        });
+    }
+
+    Ok(steps)
+}
+
+/// the content of `[...]`, the opening bracket being consumed
+fn parse_bracket(chars: &mut std::iter::Peekable<std::str::Chars>, selector: &str) -> Result<Step, String> {
+    let mut content = String::new();
+    let mut quote = None;
+    for c in chars.by_ref() {
+        match (c, quote) {
+            (']', None) => {
+                let content = content.trim();
+                return match content {
+                    "*" => Ok(Step::Wildcard),
+                    _ if content.len() >= 2 && (content.starts_with('\'') || content.starts_with('"')) => Ok(Step::Child(String::from(&content[1..content.len() - 1]))),
+                    _ => content.parse::<usize>().map(Step::Index).map_err(|_why| format!("Invalid [{}] in [{}]", content, selector)),
+                };
+            },
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(opening)) if c == opening => quote = None,
+            _ => {},
+        };
+        content.push(c);
+    }
+
+    Err( format!("Missing ] in [{}]", selector) )
+}
+
+/// the selected nodes and their JSON pointer tokens
+fn select<'a>(document: &'a Value, steps: &[Step]) -> Vec<(Vec<String>, &'a Value)> {
+    let mut nodes = vec![ (Vec::new(), document) ];
+    for step in steps {
+        nodes = nodes.into_iter().flat_map(|(tokens, node)| apply(step, tokens, node)).collect();
+    }
+
+    nodes
+}
+
+fn apply<'a>(step: &Step, tokens: Vec<String>, node: &'a Value) -> Vec<(Vec<String>, &'a Value)> {
+    let child = |token: String, value: &'a Value| {
+        let mut child_tokens = tokens.clone();
+        child_tokens.push(token);
+        (child_tokens, value)
+    };
+
+    match (step, node) {
+        (Step::Child(name), node) => get(node, name).map(|value| child(String::from(name), value)).into_iter().collect(),
+        (Step::Index(index), Value::Sequence(values)) => values.get(*index).map(|value| child(index.to_string(), value)).into_iter().collect(),
+        (Step::Wildcard, Value::Mapping(values)) => values.iter().map(|(key, value)| child(to_string(key), value)).collect(),
+        (Step::Wildcard, Value::Sequence(values)) => values.iter().enumerate().map(|(index, value)| child(index.to_string(), value)).collect(),
+        (Step::Descendants(step), node) => {
+            let mut descendants = vec![ (tokens.clone(), node) ];
+            let mut index = 0;
+            while index < descendants.len() {
+                let (descendant_tokens, descendant) = descendants[index].clone();
+                descendants.extend(apply(&Step::Wildcard, descendant_tokens, descendant));
+                index += 1;
+            }
+            descendants.into_iter().flat_map(|(descendant_tokens, descendant)| apply(step, descendant_tokens, descendant)).collect()
+        },
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::Step;
+    use crate::app::dao::catalog::lint::Severity;
+    use crate::shared::settings::Catalog;
+
+    const RULESET: &str = r#"
+rules:
+  - id: contact-email
+    severity: error
+    given: $.info.contact
+    then:
+      field: email
+      function: pattern
+      match: "@company.com$"
+  - id: operation-summary
+    given: $.paths[*][*]
+    then:
+      field: summary
+      function: exists
+  - id: short-summary
+    severity: info
+    given: $..summary
+    then:
+      function: length
+      max: 10
+  - id: known-audience
+    description: only company audiences
+    given: /info/x-audience
+    then:
+      function: enum
+      values: [ company-internal ]
+  - id: external-tags
+    given: $.tags
+    then:
+      function: exists
+    scope:
+      audiences: [ external-public ]
+  - id: tiered
+    given: $.x-tier
+    then:
+      function: exists
+    scope:
+      tiers: [ gold ]
+"#;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.0.0
+  x-audience: external-public
+  contact:
+    email: owner@gmail.com
+paths:
+  /pets:
+    get:
+      summary: list all the pets
+      responses:
+        '200':
+          description: ok
+    post:
+      responses:
+        '201':
+          description: created
+"#;
+
+    #[test]
+    fn test_parse_selector() {
+        assert_eq!(super::parse_selector("/paths/~1pets/get").unwrap(), vec![ Step::Child(String::from("paths")), Step::Child(String::from("/pets")), Step::Child(String::from("get")) ]);
+        assert_eq!(super::parse_selector("$.paths['/pets'].*[0]").unwrap(), vec![ Step::Child(String::from("paths")), Step::Child(String::from("/pets")), Step::Wildcard, Step::Index(0) ]);
+        assert_eq!(super::parse_selector("$..summary").unwrap(), vec![ Step::Descendants(Box::new(Step::Child(String::from("summary")))) ]);
+        assert!(super::parse_selector("paths").is_err());
+        assert!(super::parse_selector("$.paths['/pets'").is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let ruleset: super::Ruleset = serde_yaml::from_str(RULESET).unwrap();
+        let spec = crate::app::dao::catalog::spec::from_str(String::from("/specs/pets-openapi.yaml"), String::from("catalog"), String::from("/specs"), SPEC).unwrap();
+
+        let violations: Vec<(String, Severity, String)> = super::evaluate(&ruleset, &spec, None).into_iter()
+            .map(|violation| (violation.rule_id, violation.severity, violation.pointer))
+            .collect();
+        assert_eq!(violations, vec![
+            (String::from("contact-email"), Severity::Error, String::from("/info/contact/email")),
+            (String::from("operation-summary"), Severity::Warning, String::from("/paths/~1pets/post/summary")),
+            (String::from("short-summary"), Severity::Info, String::from("/paths/~1pets/get/summary")),
+            (String::from("known-audience"), Severity::Warning, String::from("/info/x-audience")),
+            (String::from("external-tags"), Severity::Warning, String::from("")),
+        ]);
+
+        //in scope of the gold tier
+        let violations = super::evaluate(&ruleset, &spec, Some("gold"));
+        assert_eq!(violations.last().unwrap().rule_id, "tiered");
+    }
+
+    #[test]
+    fn test_evaluate_catalog() {
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-ruleset-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&catalog_path).unwrap();
+        std::fs::write(catalog_path.join("pets-openapi.yaml"), SPEC).unwrap();
+        //without the tiered rule, the tiers being in the database
+        std::fs::write(catalog_path.join("ruleset.yaml"), RULESET.split("  - id: tiered").next().unwrap()).unwrap();
+        let catalog = Catalog {
+            catalog_id: format!("{}", uuid::Uuid::new_v4()),
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_dir: catalog_path.clone().into_os_string().into_string().unwrap(),
+            catalog_include_glob_pattern: vec![String::from("**/*.yaml")],
+            catalog_ruleset_path: String::from("ruleset.yaml"),
+            ..Default::default()
+        };
+
+        //the ruleset is not a spec
+        assert_eq!(crate::app::dao::catalog::list_catalog_specs(&catalog).len(), 1);
+        let errors = crate::app::dao::catalog::list_catalog_errors(&catalog);
+        assert_eq!(errors.len(), 5);
+        assert!(errors.iter().all(|error| error.category == crate::app::dao::catalog::ErrorCategory::Ruleset));
+        assert!(errors[0].reason.starts_with("[error] contact-email - "));
+
+        //invalid ruleset
+        std::fs::write(catalog_path.join("ruleset.yaml"), "rules:\n  - id: unknown\n    given: $.info\n    then:\n      function: unknown\n").unwrap();
+        let errors = super::evaluate_catalog(&catalog, &crate::app::dao::catalog::list_catalog_specs(&catalog));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].file_path.ends_with("ruleset.yaml\""));
+
+        crate::app::dao::catalog::purge_catalog(&catalog.catalog_id);
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+    }
+}
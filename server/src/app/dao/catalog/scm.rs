@@ -0,0 +1,241 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{Cred, FetchOptions, RemoteCallbacks, Repository};
+use log::{error, info, warn};
+
+use crate::shared::settings::Catalog;
+
+/// Git status of a catalog, as of its last clone / fetch
+#[derive(Debug, Clone, Default)]
+pub struct ScmStatus {
+    pub head_commit: Option<String>,
+    pub branch: Option<String>,
+    pub last_fetch: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+lazy_static! {
+    static ref STATUSES: Mutex<HashMap<String, ScmStatus>> = Mutex::new(HashMap::new());
+}
+
+pub fn get_status(catalog_id: &str) -> Option<ScmStatus> {
+    STATUSES.lock().unwrap().get(catalog_id).cloned()
+}
+
+pub fn remove_status(catalog_id: &str) {
+    STATUSES.lock().unwrap().remove(catalog_id);
+}
+
+/// Clones `catalog_scm_url` into `catalog_path` if it is not a git repository yet, otherwise fetches it.
+/// The (remote) branch is then checked out, local changes being discarded. Returns the HEAD commit.
+pub fn sync(catalog: &Catalog) -> Result<String, String> {
+    let result = match Repository::open(&catalog.catalog_path) {
+        Ok(repo) => fetch_and_checkout(&repo, catalog),
+        Err(_) => clone(catalog),
+    };
+
+    let mut statuses = STATUSES.lock().unwrap();
+    let status = statuses.entry(String::from(&catalog.catalog_id)).or_default();
+    match result {
+        Ok((head_commit, branch)) => {
+            info!("Catalog [{:?}] - [{:?}] is at commit [{:?}] on branch [{:?}]", catalog.catalog_id, catalog.catalog_scm_url, head_commit, branch);
+            status.head_commit = Some(head_commit.clone());
+            status.branch = Some(branch);
+            status.last_fetch = Some(Utc::now());
+            status.last_error = None;
+
+            Ok(head_commit)
+        }
+        Err(why) => {
+            let error_message = format!("Unable to sync catalog [{}] with [{}] - {}", catalog.catalog_id, catalog.catalog_scm_url, why.message());
+            error!("{}", error_message);
+            status.last_error = Some(error_message.clone());
+
+            Err(error_message)
+        }
+    }
+}
+
+fn clone(catalog: &Catalog) -> Result<(String, String), git2::Error> {
+    info!("Cloning [{:?}] into [{:?}]", catalog.catalog_scm_url, catalog.catalog_path);
+
+    let mut builder = RepoBuilder::new();
+    builder.fetch_options(get_fetch_options(catalog));
+    if !catalog.catalog_scm_branch.is_empty() {
+        builder.branch(&catalog.catalog_scm_branch);
+    }
+
+    let repo = builder.clone(&catalog.catalog_scm_url, std::path::Path::new(&catalog.catalog_path))?;
+    let head = repo.head()?;
+    let branch = String::from(head.shorthand().unwrap_or(""));
+    let head_commit = head.peel_to_commit()?.id().to_string();
+
+    Ok((head_commit, branch))
+}
+
+fn fetch_and_checkout(repo: &Repository, catalog: &Catalog) -> Result<(String, String), git2::Error> {
+    //the branch currently checked out, if not configured
+    let branch = match catalog.catalog_scm_branch.is_empty() {
+        true => String::from(repo.head()?.shorthand().unwrap_or("master")),
+        false => String::from(&catalog.catalog_scm_branch),
+    };
+    info!("Fetching branch [{:?}] of [{:?}] into [{:?}]", branch, catalog.catalog_scm_url, catalog.catalog_path);
+
+    let mut remote = repo.remote_anonymous(&catalog.catalog_scm_url)?;
+    let refspec = format!("+refs/heads/{}:refs/remotes/origin/{}", branch, branch);
+    remote.fetch(&[refspec.as_str()], Some(&mut get_fetch_options(catalog)), None)?;
+
+    let fetched = repo.find_reference(&format!("refs/remotes/origin/{}", branch))?.peel_to_commit()?;
+    repo.reference(&format!("refs/heads/{}", branch), fetched.id(), true, "apis-catalog: fetch")?;
+    repo.set_head(&format!("refs/heads/{}", branch))?;
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
+
+    Ok((fetched.id().to_string(), branch))
+}
+
+/// `catalog_scm_password`, or the value of `catalog_scm_password_env` if not set
+fn get_password(catalog: &Catalog) -> String {
+    if !catalog.catalog_scm_password.is_empty() || catalog.catalog_scm_password_env.is_empty() {
+        return String::from(&catalog.catalog_scm_password);
+    }
+
+    match std::env::var(&catalog.catalog_scm_password_env) {
+        Ok(password) => password,
+        Err(why) => {
+            warn!("Unable to read the password of catalog [{:?}] from [{:?}] - [{:?}]", &catalog.catalog_id, &catalog.catalog_scm_password_env, why);
+            String::new()
+        }
+    }
+}
+
+fn get_fetch_options(catalog: &Catalog) -> FetchOptions<'_> {
+    let mut callbacks = RemoteCallbacks::new();
+    //libgit2 calls back as long as credentials are rejected, only try once
+    let attempts = Cell::new(0);
+    callbacks.credentials(move |_url, username_from_url, allowed_types| {
+        attempts.set(attempts.get() + 1);
+        if attempts.get() > 1 {
+            return Err(git2::Error::from_str("credentials have been rejected"));
+        }
+
+        if allowed_types.is_user_pass_plaintext() && !catalog.catalog_scm_username.is_empty() {
+            Cred::userpass_plaintext(&catalog.catalog_scm_username, &get_password(catalog))
+        } else if allowed_types.is_ssh_key() {
+            Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"))
+        } else {
+            Cred::default()
+        }
+    });
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    if catalog.catalog_scm_depth > 0 {
+        fetch_options.depth(catalog.catalog_scm_depth);
+    }
+
+    fetch_options
+}
+
+#[cfg(test)]
+pub mod tests {
+    use git2::{Oid, Repository, Signature};
+    use crate::shared::settings::Catalog;
+
+    /// commits `content` as `file_name` on top of `branch` of the (bare) repository
+    pub fn commit_file(repo: &Repository, branch: &str, file_name: &str, content: &str) -> Oid {
+        let reference = format!("refs/heads/{}", branch);
+        let parent = repo.find_reference(&reference).and_then(|reference| reference.peel_to_commit()).ok();
+
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut tree_builder = repo.treebuilder(parent.as_ref().map(|parent| parent.tree().unwrap()).as_ref()).unwrap();
+        tree_builder.insert(file_name, blob, 0o100644).unwrap();
+        let tree = repo.find_tree(tree_builder.write().unwrap()).unwrap();
+
+        let signature = Signature::now("catalog", "catalog@company.com").unwrap();
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some(&reference), &signature, &signature, &format!("update {}", file_name), &tree, &parents).unwrap()
+    }
+
+    pub fn init_bare_repo() -> (std::path::PathBuf, Repository) {
+        let path = std::env::temp_dir().join(format!("apis-catalog-bare-{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&path).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+
+        (path, repo)
+    }
+
+    fn get_catalog(bare_path: &std::path::Path, branch: &str) -> Catalog {
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-clone-{}", uuid::Uuid::new_v4()));
+
+        Catalog {
+            catalog_id: format!("{}", uuid::Uuid::new_v4()),
+            catalog_path: catalog_path.into_os_string().into_string().unwrap(),
+            catalog_scm_url: format!("file://{}", bare_path.to_str().unwrap()),
+            catalog_scm_branch: String::from(branch),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sync_clones_then_fetches() {
+        let (bare_path, bare) = init_bare_repo();
+        let first_commit = commit_file(&bare, "main", "openapi.yaml", "openapi: 3.0.0");
+
+        let catalog = get_catalog(&bare_path, "");
+        let head = super::sync(&catalog).unwrap();
+        assert_eq!(head, first_commit.to_string());
+        let catalog_file = std::path::Path::new(&catalog.catalog_path).join("openapi.yaml");
+        assert_eq!(std::fs::read_to_string(&catalog_file).unwrap(), "openapi: 3.0.0");
+
+        let status = super::get_status(&catalog.catalog_id).unwrap();
+        assert_eq!(status.head_commit, Some(first_commit.to_string()));
+        assert_eq!(status.branch, Some(String::from("main")));
+        assert!(status.last_fetch.is_some());
+        assert!(status.last_error.is_none());
+
+        //local changes are discarded
+        std::fs::write(&catalog_file, "local change").unwrap();
+        let second_commit = commit_file(&bare, "main", "openapi.yaml", "openapi: 3.0.3");
+        let head = super::sync(&catalog).unwrap();
+        assert_eq!(head, second_commit.to_string());
+        assert_eq!(std::fs::read_to_string(&catalog_file).unwrap(), "openapi: 3.0.3");
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_dir_all(&bare_path).unwrap();
+    }
+
+    #[test]
+    fn test_sync_checks_out_the_configured_branch() {
+        let (bare_path, bare) = init_bare_repo();
+        commit_file(&bare, "main", "openapi.yaml", "openapi: 3.0.0");
+        let release_commit = commit_file(&bare, "release", "openapi.yaml", "openapi: 3.1.0");
+
+        let catalog = get_catalog(&bare_path, "release");
+        let head = super::sync(&catalog).unwrap();
+        assert_eq!(head, release_commit.to_string());
+        assert_eq!(super::get_status(&catalog.catalog_id).unwrap().branch, Some(String::from("release")));
+
+        let catalog_file = std::path::Path::new(&catalog.catalog_path).join("openapi.yaml");
+        assert_eq!(std::fs::read_to_string(catalog_file).unwrap(), "openapi: 3.1.0");
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_dir_all(&bare_path).unwrap();
+    }
+
+    #[test]
+    fn test_sync_records_errors() {
+        let bare_path = std::env::temp_dir().join(format!("apis-catalog-missing-{}", uuid::Uuid::new_v4()));
+        let catalog = get_catalog(&bare_path, "");
+
+        let error = super::sync(&catalog).err().unwrap();
+        assert!(error.contains(&catalog.catalog_id));
+
+        let status = super::get_status(&catalog.catalog_id).unwrap();
+        assert!(status.head_commit.is_none());
+        assert_eq!(status.last_error, Some(error));
+    }
+}
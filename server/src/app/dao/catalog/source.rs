@@ -0,0 +1,512 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use sha2::{Digest, Sha256};
+
+use crate::app::dao::repo_catalogs::SourceChecksum;
+use crate::shared::settings::{Catalog, Database};
+
+/// kept in the directories written by `fetch`, the other ones are never replaced
+const MARKER_FILE: &str = ".apis-catalog-source";
+
+/// Where the specs of a catalog come from (refer to `catalog_source_type`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceType {
+    /// a working copy, cloned / pulled through the scm settings
+    Git,
+    /// a zip or tar.gz bundle, unpacked into the catalog directory
+    Archive,
+    /// a JSON list of spec URLs, downloaded into the catalog directory
+    HttpIndex,
+}
+
+impl SourceType {
+    pub fn parse(source_type: &str) -> Result<Self, String> {
+        match source_type.trim().to_lowercase().as_str() {
+            "" | "git" => Ok(SourceType::Git),
+            "archive" => Ok(SourceType::Archive),
+            "http_index" => Ok(SourceType::HttpIndex),
+            other => Err( format!("Unknown catalog source type [{}] - expected `git`, `archive` or `http_index`", other) ),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceType::Git => "git",
+            SourceType::Archive => "archive",
+            SourceType::HttpIndex => "http_index",
+        }
+    }
+}
+
+/// Status of an archive or HTTP index catalog, as of its last fetch
+#[derive(Debug, Clone, Default)]
+pub struct SourceStatus {
+    /// sha256 of the archive, or of the index and the specs it lists
+    pub checksum: Option<String>,
+    /// files written into the catalog directory
+    pub files: usize,
+    pub last_fetch: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+lazy_static! {
+    static ref STATUSES: Mutex<HashMap<String, SourceStatus>> = Mutex::new(HashMap::new());
+}
+
+/// The status of the catalog, its last checksum being read from the database after a restart
+pub fn get_status(database: &Database, catalog_id: &str) -> Option<SourceStatus> {
+    if let Some(status) = STATUSES.lock().unwrap().get(catalog_id) {
+        return Some(status.clone());
+    }
+
+    match crate::app::dao::repo_catalogs::get_source_checksum(database, catalog_id) {
+        Ok(Some(source)) => {
+            let status = SourceStatus { checksum: Some(source.checksum), files: source.files, last_fetch: Some(source.fetched_at), last_error: None };
+            STATUSES.lock().unwrap().insert(String::from(catalog_id), status.clone());
+            Some(status)
+        },
+        Ok(None) => None,
+        Err(why) => {
+            warn!("Unable to read the source checksum of catalog [{:?}] - [{:?}]", catalog_id, why);
+            None
+        }
+    }
+}
+
+pub fn remove_status(database: &Database, catalog_id: &str) {
+    STATUSES.lock().unwrap().remove(catalog_id);
+    if let Err(why) = crate::app::dao::repo_catalogs::delete_source_checksum(database, catalog_id) {
+        warn!("Unable to delete the source checksum of catalog [{:?}] - [{:?}]", catalog_id, why);
+    }
+}
+
+/// Fetches the archive or the index of the catalog and replaces the content of `catalog_path`.
+/// The catalog directory is left untouched if the fetch fails or if the checksum did not change. Returns the checksum,
+/// which is kept in the database.
+pub fn fetch(database: &Database, catalog: &Catalog, source_type: SourceType) -> Result<String, String> {
+    let previous = get_status(database, &catalog.catalog_id).unwrap_or_default();
+    let result = match source_type {
+        SourceType::Archive => fetch_archive(catalog, &previous),
+        SourceType::HttpIndex => fetch_index(catalog, &previous),
+        SourceType::Git => Err( String::from("git catalogs are cloned and pulled through the scm settings") ),
+    };
+
+    let mut statuses = STATUSES.lock().unwrap();
+    let status = statuses.entry(String::from(&catalog.catalog_id)).or_default();
+    match result {
+        Ok((checksum, files)) => {
+            info!("Catalog [{:?}] - fetched [{:?}] files from [{:?}] with checksum [{:?}]", catalog.catalog_id, files, catalog.catalog_source_url, checksum);
+            let source = SourceChecksum { checksum: checksum.clone(), files, fetched_at: Utc::now() };
+            if let Err(why) = crate::app::dao::repo_catalogs::save_source_checksum(database, &catalog.catalog_id, &source) {
+                warn!("Unable to save the source checksum of catalog [{:?}] - [{:?}]", catalog.catalog_id, why);
+            }
+            status.checksum = Some(checksum.clone());
+            status.files = files;
+            status.last_fetch = Some(source.fetched_at);
+            status.last_error = None;
+
+            Ok(checksum)
+        }
+        Err(why) => {
+            let error_message = format!("Unable to fetch catalog [{}] from [{}] - {}", catalog.catalog_id, catalog.catalog_source_url, why);
+            error!("{}", error_message);
+            status.last_error = Some(error_message.clone());
+
+            Err(error_message)
+        }
+    }
+}
+
+fn fetch_archive(catalog: &Catalog, previous: &SourceStatus) -> Result<(String, usize), String> {
+    let content = read(&catalog.catalog_source_url)?;
+    let checksum = format!("{:x}", Sha256::digest(&content));
+    if is_unchanged(catalog, previous, &checksum) {
+        return Ok((checksum, previous.files));
+    }
+
+    let staging_path = get_staging_path(catalog)?;
+    if let Err(why) = unpack(&content, &staging_path) {
+        let _ = std::fs::remove_dir_all(&staging_path);
+        return Err(why);
+    }
+    let files = replace_catalog_dir(catalog, &staging_path)?;
+
+    Ok((checksum, files))
+}
+
+/// The index is either a JSON array of URLs or an object with a `specs` array.
+/// Relative URLs are resolved against the index location and keep their relative path in the catalog directory.
+fn fetch_index(catalog: &Catalog, previous: &SourceStatus) -> Result<(String, usize), String> {
+    let index = read(&catalog.catalog_source_url)?;
+    let index: serde_json::Value = serde_json::from_slice(&index).map_err(|why| format!("Unable to parse index - {}", why))?;
+    let entries = match index.get("specs").unwrap_or(&index).as_array() {
+        Some(entries) => entries.iter().filter_map(|entry| entry.as_str()).map(String::from).collect::<Vec<String>>(),
+        None => return Err( String::from("Unable to parse index - expected an array of URLs or a `specs` array") ),
+    };
+
+    let mut hasher = Sha256::new();
+    let mut specs: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    for entry in entries {
+        let location = resolve(&catalog.catalog_source_url, &entry)?;
+        let content = read(&location)?;
+        debug!("Got spec [{:?}] from index [{:?}]", location, catalog.catalog_source_url);
+
+        hasher.update(entry.as_bytes());
+        hasher.update(&content);
+        specs.push((get_index_file_path(&entry), content));
+    }
+    let checksum = format!("{:x}", hasher.finalize());
+    if is_unchanged(catalog, previous, &checksum) {
+        return Ok((checksum, previous.files));
+    }
+
+    let staging_path = get_staging_path(catalog)?;
+    for (file_path, content) in specs {
+        let file_path = staging_path.join(file_path);
+        let written = std::fs::create_dir_all(file_path.parent().unwrap()).and_then(|_| std::fs::write(&file_path, content));
+        if let Err(why) = written {
+            let _ = std::fs::remove_dir_all(&staging_path);
+            return Err( format!("Unable to write [{:?}] - {}", file_path, why) );
+        }
+    }
+    let files = replace_catalog_dir(catalog, &staging_path)?;
+
+    Ok((checksum, files))
+}
+
+fn is_unchanged(catalog: &Catalog, previous: &SourceStatus, checksum: &str) -> bool {
+    let unchanged = previous.checksum.as_deref() == Some(checksum) && Path::new(&catalog.catalog_path).is_dir();
+    if unchanged {
+        info!("Catalog [{:?}] - [{:?}] did not change since its last fetch", catalog.catalog_id, catalog.catalog_source_url);
+    }
+
+    unchanged
+}
+
+/// `http(s)://` URLs are downloaded, anything else (optionally prefixed by `file://`) is read from disk
+fn read(location: &str) -> Result<Vec<u8>, String> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let url = String::from(location);
+        //the blocking client cannot be used from within the (actix) runtime
+        return std::thread::spawn(move || -> Result<Vec<u8>, reqwest::Error> {
+            let response = reqwest::blocking::get(url)?.error_for_status()?;
+            Ok(response.bytes()?.to_vec())
+        })
+        .join()
+        .map_err(|_why| format!("Unable to download [{}]", location))?
+        .map_err(|why| format!("Unable to download [{}] - {}", location, why));
+    }
+
+    let path = location.strip_prefix("file://").unwrap_or(location);
+    std::fs::read(path).map_err(|why| format!("Unable to read [{}] - {}", path, why))
+}
+
+/// Location of a spec listed by an index - an `http(s)` index only lists `http(s)` URLs,
+/// a local index only lists files of its directory (or `http(s)` URLs)
+fn resolve(index_location: &str, entry: &str) -> Result<String, String> {
+    let is_http = |location: &str| location.starts_with("http://") || location.starts_with("https://");
+    if is_http(entry) {
+        return Ok(String::from(entry));
+    }
+
+    if is_http(index_location) {
+        let index_url = reqwest::Url::parse(index_location).map_err(|why| format!("Unable to resolve [{}] - {}", entry, why))?;
+        let url = index_url.join(entry).map_err(|why| format!("Unable to resolve [{}] - {}", entry, why))?;
+        return match url.scheme() {
+            "http" | "https" => Ok(String::from(url)),
+            scheme => Err( format!("Unable to resolve [{}] - [{}] entries are not allowed in an http index", entry, scheme) ),
+        };
+    }
+
+    let index_path = Path::new(index_location.strip_prefix("file://").unwrap_or(index_location));
+    let index_dir = std::fs::canonicalize(index_path.parent().unwrap_or(Path::new("."))).map_err(|why| format!("Unable to resolve [{}] - {}", entry, why))?;
+    let file_path = std::fs::canonicalize(index_dir.join(entry.strip_prefix("file://").unwrap_or(entry))).map_err(|why| format!("Unable to resolve [{}] - {}", entry, why))?;
+    match file_path.starts_with(&index_dir) {
+        true => Ok(String::from(file_path.to_string_lossy())),
+        false => Err( format!("Unable to resolve [{}] - the file is outside of the index directory", entry) ),
+    }
+}
+
+/// path of a spec listed by an index, relative to the catalog directory - parent (`..`) and root components are dropped
+fn get_index_file_path(entry: &str) -> PathBuf {
+    let path = match reqwest::Url::parse(entry) {
+        Ok(url) => String::from(url.path()),
+        Err(_) => String::from(entry),
+    };
+
+    Path::new(&path).components().filter_map(|component| match component {
+        Component::Normal(name) => Some(name),
+        _ => None,
+    }).collect()
+}
+
+/// zip, tar.gz and tar archives, detected from their content
+fn unpack(content: &[u8], target: &Path) -> Result<(), String> {
+    if content.starts_with(b"PK\x03\x04") {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(content)).map_err(|why| format!("Unable to read zip archive - {}", why))?;
+        return archive.extract(target).map_err(|why| format!("Unable to unpack zip archive - {}", why));
+    }
+
+    let mut tar_content = Vec::new();
+    let content = match content.starts_with(&[0x1f, 0x8b]) {
+        true => {
+            flate2::read::GzDecoder::new(content).read_to_end(&mut tar_content).map_err(|why| format!("Unable to read gzip archive - {}", why))?;
+            tar_content.as_slice()
+        },
+        false => content,
+    };
+    if content.len() < 262 || &content[257..262] != b"ustar" {
+        return Err( String::from("Unsupported archive - expected a zip, tar.gz or tar archive") );
+    }
+
+    //entries outside of the target directory are skipped
+    tar::Archive::new(content).unpack(target).map_err(|why| format!("Unable to unpack tar archive - {}", why))
+}
+
+/// a sibling of the catalog directory, so that it can be renamed
+fn get_staging_path(catalog: &Catalog) -> Result<PathBuf, String> {
+    let staging_path = PathBuf::from(format!("{}.staging", catalog.catalog_path.trim_end_matches('/')));
+    let _ = std::fs::remove_dir_all(&staging_path);
+    std::fs::create_dir_all(&staging_path).map_err(|why| format!("Unable to create [{:?}] - {}", staging_path, why))?;
+
+    Ok(staging_path)
+}
+
+/// Replaces the catalog directory by the staging one, returns the number of files. The catalog directory
+/// is only replaced if empty or written by a previous fetch (i.e. it holds the marker file)
+fn replace_catalog_dir(catalog: &Catalog, staging_path: &Path) -> Result<usize, String> {
+    let files = globwalk::GlobWalkerBuilder::from_patterns(staging_path, &["**"])
+        .file_type(globwalk::FileType::FILE)
+        .build()
+        .map(|walker| walker.filter_map(Result::ok).count())
+        .unwrap_or(0);
+
+    let catalog_path = Path::new(&catalog.catalog_path);
+    if catalog_path.exists() {
+        let is_empty = std::fs::read_dir(catalog_path).map(|mut entries| entries.next().is_none()).unwrap_or(false);
+        if !is_empty && !catalog_path.join(MARKER_FILE).is_file() {
+            let _ = std::fs::remove_dir_all(staging_path);
+            return Err( format!("[{:?}] has not been written by a previous fetch and will not be replaced - empty it or use another catalog path", catalog_path) );
+        }
+        std::fs::remove_dir_all(catalog_path).map_err(|why| format!("Unable to clean [{:?}] - {}", catalog_path, why))?;
+    }
+    std::fs::write(staging_path.join(MARKER_FILE), &catalog.catalog_source_url).map_err(|why| format!("Unable to write [{:?}] - {}", staging_path, why))?;
+    std::fs::rename(staging_path, catalog_path).map_err(|why| format!("Unable to move [{:?}] to [{:?}] - {}", staging_path, catalog_path, why))?;
+
+    Ok(files)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::{Read, Write};
+    use super::SourceType;
+    use crate::shared::settings::Catalog;
+
+    /// Serves the given (path, content) over HTTP - unknown paths get a 404. Returns the base URL.
+    pub fn start_http_stub(files: Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 4096];
+                let size = stream.read(&mut request).unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..size]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                let response = match files.iter().find(|(file_path, _content)| *file_path == path) {
+                    Some((_file_path, content)) => [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", content.len()).into_bytes(), content.to_vec()].concat(),
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    fn get_catalog(source_type: &str, source_url: &str) -> Catalog {
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-source-{}", uuid::Uuid::new_v4()));
+
+        Catalog {
+            catalog_id: format!("{}", uuid::Uuid::new_v4()),
+            catalog_path: catalog_path.into_os_string().into_string().unwrap(),
+            catalog_source_type: String::from(source_type),
+            catalog_source_url: String::from(source_url),
+            ..Default::default()
+        }
+    }
+
+    fn get_tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        for (file_path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, file_path, content.as_bytes()).unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_parse_source_type() {
+        assert_eq!(SourceType::parse("").unwrap(), SourceType::Git);
+        assert_eq!(SourceType::parse("Archive").unwrap(), SourceType::Archive);
+        assert_eq!(SourceType::parse("http_index").unwrap(), SourceType::HttpIndex);
+        assert!(SourceType::parse("svn").is_err());
+    }
+
+    #[test]
+    fn test_fetch_zip_archive_from_a_local_path() {
+        let database = crate::app::dao::repo_catalogs::tests::get_database();
+        let archive_path = std::env::temp_dir().join(format!("apis-catalog-archive-{}.zip", uuid::Uuid::new_v4()));
+        {
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive_path).unwrap());
+            writer.start_file("pets/openapi.yaml", zip::write::FileOptions::default()).unwrap();
+            writer.write_all(b"openapi: 3.0.0").unwrap();
+            writer.finish().unwrap();
+        }
+
+        let catalog = get_catalog("archive", archive_path.to_str().unwrap());
+        let checksum = super::fetch(&database, &catalog, SourceType::Archive).unwrap();
+        assert_eq!(checksum.len(), 64);
+        assert_eq!(std::fs::read_to_string(std::path::Path::new(&catalog.catalog_path).join("pets/openapi.yaml")).unwrap(), "openapi: 3.0.0");
+        assert_eq!(super::get_status(&database, &catalog.catalog_id).unwrap().files, 1);
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_tar_gz_archive_over_http() {
+        let database = crate::app::dao::repo_catalogs::tests::get_database();
+        let base_url = start_http_stub(vec![
+            ("/v1/specs.tar.gz", get_tar_gz(&[("pets/openapi.yaml", "openapi: 3.0.0"), ("stores/asyncapi.yaml", "asyncapi: 2.0.0")])),
+        ]);
+        let catalog = get_catalog("archive", &format!("{}/v1/specs.tar.gz", base_url));
+        let catalog_path = std::path::Path::new(&catalog.catalog_path);
+
+        let checksum = super::fetch(&database, &catalog, SourceType::Archive).unwrap();
+        assert!(catalog_path.join("pets/openapi.yaml").exists());
+        assert!(catalog_path.join("stores/asyncapi.yaml").exists());
+
+        //unchanged archive, the catalog directory is kept as is
+        std::fs::write(catalog_path.join("local.yaml"), "openapi: 3.0.0").unwrap();
+        assert_eq!(super::fetch(&database, &catalog, SourceType::Archive).unwrap(), checksum);
+        assert!(catalog_path.join("local.yaml").exists());
+        assert_eq!(super::get_status(&database, &catalog.catalog_id).unwrap().files, 2);
+
+        //the checksum is read from the database after a restart
+        super::STATUSES.lock().unwrap().remove(&catalog.catalog_id);
+        assert_eq!(super::fetch(&database, &catalog, SourceType::Archive).unwrap(), checksum);
+        assert!(catalog_path.join("local.yaml").exists());
+
+        super::remove_status(&database, &catalog.catalog_id);
+        assert!(super::get_status(&database, &catalog.catalog_id).is_none());
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_http_index() {
+        let database = crate::app::dao::repo_catalogs::tests::get_database();
+        let base_url = start_http_stub(vec![
+            ("/index.json", br#"{ "specs": [ "pets/openapi.yaml", "../stores/asyncapi.yaml" ] }"#.to_vec()),
+            ("/pets/openapi.yaml", b"openapi: 3.0.0".to_vec()),
+            ("/stores/asyncapi.yaml", b"asyncapi: 2.0.0".to_vec()),
+        ]);
+        let catalog = get_catalog("http_index", &format!("{}/index.json", base_url));
+        let catalog_path = std::path::Path::new(&catalog.catalog_path);
+
+        super::fetch(&database, &catalog, SourceType::HttpIndex).unwrap();
+        assert_eq!(std::fs::read_to_string(catalog_path.join("pets/openapi.yaml")).unwrap(), "openapi: 3.0.0");
+        assert_eq!(std::fs::read_to_string(catalog_path.join("stores/asyncapi.yaml")).unwrap(), "asyncapi: 2.0.0");
+        assert_eq!(super::get_status(&database, &catalog.catalog_id).unwrap().files, 2);
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+
+    #[test]
+    fn test_index_entries_stay_in_the_index_location() {
+        assert_eq!(super::resolve("https://host/specs/index.json", "../pets.yaml").unwrap(), "https://host/pets.yaml");
+        assert!(super::resolve("https://host/specs/index.json", "file:///etc/passwd").unwrap_err().contains("not allowed"));
+
+        let index_dir = std::env::temp_dir().join(format!("apis-catalog-index-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(index_dir.join("pets")).unwrap();
+        std::fs::write(index_dir.join("pets/openapi.yaml"), "openapi: 3.0.0").unwrap();
+        let index_location = String::from(index_dir.join("index.json").to_str().unwrap());
+
+        let resolved = super::resolve(&index_location, "pets/openapi.yaml").unwrap();
+        assert_eq!(std::path::Path::new(&resolved), std::fs::canonicalize(index_dir.join("pets/openapi.yaml")).unwrap());
+        let outside = std::env::temp_dir().join(format!("apis-catalog-outside-{}.yaml", uuid::Uuid::new_v4()));
+        std::fs::write(&outside, "openapi: 3.0.0").unwrap();
+        let outside = String::from(outside.to_str().unwrap());
+        assert!(super::resolve(&index_location, &outside).unwrap_err().contains("outside of the index directory"));
+        assert!(super::resolve(&index_location, &format!("file://{}", outside)).unwrap_err().contains("outside of the index directory"));
+        assert!(super::resolve(&index_location, &format!("../{}", outside.rsplit('/').next().unwrap())).unwrap_err().contains("outside of the index directory"));
+
+        std::fs::remove_file(&outside).unwrap();
+        std::fs::remove_dir_all(&index_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_does_not_replace_foreign_dirs() {
+        let database = crate::app::dao::repo_catalogs::tests::get_database();
+        let base_url = start_http_stub(vec![
+            ("/v1/specs.tar.gz", get_tar_gz(&[("pets/openapi.yaml", "openapi: 3.0.0")])),
+        ]);
+        let catalog = get_catalog("archive", &format!("{}/v1/specs.tar.gz", base_url));
+        let catalog_path = std::path::Path::new(&catalog.catalog_path);
+        std::fs::create_dir_all(catalog_path).unwrap();
+        std::fs::write(catalog_path.join("notes.txt"), "not a spec").unwrap();
+
+        assert!(super::fetch(&database, &catalog, SourceType::Archive).unwrap_err().contains("will not be replaced"));
+        assert!(catalog_path.join("notes.txt").exists());
+        assert!(!std::path::Path::new(&format!("{}.staging", catalog.catalog_path)).exists());
+
+        //an empty directory is
+        std::fs::remove_file(catalog_path.join("notes.txt")).unwrap();
+        super::fetch(&database, &catalog, SourceType::Archive).unwrap();
+        assert!(catalog_path.join("pets/openapi.yaml").exists());
+        assert!(catalog_path.join(super::MARKER_FILE).exists());
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_errors_keep_the_catalog_dir() {
+        let database = crate::app::dao::repo_catalogs::tests::get_database();
+        let base_url = start_http_stub(vec![
+            ("/index.json", br#"[ "pets/openapi.yaml", "missing.yaml" ]"#.to_vec()),
+            ("/pets/openapi.yaml", b"openapi: 3.0.0".to_vec()),
+            ("/not-an-archive.zip", b"openapi: 3.0.0".to_vec()),
+        ]);
+
+        let catalog = get_catalog("http_index", &format!("{}/index.json", base_url));
+        std::fs::create_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::write(std::path::Path::new(&catalog.catalog_path).join("openapi.yaml"), "openapi: 3.0.0").unwrap();
+
+        let why = super::fetch(&database, &catalog, SourceType::HttpIndex).unwrap_err();
+        assert!(why.contains("404"));
+        assert!(std::path::Path::new(&catalog.catalog_path).join("openapi.yaml").exists());
+        assert_eq!(super::get_status(&database, &catalog.catalog_id).unwrap().last_error, Some(why));
+
+        let archive = get_catalog("archive", &format!("{}/not-an-archive.zip", base_url));
+        assert!(super::fetch(&database, &archive, SourceType::Archive).unwrap_err().contains("Unsupported archive"));
+        assert!(!std::path::Path::new(&archive.catalog_path).exists());
+
+        std::fs::remove_dir_all(&catalog.catalog_path).unwrap();
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+}
@@ -1,6 +1,5 @@
 use log::warn;
-use regex::Regex;
-use super::handlers::{Path, SpecHandler, SpecType};
+use super::handlers::{Path, SpecHandler, SpecType, SpecDocument, SpecFormat, SecurityScheme, OperationSecurity, SPEC_FORMATS};
 
 #[derive(Debug, Clone)]
 pub struct SpecItem {
@@ -81,111 +80,89 @@ impl SpecItem {
     pub fn get_spec_type(&self) -> SpecType {
         self.spec_type
     }
-    
-}
 
-lazy_static! {
-    static ref OPENAPI: Regex = Regex::new(r"openapi:\W*3").expect("Invalid regex pattern");
-    static ref ASYNCAPI_V1: Regex = Regex::new(r"asyncapi:\W*1").expect("Invalid regex pattern");
-    static ref ASYNCAPI_V2: Regex = Regex::new(r"asyncapi:\W*2").expect("Invalid regex pattern");
-    static ref PROTO_3: Regex = Regex::new(r"syntax=.proto3").expect("Invalid regex pattern");
+    pub fn get_document(&self) -> Option<serde_yaml::Value> {
+        self.handler.get_document()
+    }
+
+    pub fn get_security_schemes(&self) -> Vec<SecurityScheme> {
+        self.handler.get_security_schemes()
+    }
 
-    pub static ref REGEXES: Vec<&'static Regex> = vec![&OPENAPI, &ASYNCAPI_V1, &ASYNCAPI_V2, &PROTO_3];
+    pub fn get_operations_security(&self) -> Vec<OperationSecurity> {
+        self.handler.get_operations_security()
+    }
+    
 }
 
+/// Builds the `SpecItem` of a spec file. Its format is detected by asking all the registered `SpecFormat`s
+/// (refer to `SPEC_FORMATS`), exactly one of them must claim it.
 pub fn from_str(path: std::string::String, catalog_id: String, catalog_dir: String, spec: &str) -> Result<SpecItem, String> {
+    let formats: Vec<&SpecFormat> = {
+        let document = SpecDocument::new(&path, spec);
+        SPEC_FORMATS.iter().filter(|format| (format.detect)(&document)).collect()
+    };
 
-    let mut returned_val = Err( format!("Unable to parse content for path {:?}", path) );
-    for (index, regex) in REGEXES.iter().enumerate() {
-        if regex.is_match(spec) {
-            returned_val = match index {
-                0 => {
-                    let val = match crate::app::dao::catalog::handlers::implem::opanapi::V3::new(&spec) {
-                        Ok(v3) => {
-                            let spec = SpecItem{
-                                spec_type: SpecType::OpenAPIv3,
-                                path: path.clone(), 
-                                catalog_id: catalog_id.clone(),
-                                catalog_dir: catalog_dir.clone(),
-                                handler: Box::new( v3 ),
-                            };
-
-                            Ok(spec)
-                        },
-                        Err(why)=> {
-                            warn!("Unable to parse file [{:?}] - reason [{:?}]", &path, &why);
-                            let error_message = format!("Unable to parse file [{:?}] - reason [{:?}]", path, &why);
-                        
-                            Err( error_message )
-                        }
-                    };
+    match formats.as_slice() {
+        [] => {
+            warn!("Content for spec  [{:?}] does not match any of the support spec format", &path);
+            let error_message = format!("Content for spec  [{:?}] does not match any of the support spec format", &path);
 
-                    val
-                }
-                1 => {
-                    let val = match crate::app::dao::catalog::handlers::implem::asyncapi::V1::new(&spec){
-                        Ok(v1) => {
-                            let spec = SpecItem{
-                                spec_type: SpecType::AsyncAPIv1,
-                                path: path.clone(), 
-                                catalog_id: catalog_id.clone(),
-                                catalog_dir: catalog_dir.clone(),
-                                handler: Box::new( v1 ),
-                            };
-
-                            Ok(spec)
-                        }
-                        Err(why) => {
-                            warn!("Unable to parse file [{:?}] - reason [{:?}]", &path, &why);
-                            let error_message = format!("Unable to parse file [{:?}] - reason [{:?}]", path, &why);
-                        
-                            Err( error_message )
-                        }
+            Err( error_message )
+        }
+        [format] => {
+            match (format.build)(spec) {
+                Ok(handler) => {
+                    let spec = SpecItem{
+                        spec_type: format.spec_type,
+                        path,
+                        catalog_id,
+                        catalog_dir,
+                        handler,
                     };
 
-                    val
+                    Ok(spec)
                 }
-                2 => {
-                    let val = match crate::app::dao::catalog::handlers::implem::asyncapi::V2::new(&spec){
-                        Ok(v2) => {
-                            let spec = SpecItem{
-                                spec_type: SpecType::AsyncAPIv2,
-                                path: path.clone(), 
-                                catalog_id: catalog_id.clone(),
-                                catalog_dir: catalog_dir.clone(),
-                                handler: Box::new( v2 ),
-                            };
-
-                            Ok(spec)
-                        }
-                        Err(why) => {
-                            warn!("Unable to parse file [{:?}] - reason [{:?}]", &path, &why);
-                            let error_message = format!("Unable to parse file [{:?}] - reason [{:?}]", path, &why);
-                        
-                            Err( error_message )
-                        }
-                    };
+                Err(why) => {
+                    warn!("Unable to parse file [{:?}] - reason [{:?}]", &path, &why);
+                    let error_message = format!("Unable to parse file [{:?}] - reason [{:?}]", path, &why);
 
-                    val
-                }
-                // 3 => {
-                //     println!("index {:?}", index);
-                // }
-                _ => {
-                    warn!("Content for spec  [{:?}] does not match any of the support spec format", &path);
-                    let error_message = format!("Content for spec  [{:?}] does not match any of the support spec format", &path);
-        
                     Err( error_message )
                 }
-            };
-            break;
+            }
+        }
+        formats => {
+            let spec_types: Vec<String> = formats.iter().map(|format| format.spec_type.to_string()).collect();
+            warn!("Content for spec [{:?}] is claimed by several spec formats {:?}", &path, &spec_types);
+            let error_message = format!("Content for spec [{:?}] is claimed by several spec formats {:?}", path, spec_types);
+
+            Err( error_message )
         }
     }
+}
 
-    returned_val
 
-}
+/// JSON specs are converted to yaml, so that handlers and metrics extractors only have to deal with yaml.
+/// Any other content (yaml, proto, graphql...) is returned as is.
+pub fn to_yaml(content: &str) -> String {
+    if !content.trim_start().starts_with('{') {
+        return String::from(content);
+    }
 
+    match serde_json::from_str::<serde_yaml::Value>(content) {
+        Ok(value) => match serde_yaml::to_string(&value) {
+            Ok(yaml) => yaml,
+            Err(why) => {
+                warn!("Unable to convert json content to yaml - reason [{:?}]", why);
+                String::from(content)
+            }
+        },
+        Err(why) => {
+            warn!("Unable to parse json content - reason [{:?}]", why);
+            String::from(content)
+        }
+    }
+}
 
 pub fn extact_relative_path<'a>(spec_path: &'a String, catalog_dir_srt: &'a String) -> &'a str {
     let catalog_dir = catalog_dir_srt.as_str().len();
@@ -306,4 +283,168 @@ pub mod tests {
         assert_eq!(spec.get_paths_len(), 1);
     }
 
+    #[test]
+    fn test_spec_item_from_str_for_openapi_v31_and_swagger_v2(){
+        let spec = "
+        openapi: '3.1.0'
+        info:
+          title: My API
+          version: 1.4.0
+        paths:
+          /example:
+            get:
+              summary: Get example
+        ";
+
+        let spec = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).unwrap();
+        assert_eq!(spec.get_spec_type(), SpecType::OpenAPIv31);
+        assert_eq!(spec.get_version(), "1.4.0");
+        assert_eq!(spec.get_paths_len(), 1);
+
+        let spec = "
+        swagger: '2.0'
+        info:
+          title: My legacy API
+          version: 0.1.0
+        basePath: /v1/legacy
+        paths:
+          /example:
+            get:
+              summary: Get example
+        ";
+
+        let spec = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).unwrap();
+        assert_eq!(spec.get_spec_type(), SpecType::Swagger2);
+        assert_eq!(spec.get_title(), "My legacy API");
+        assert_eq!(spec.get_domain(), "/v1/legacy");
+        assert_eq!(spec.get_paths()[0].methods[0].method, "GET");
+    }
+
+    #[test]
+    fn test_spec_item_from_str_detects_on_document_root(){
+        let spec = "
+        openapi: 3.0.3
+        info:
+          title: My API
+          version: 1.4.0
+          description: |
+            this API replaces the events previously published with
+            asyncapi: 2.6.0
+        paths: {}
+        ";
+
+        let spec = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).unwrap();
+        assert_eq!(spec.get_spec_type(), SpecType::OpenAPIv3);
+
+        let spec = "
+        openapi: 3.0.3
+        asyncapi: 2.6.0
+        info:
+          title: My API
+          version: 1.4.0
+        paths: {}
+        channels: {}
+        ";
+
+        let error = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).err().unwrap();
+        assert!(error.contains("claimed by several spec formats"));
+        assert!(error.contains("OpenAPI.v3"));
+        assert!(error.contains("AsyncAPI.v2"));
+
+        let spec = "
+        info:
+          title: My API
+        ";
+
+        let error = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).err().unwrap();
+        assert!(error.contains("does not match any of the support spec format"));
+    }
+
+    #[test]
+    fn test_spec_item_from_str_detects_on_file_extension(){
+        let spec = "
+        package v1.audit.trails;
+        service HistoryTrail {}
+        ";
+
+        let spec = crate::app::dao::catalog::spec::from_str("history-trail.proto".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).err().unwrap();
+        //claimed as proto3 (because of the extension) but syntax is not specified
+        assert!(spec.contains("Unable to parse file"));
+
+        let spec = "
+        type Query {
+          events: [String]
+        }
+        ";
+
+        let spec = crate::app::dao::catalog::spec::from_str("events.graphqls".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), spec).unwrap();
+        assert_eq!(spec.get_spec_type(), SpecType::GraphQL);
+    }
+
+    #[test]
+    fn test_to_yaml(){
+        //json with tab indentation is not valid yaml
+        let content = "{\n\t\"asyncapi\": \"2.6.0\",\n\t\"info\": {\n\t\t\"title\": \"Account Service\",\n\t\t\"version\": \"1.0.0\"\n\t},\n\t\"channels\": { \"user/signedup\": { \"publish\": {} } }\n}";
+
+        let spec = crate::app::dao::catalog::spec::from_str("path.json".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), super::to_yaml(content).as_str()).unwrap();
+        assert_eq!(spec.get_spec_type(), SpecType::AsyncAPIv2);
+        assert_eq!(spec.get_title(), "Account Service");
+        assert_eq!(spec.get_paths()[0].path, "user/signedup");
+
+        let content = "openapi: 3.0.0";
+        assert_eq!(super::to_yaml(content), content);
+        let content = "{ not json";
+        assert_eq!(super::to_yaml(content), content);
+    }
+
+    #[test]
+    fn test_spec_item_from_str_for_graphql(){
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/catalog/graphql/audit-trails.graphql");
+
+        let content = std::fs::read_to_string(path.as_path()).unwrap();
+
+        let spec = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), content.as_str()).unwrap();
+
+        assert_eq!(spec.get_spec_type(), SpecType::GraphQL);
+        assert_eq!(spec.get_title(), "Audit Trails");
+        assert_eq!(spec.get_paths_len(), 4);
+    }
+
+    #[test]
+    fn test_spec_item_from_str_for_asyncapi_v3(){
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/catalog/async/messaging-3.0.0.yml");
+
+        let content = std::fs::read_to_string(path.as_path()).unwrap();
+
+        let spec = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), content.as_str()).unwrap();
+
+        assert_eq!(spec.get_spec_type(), SpecType::AsyncAPIv3);
+        assert_eq!(spec.get_version(), "2.0.0");
+        assert_eq!(spec.get_title(), "Account Service");
+        assert_eq!(spec.get_paths_len(), 2);
+    }
+
+    #[test]
+    fn test_spec_item_from_str_for_proto3(){
+        let mut path = std::path::PathBuf::new();
+        path.push(env!("CARGO_MANIFEST_DIR"));
+        path.push("./tests/data/catalog/proto/history-trail.proto");
+
+        let content = std::fs::read_to_string(path.as_path()).unwrap();
+
+        let spec = crate::app::dao::catalog::spec::from_str("path".to_string(), "catalog_id".to_string(), "catalog_dir".to_string(), content.as_str()).unwrap();
+
+        assert_eq!(spec.get_spec_type(), SpecType::Proto3);
+        assert_eq!(spec.get_version(), "v1");
+        assert_eq!(spec.get_title(), "HistoryTrail");
+        assert_eq!(spec.get_description(), "Exposes the audit trail events");
+        assert_eq!(spec.get_domain(), "/v1/audit/trails");
+        assert_eq!(spec.get_audience(), "company");
+        assert_eq!(spec.get_paths_len(), 2);
+    }
+
 }
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{debug, error, info};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::shared::settings::Catalog;
+
+/// events received within this delay are handled together (an editor usually writes a file several times)
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+lazy_static! {
+    /// watchers stop as soon as they are dropped
+    static ref WATCHERS: Mutex<HashMap<String, RecommendedWatcher>> = Mutex::new(HashMap::new());
+}
+
+/// Watches the files under `catalog_path`, `on_change` is called (from a dedicated thread) with the created,
+/// modified or deleted files. Watching an already watched catalog replaces the previous watcher.
+pub fn watch<F>(catalog: &Catalog, on_change: F) -> Result<(), String>
+where
+    F: Fn(&Catalog, &[PathBuf]) + Send + 'static,
+{
+    let catalog_path = std::fs::canonicalize(&catalog.catalog_path).map_err(|why| format!("Unable to watch catalog [{}] - path [{}] not found - {}", catalog.catalog_id, catalog.catalog_path, why))?;
+
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|why| format!("Unable to watch catalog [{}] - {}", catalog.catalog_id, why))?;
+    watcher.watch(&catalog_path, RecursiveMode::Recursive).map_err(|why| format!("Unable to watch catalog [{}] - {}", catalog.catalog_id, why))?;
+
+    let watched_catalog = catalog.clone();
+    std::thread::spawn(move || {
+        let mut changed_files: Vec<PathBuf> = Vec::new();
+        loop {
+            let event = match changed_files.is_empty() {
+                true => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                false => receiver.recv_timeout(DEBOUNCE_DELAY),
+            };
+
+            match event {
+                Ok(Ok(event)) => {
+                    debug!("Got event [{:?}] for catalog [{:?}]", event, watched_catalog.catalog_id);
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                        for path in event.paths {
+                            if !changed_files.contains(&path) {
+                                changed_files.push(path);
+                            }
+                        }
+                    }
+                },
+                Ok(Err(why)) => error!("Error while watching catalog [{:?}] - [{:?}]", watched_catalog.catalog_id, why),
+                Err(RecvTimeoutError::Timeout) => {
+                    info!("[{:?}] files changed in catalog [{:?}]", changed_files.len(), watched_catalog.catalog_id);
+                    on_change(&watched_catalog, &changed_files);
+                    changed_files.clear();
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    info!("Stop watching catalog [{:?}]", watched_catalog.catalog_id);
+                    break;
+                },
+            }
+        }
+    });
+
+    info!("Watching catalog [{:?}] - [{:?}]", catalog.catalog_id, catalog_path);
+    WATCHERS.lock().unwrap().insert(String::from(&catalog.catalog_id), watcher);
+
+    Ok(())
+}
+
+/// Stops watching the catalog, if it was
+pub fn unwatch(catalog_id: &str) {
+    if WATCHERS.lock().unwrap().remove(catalog_id).is_some() {
+        info!("Catalog [{:?}] is no longer watched", catalog_id);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    use crate::shared::settings::Catalog;
+
+    #[test]
+    fn test_watch_reports_changed_files() {
+        let catalog_path = std::env::temp_dir().join(format!("apis-catalog-watch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&catalog_path).unwrap();
+        let catalog = Catalog {
+            catalog_id: format!("{}", uuid::Uuid::new_v4()),
+            catalog_path: catalog_path.clone().into_os_string().into_string().unwrap(),
+            ..Default::default()
+        };
+
+        let (sender, receiver) = channel();
+        super::watch(&catalog, move |_catalog, changed_files| sender.send(changed_files.to_vec()).unwrap()).unwrap();
+
+        std::fs::write(catalog_path.join("openapi.yaml"), "openapi: 3.0.0").unwrap();
+        std::fs::write(catalog_path.join("openapi.yaml"), "openapi: 3.0.3").unwrap();
+        let changed_files = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(changed_files, vec![std::fs::canonicalize(&catalog_path).unwrap().join("openapi.yaml")]);
+
+        super::unwatch(&catalog.catalog_id);
+        std::fs::remove_dir_all(&catalog_path).unwrap();
+    }
+}
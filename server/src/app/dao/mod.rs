@@ -1,5 +1,6 @@
 pub mod catalog;
 pub mod repo_apis;
+pub mod repo_catalogs;
 pub mod repo_deployments;
 pub mod repo_domains;
 pub mod repo_envs;
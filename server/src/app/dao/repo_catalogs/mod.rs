@@ -0,0 +1,284 @@
+extern crate rusqlite;
+
+use chrono::{DateTime, Utc};
+
+use rusqlite::NO_PARAMS;
+use rusqlite::{params, Connection, Result, Row};
+
+use crate::shared::settings::*;
+
+use log::debug;
+
+/// Catalogs registered through the API. They are either cloned and fetched natively (refer to `catalog_scm_url`)
+/// or fed by an archive / HTTP index (refer to `catalog_source_type`). Their credentials are not stored, only
+/// the environment variable holding them (refer to `catalog_scm_password_env`)
+pub fn list_all_catalogs(config: &Database) -> Result<Vec<Catalog>> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Reading all catalogs from Catalog_Database [{:?}]", db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+
+    let mut stmt = conn.prepare("SELECT catalog_id, name, path, dir, include_glob_pattern, http_base_uri, scm_url, scm_branch, scm_depth, scm_username, scm_password_env, watch, source_type, source_url, ruleset_path FROM catalogs ORDER BY created_at")?;
+    let mut rows = stmt.query(NO_PARAMS)?;
+
+    let mut catalogs = Vec::new();
+    while let Some(row) = rows.next()? {
+        catalogs.push(to_catalog(row)?);
+    }
+
+    Ok(catalogs)
+}
+
+pub fn add_catalog(config: &Database, catalog: &Catalog) -> Result<()> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Creating catalog [{:?}] into Catalog_Database [{:?}]", catalog.catalog_id, db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+
+    conn.execute(
+        "INSERT INTO catalogs (catalog_id, name, path, dir, include_glob_pattern, http_base_uri, scm_url, scm_branch, scm_depth, scm_username, scm_password_env, watch, source_type, source_url, ruleset_path, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            catalog.catalog_id,
+            catalog.catalog_name,
+            catalog.catalog_path,
+            catalog.catalog_dir,
+            serde_json::to_string(&catalog.catalog_include_glob_pattern).unwrap(),
+            catalog.catalog_http_base_uri,
+            catalog.catalog_scm_url,
+            catalog.catalog_scm_branch,
+            catalog.catalog_scm_depth,
+            catalog.catalog_scm_username,
+            catalog.catalog_scm_password_env,
+            catalog.catalog_watch,
+            catalog.catalog_source_type,
+            catalog.catalog_source_url,
+            catalog.catalog_ruleset_path,
+            Utc::now()
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Returns the number of updated catalogs - 0 if the catalog does not exist
+pub fn update_catalog(config: &Database, catalog: &Catalog) -> Result<usize> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Updating catalog [{:?}] into Catalog_Database [{:?}]", catalog.catalog_id, db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+
+    let updated = conn.execute(
+        "UPDATE catalogs SET name = ?2, path = ?3, dir = ?4, include_glob_pattern = ?5, http_base_uri = ?6, scm_url = ?7, scm_branch = ?8,
+            scm_depth = ?9, scm_username = ?10, scm_password_env = ?11, watch = ?12, source_type = ?13, source_url = ?14, ruleset_path = ?15 WHERE catalog_id = ?1",
+        params![
+            catalog.catalog_id,
+            catalog.catalog_name,
+            catalog.catalog_path,
+            catalog.catalog_dir,
+            serde_json::to_string(&catalog.catalog_include_glob_pattern).unwrap(),
+            catalog.catalog_http_base_uri,
+            catalog.catalog_scm_url,
+            catalog.catalog_scm_branch,
+            catalog.catalog_scm_depth,
+            catalog.catalog_scm_username,
+            catalog.catalog_scm_password_env,
+            catalog.catalog_watch,
+            catalog.catalog_source_type,
+            catalog.catalog_source_url,
+            catalog.catalog_ruleset_path
+        ],
+    )?;
+
+    Ok(updated)
+}
+
+/// Returns the number of deleted catalogs - 0 if the catalog does not exist
+pub fn delete_catalog(config: &Database, id: &str) -> Result<usize> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Deleting catalog [{:?}] from Catalog_Database [{:?}]", id, db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+    let deleted = conn.execute("DELETE FROM catalogs WHERE catalog_id = ?1", params![id])?;
+
+    Ok(deleted)
+}
+
+/// Last fetch of an archive or HTTP index catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceChecksum {
+    pub checksum: String,
+    pub files: usize,
+    pub fetched_at: DateTime<Utc>,
+}
+
+pub fn get_source_checksum(config: &Database, catalog_id: &str) -> Result<Option<SourceChecksum>> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Reading source checksum of catalog [{:?}] from Catalog_Database [{:?}]", catalog_id, db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+
+    let mut stmt = conn.prepare("SELECT checksum, files, fetched_at FROM catalogs_sources WHERE catalog_id = ?1")?;
+    let mut rows = stmt.query(params![catalog_id])?;
+    match rows.next()? {
+        Some(row) => {
+            let files: i64 = row.get(1)?;
+            Ok(Some(SourceChecksum { checksum: row.get(0)?, files: files as usize, fetched_at: row.get(2)? }))
+        },
+        None => Ok(None),
+    }
+}
+
+/// the last fetch wins
+pub fn save_source_checksum(config: &Database, catalog_id: &str, source: &SourceChecksum) -> Result<()> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Saving source checksum of catalog [{:?}] into Catalog_Database [{:?}]", catalog_id, db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO catalogs_sources (catalog_id, checksum, files, fetched_at) VALUES (?1, ?2, ?3, ?4)",
+        params![catalog_id, source.checksum, source.files as i64, source.fetched_at],
+    )?;
+
+    Ok(())
+}
+
+pub fn delete_source_checksum(config: &Database, catalog_id: &str) -> Result<usize> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!("Deleting source checksum of catalog [{:?}] from Catalog_Database [{:?}]", catalog_id, db_path);
+    }
+
+    let conn = Connection::open(db_path)?;
+    let deleted = conn.execute("DELETE FROM catalogs_sources WHERE catalog_id = ?1", params![catalog_id])?;
+
+    Ok(deleted)
+}
+
+fn to_catalog(row: &Row) -> Result<Catalog> {
+    let include_glob_pattern: String = row.get(4)?;
+    let scm_url: String = row.get(6)?;
+
+    Ok(Catalog {
+        catalog_id: row.get(0)?,
+        catalog_name: row.get(1)?,
+        catalog_path: row.get(2)?,
+        catalog_dir: row.get(3)?,
+        catalog_include_glob_pattern: serde_json::from_str(&include_glob_pattern).unwrap_or_default(),
+        catalog_http_base_uri: row.get(5)?,
+        catalog_scm_clone: !scm_url.is_empty(),
+        catalog_scm_url: scm_url,
+        catalog_scm_branch: row.get(7)?,
+        catalog_scm_depth: row.get(8)?,
+        catalog_scm_username: row.get(9)?,
+        catalog_scm_password_env: row.get(10)?,
+        catalog_watch: row.get(11)?,
+        catalog_source_type: row.get(12)?,
+        catalog_source_url: row.get(13)?,
+        catalog_ruleset_path: row.get(14)?,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::shared::settings::{Catalog, Database};
+
+    /// a database where the `catalogs` table has been created (and migrated)
+    pub fn get_database() -> Database {
+        let path = std::env::temp_dir().join(format!("apis-catalog-db-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+
+        let conn = rusqlite::Connection::open(path.join("apis-catalog-all.db")).unwrap();
+        conn.execute_batch(include_str!("../../../../migrations/2023-11-20-090000_catalogs/up.sql")).unwrap();
+        conn.execute_batch(include_str!("../../../../migrations/2023-11-27-090000_catalogs_sources/up.sql")).unwrap();
+        conn.execute_batch(include_str!("../../../../migrations/2023-12-11-090000_catalogs_rulesets/up.sql")).unwrap();
+        conn.execute_batch(include_str!("../../../../migrations/2023-12-21-090000_catalogs_sources_checksums/up.sql")).unwrap();
+
+        Database { rusqlite_path: path.into_os_string().into_string().unwrap() }
+    }
+
+    #[test]
+    fn test_add_update_and_delete_catalogs() {
+        let database = get_database();
+        let catalog = Catalog {
+            catalog_id: String::from("runtime-catalog"),
+            catalog_name: String::from("Runtime Catalog"),
+            catalog_path: String::from("/tmp/runtime-catalog"),
+            catalog_include_glob_pattern: vec![ String::from("**/*.yaml"), String::from("!**/tests/*") ],
+            catalog_scm_url: String::from("https://git.company.com/apis.git"),
+            catalog_scm_branch: String::from("main"),
+            catalog_scm_password: String::from("not stored"),
+            catalog_scm_password_env: String::from("APIS_GIT_TOKEN"),
+            catalog_watch: true,
+            ..Default::default()
+        };
+
+        super::add_catalog(&database, &catalog).unwrap();
+        assert!(super::add_catalog(&database, &catalog).is_err());
+
+        let catalogs = super::list_all_catalogs(&database).unwrap();
+        assert_eq!(catalogs.len(), 1);
+        assert_eq!(catalogs[0].catalog_include_glob_pattern, vec!["**/*.yaml", "!**/tests/*"]);
+        assert_eq!(catalogs[0].catalog_scm_branch, "main");
+        assert!(catalogs[0].catalog_scm_clone);
+        assert!(catalogs[0].catalog_watch);
+        assert_eq!(catalogs[0].catalog_scm_password, "");
+        assert_eq!(catalogs[0].catalog_scm_password_env, "APIS_GIT_TOKEN");
+
+        let mut updated = catalog.clone();
+        updated.catalog_name = String::from("Renamed");
+        updated.catalog_watch = false;
+        updated.catalog_source_type = String::from("archive");
+        updated.catalog_source_url = String::from("https://artifacts.company.com/apis.tar.gz");
+        assert_eq!(super::update_catalog(&database, &updated).unwrap(), 1);
+        let read = &super::list_all_catalogs(&database).unwrap()[0];
+        assert_eq!(read.catalog_name, "Renamed");
+        assert!(!read.catalog_watch);
+        assert_eq!(read.catalog_source_type, "archive");
+        assert_eq!(read.catalog_source_url, "https://artifacts.company.com/apis.tar.gz");
+        assert_eq!(read.catalog_ruleset_path, "");
+
+        assert_eq!(super::delete_catalog(&database, "runtime-catalog").unwrap(), 1);
+        assert_eq!(super::delete_catalog(&database, "runtime-catalog").unwrap(), 0);
+        assert_eq!(super::list_all_catalogs(&database).unwrap().len(), 0);
+
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+
+    #[test]
+    fn test_save_source_checksums() {
+        let database = get_database();
+        assert_eq!(super::get_source_checksum(&database, "archive-catalog").unwrap(), None);
+
+        let source = super::SourceChecksum { checksum: String::from("abc"), files: 2, fetched_at: chrono::Utc::now() };
+        super::save_source_checksum(&database, "archive-catalog", &source).unwrap();
+        let fetched = super::SourceChecksum { checksum: String::from("def"), ..source };
+        super::save_source_checksum(&database, "archive-catalog", &fetched).unwrap();
+        assert_eq!(super::get_source_checksum(&database, "archive-catalog").unwrap(), Some(fetched));
+
+        assert_eq!(super::delete_source_checksum(&database, "archive-catalog").unwrap(), 1);
+        assert_eq!(super::get_source_checksum(&database, "archive-catalog").unwrap(), None);
+
+        std::fs::remove_dir_all(&database.rusqlite_path).unwrap();
+    }
+}
@@ -238,7 +238,7 @@ pub fn get_metrics_endpoints_number(
 pub fn save_metrics_zally_ignore(
     config: &Database,
     datetime: DateTime<Utc>,
-    stats: std::collections::HashMap<i64, usize>,
+    stats: std::collections::HashMap<String, usize>,
 ) -> Result<()> {
     let mut db_path = String::from(&config.rusqlite_path);
     db_path.push_str("/apis-catalog-all.db");
@@ -287,14 +287,9 @@ pub fn save_metrics_endpoints_num_per_audience(
     Ok(())
 }
 
-#[derive(Debug)]
-pub struct I64BasedTimeSeries {
-    pub points: Vec<(DateTime<Utc>, std::collections::HashMap<i64, usize>)>,
-}
-
 pub fn get_metrics_zally_ignore(
     config: &Database,
-) -> Result<I64BasedTimeSeries> {
+) -> Result<StringBasedTimeSeries> {
     let mut db_path = String::from(&config.rusqlite_path);
     db_path.push_str("/apis-catalog-all.db");
     {
@@ -313,13 +308,20 @@ pub fn get_metrics_zally_ignore(
     while let Some(row) = rows.next()? {
         let time = row.get("date_time")?;
         let val: String = row.get("data_points")?;
+        //rules used to be stored as numbers, string ids (e.g. M010) being dropped
+        let data_points: std::collections::HashMap<serde_yaml::Value, usize> =
+            serde_yaml::from_str(val.as_str()).unwrap_or(std::collections::HashMap::new());
         points.push((
             time,
-            serde_yaml::from_str(val.as_str()).unwrap_or(std::collections::HashMap::new()),
+            data_points.into_iter().filter_map(|(rule, count)| match rule {
+                serde_yaml::Value::String(rule) => Some((rule, count)),
+                serde_yaml::Value::Number(rule) => Some((rule.to_string(), count)),
+                _ => None,
+            }).collect(),
         ));
     }
 
-    let timeseries = I64BasedTimeSeries { points: points };
+    let timeseries = StringBasedTimeSeries { points };
 
     Ok(timeseries)
 }
@@ -361,3 +363,127 @@ pub fn get_metrics_endpoints_per_audience(
 
     Ok(timeseries)
 }
+
+pub fn save_metrics_lint_violations(
+    config: &Database,
+    datetime: DateTime<Utc>,
+    stats: std::collections::HashMap<String, usize>,
+) -> Result<()> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!(
+            "Saving [metrics_lint_violations] metrics into Metrics_Database [{:?}]",
+            db_path
+        );
+    }
+
+    let conn = Connection::open(db_path)?;
+
+    let stats_as_yaml = serde_yaml::to_string(&stats)
+        .unwrap_or(String::from("Error: Unable to get yaml from stats"));
+    debug!("Saving stats {:?}", stats_as_yaml);
+    conn.execute(
+        "INSERT INTO metrics_lint_violations (date_time, data_points) VALUES (?1, ?2)",
+        params![datetime, stats_as_yaml],
+    )?;
+    Ok(())
+}
+
+pub fn get_metrics_lint_violations(
+    config: &Database,
+) -> Result<StringBasedTimeSeries> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!(
+            "Reading all [metrics_lint_violations] metrics from Metrics_Database [{:?}]",
+            db_path
+        );
+    }
+
+    let conn = Connection::open(db_path)?;
+    let mut stmt =
+        conn.prepare("SELECT date_time, data_points FROM metrics_lint_violations")?;
+    let mut rows = stmt.query(NO_PARAMS)?;
+
+    let mut points = Vec::new();
+    while let Some(row) = rows.next()? {
+        let time = row.get("date_time")?;
+        let val: String = row.get("data_points")?;
+        points.push((
+            time,
+            serde_yaml::from_str(val.as_str()).unwrap_or(std::collections::HashMap::new()),
+        ));
+    }
+
+    let timeseries = StringBasedTimeSeries { points };
+
+    Ok(timeseries)
+}
+
+/// `date_time` being the day of the snapshot, a new snapshot replaces the one of the day
+pub fn save_metrics_scorecards(
+    config: &Database,
+    datetime: DateTime<Utc>,
+    dimension: &str,
+    scores: std::collections::HashMap<String, f64>,
+) -> Result<()> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!(
+            "Saving [metrics_scorecards] metrics for [{:?}] into Metrics_Database [{:?}]",
+            dimension, db_path
+        );
+    }
+
+    let conn = Connection::open(db_path)?;
+
+    let scores_as_yaml = serde_yaml::to_string(&scores)
+        .unwrap_or(String::from("Error: Unable to get yaml from scores"));
+    debug!("Saving scores {:?}", scores_as_yaml);
+    conn.execute(
+        "INSERT OR REPLACE INTO metrics_scorecards (date_time, dimension, data_points) VALUES (?1, ?2, ?3)",
+        params![datetime, dimension, scores_as_yaml],
+    )?;
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct FloatBasedTimeSeries {
+    pub points: Vec<(DateTime<Utc>, std::collections::HashMap<String, f64>)>,
+}
+
+pub fn get_metrics_scorecards(
+    config: &Database,
+    dimension: &str,
+) -> Result<FloatBasedTimeSeries> {
+    let mut db_path = String::from(&config.rusqlite_path);
+    db_path.push_str("/apis-catalog-all.db");
+    {
+        debug!(
+            "Reading all [metrics_scorecards] metrics for [{:?}] from Metrics_Database [{:?}]",
+            dimension, db_path
+        );
+    }
+
+    let conn = Connection::open(db_path)?;
+    let mut stmt =
+        conn.prepare("SELECT date_time, data_points FROM metrics_scorecards WHERE dimension = ?1 ORDER BY date_time")?;
+    let mut rows = stmt.query(params![dimension])?;
+
+    let mut points = Vec::new();
+    while let Some(row) = rows.next()? {
+        let time = row.get("date_time")?;
+        let val: String = row.get("data_points")?;
+        points.push((
+            time,
+            serde_yaml::from_str(val.as_str()).unwrap_or(std::collections::HashMap::new()),
+        ));
+    }
+
+    let timeseries = FloatBasedTimeSeries { points };
+
+    Ok(timeseries)
+}
@@ -13,18 +13,12 @@ use tantivy::doc;
 use serde::{Serialize, Deserialize};
 use log::{debug, info, warn, error};
 
-pub fn build_index(index_path: &str, specs: &Vec<SpecItem>) -> tantivy::Result<()> {
-    info!("Building Index in folder [{}]", index_path);
-
-    let now = Instant::now();
-
-    if let Err(err) = fs::create_dir_all(&index_path) {
-        error!("Failed to create directory {:?} - {:?}", &index_path, err);
-    }
-
-    let index_path = Path::new(index_path);
-
+lazy_static! {
+    /// only one index writer at a time (refresh jobs, watched catalogs...)
+    static ref INDEX_WRITER_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+}
 
+fn get_schema() -> Schema {
     let mut schema_builder = Schema::builder();
     schema_builder.add_text_field("audience", TEXT | STORED);
     schema_builder.add_text_field("domain", TEXT | STORED);
@@ -37,16 +31,79 @@ pub fn build_index(index_path: &str, specs: &Vec<SpecItem>) -> tantivy::Result<(
     schema_builder.add_text_field("catalog_id", TEXT | STORED);
     schema_builder.add_text_field("spec_path", TEXT | STORED);
     schema_builder.add_text_field("version", TEXT | STORED);
+    //untokenized spec_path, to delete the documents of a spec
+    schema_builder.add_text_field("spec_file", STRING);
     //TODO schema_builder.add_text_field("system", TEXT);
-    let schema = schema_builder.build();
+    schema_builder.build()
+}
+
+fn open_index(index_path: &str) -> tantivy::Result<Index> {
+    if let Err(err) = fs::create_dir_all(index_path) {
+        error!("Failed to create directory {:?} - {:?}", &index_path, err);
+    }
 
+    let index_path = Path::new(index_path);
     let mmap_directory = MmapDirectory::open(index_path)?;
-    let index = Index::open_or_create(mmap_directory, schema.clone())?; // should use open_or_create to not overwrite existing index.
+    match Index::open_or_create(mmap_directory, get_schema()) {
+        Ok(index) => Ok(index),
+        Err(tantivy::TantivyError::SchemaError(why)) => {
+            //the index has been built by a previous version, it is rebuilt anyway
+            warn!("Index in folder [{:?}] does not match the current schema - [{}] - will be recreated", index_path, why);
+            fs::remove_dir_all(index_path)?;
+            fs::create_dir_all(index_path)?;
+            Index::open_or_create(MmapDirectory::open(index_path)?, get_schema())
+        },
+        Err(why) => Err(why),
+    }
+}
+
+pub fn build_index(index_path: &str, specs: &Vec<SpecItem>) -> tantivy::Result<()> {
+    info!("Building Index in folder [{}]", index_path);
+
+    let now = Instant::now();
+
+    let _lock = INDEX_WRITER_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let index = open_index(index_path)?; // should use open_or_create to not overwrite existing index.
     let mut index_writer = index.writer(100_000_000)?; //multi threaded behind the scene # of thread < 8
 
     index_writer.delete_all_documents()?;
     index_writer.commit()?;
 
+    //  will index all specs
+    for spec in specs {
+        add_spec(&index_writer, &index.schema(), spec);
+    }
+    index_writer.commit()?;
+    info!("Indexing Time [{}] milli seconds", now.elapsed().as_millis());
+
+    Ok(())
+}
+
+/// Removes the documents of the `removed_files` (spec file paths) then indexes `specs`.
+/// A spec which changed is expected to be in both.
+pub fn update_index(index_path: &str, removed_files: &Vec<String>, specs: &Vec<SpecItem>) -> tantivy::Result<()> {
+    info!("Updating Index in folder [{}] - [{:?}] removed files, [{:?}] specs", index_path, removed_files.len(), specs.len());
+
+    let now = Instant::now();
+
+    let _lock = INDEX_WRITER_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let index = open_index(index_path)?;
+    let mut index_writer = index.writer(50_000_000)?;
+
+    let spec_file = index.schema().get_field("spec_file").unwrap();
+    for removed_file in removed_files {
+        index_writer.delete_term(Term::from_field_text(spec_file, removed_file));
+    }
+    for spec in specs {
+        add_spec(&index_writer, &index.schema(), spec);
+    }
+    index_writer.commit()?;
+    info!("Indexing Time [{}] milli seconds", now.elapsed().as_millis());
+
+    Ok(())
+}
+
+fn add_spec(index_writer: &tantivy::IndexWriter, schema: &Schema, spec: &SpecItem) {
     let audience = schema.get_field("audience").unwrap();
     let domain = schema.get_field("domain").unwrap();
     let systems = schema.get_field("systems").unwrap();
@@ -58,45 +115,40 @@ pub fn build_index(index_path: &str, specs: &Vec<SpecItem>) -> tantivy::Result<(
     let catalog_id = schema.get_field("catalog_id").unwrap();
     let spec_path = schema.get_field("spec_path").unwrap();
     let spec_version = schema.get_field("version").unwrap();
+    let spec_file = schema.get_field("spec_file").unwrap();
 
-    //  will index all specs
-    for spec in specs {
-        let systems_as_text = &spec.get_systems().join(" ");
-        let paths = &spec.get_paths();
-        for path_item in paths.iter() {
-
-            let mut ope_summary = String::from("");
-            let mut ope_description = String::from("");
-            let mut ope_methods = String::from("");
-
-            for path_method in &path_item.methods{
-                ope_summary.push_str( path_method.summary.as_str() );
-                ope_summary.push_str( " " );
-                ope_description.push_str( path_method.description.as_str()  );
-                ope_description.push_str( " " );
-                ope_methods.push_str( path_method.method.as_str() );
-                ope_methods.push_str( " " );
-            }
-            //add the doc to the index 
-            index_writer.add_document(doc!(
-                audience => String::from( &spec.get_audience() ),
-                domain => String::from( spec.get_domain() ), 
-                systems => String::from(systems_as_text),
-                layer => String::from( &spec.get_layer() ),
-                path => String::from(&path_item.path),
-                operations => ope_methods,
-                summary => ope_summary,
-                description => ope_description,
-                catalog_id => String::from(spec.get_catalog_id()), 
-                spec_path => String::from(spec.get_file_path()),
-                spec_version => String::from(spec.get_version()),
-            )).ok();
+    let systems_as_text = &spec.get_systems().join(" ");
+    let paths = &spec.get_paths();
+    for path_item in paths.iter() {
+
+        let mut ope_summary = String::from("");
+        let mut ope_description = String::from("");
+        let mut ope_methods = String::from("");
+
+        for path_method in &path_item.methods{
+            ope_summary.push_str( path_method.summary.as_str() );
+            ope_summary.push_str( " " );
+            ope_description.push_str( path_method.description.as_str()  );
+            ope_description.push_str( " " );
+            ope_methods.push_str( path_method.method.as_str() );
+            ope_methods.push_str( " " );
         }
+        //add the doc to the index 
+        index_writer.add_document(doc!(
+            audience => String::from( &spec.get_audience() ),
+            domain => String::from( spec.get_domain() ), 
+            systems => String::from(systems_as_text),
+            layer => String::from( &spec.get_layer() ),
+            path => String::from(&path_item.path),
+            operations => ope_methods,
+            summary => ope_summary,
+            description => ope_description,
+            catalog_id => String::from(spec.get_catalog_id()), 
+            spec_path => String::from(spec.get_file_path()),
+            spec_version => String::from(spec.get_version()),
+            spec_file => String::from(spec.get_file_path()),
+        )).ok();
     }
-    index_writer.commit()?;
-    info!("Indexing Time [{}] milli seconds", now.elapsed().as_millis());
-
-    Ok(())
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -120,20 +172,7 @@ pub fn search(index_path: &str, query_as_string: String, limit: usize) -> tantiv
     //println!("file exist {}", Index::exists(&mmap_directory) );
     let index = Index::open(mmap_directory)?;
     //
-    let mut schema_builder = Schema::builder();
-    schema_builder.add_text_field("audience", TEXT | STORED);
-    schema_builder.add_text_field("domain", TEXT | STORED);
-    schema_builder.add_text_field("systems", TEXT | STORED);
-    schema_builder.add_text_field("layer", TEXT | STORED);
-    schema_builder.add_text_field("path", TEXT | STORED);
-    schema_builder.add_text_field("operations", TEXT | STORED);
-    schema_builder.add_text_field("summary", TEXT);
-    schema_builder.add_text_field("description", TEXT);
-    schema_builder.add_text_field("catalog_id", TEXT | STORED);
-    schema_builder.add_text_field("spec_path", TEXT | STORED);
-    schema_builder.add_text_field("version", TEXT | STORED);
-    
-    let schema = schema_builder.build();
+    let schema = get_schema();
 
     let audience = schema.get_field("audience").unwrap();
     let domain = schema.get_field("domain").unwrap();
@@ -199,4 +238,23 @@ mod tests {
         assert_eq!(returned_value.len(), 0);
 
     }
+
+    #[test]
+    fn test_update_index() {
+        let index_path = env::temp_dir().join(format!("apis-catalog-test-{}", uuid::Uuid::new_v4()));
+        let index_path = index_path.to_str().unwrap();
+
+        let specs = crate::app::dao::catalog::tests::get_mocked_specs();
+        let _ = super::build_index(index_path, &specs);
+        assert_eq!(super::search(index_path, String::from("resource"), 10).unwrap().len(), 1);
+
+        let removed_files = specs.iter().map(|spec| String::from(spec.get_file_path())).collect();
+        super::update_index(index_path, &removed_files, &Vec::new()).unwrap();
+        assert_eq!(super::search(index_path, String::from("resource"), 10).unwrap().len(), 0);
+
+        super::update_index(index_path, &Vec::new(), &specs).unwrap();
+        assert_eq!(super::search(index_path, String::from("resource"), 10).unwrap().len(), 1);
+
+        std::fs::remove_dir_all(index_path).unwrap();
+    }
 }
\ No newline at end of file
@@ -80,7 +80,7 @@ pub async fn get_all_domains_for_all_catalogs() -> impl Responder {
 
     let mut domains = HashSet::new();
 
-    let all_specs = catalog::list_specs(&SETTINGS.catalogs);
+    let all_specs = catalog::list_specs(&crate::app::catalogs::get_all_catalogs());
     //loop over the list and check system and layer equality
     for spec in all_specs{
         domains.insert(String::from( spec.get_domain() ));
@@ -96,7 +96,7 @@ pub async fn get_domains_errors() -> impl Responder {
     info!("get domains errors");
 
     //get all specs
-    let all_specs: Vec<SpecItem> = list_specs(&SETTINGS.catalogs);
+    let all_specs: Vec<SpecItem> = list_specs(&crate::app::catalogs::get_all_catalogs());
     //at this stage data = {"N/A - servers not specified": 11, "/v1/settlement/operational-arrangement": 8, "/v1/market-risk/scenarios": 10,....
 
     let data: std::collections::HashMap<String, usize> = get_endpoints_num_per_subdomain(&all_specs);
@@ -118,35 +118,7 @@ pub async fn get_domains_errors() -> impl Responder {
     for spec in &all_specs {
         let short_path = SpecItem::get_spec_short_path( &spec);
         let spec_domain = spec.get_domain();
-        //will loop over all_domains to check if domains "match or not". contains() cannot work as the yml contains /v1 and not the domain
-        let mut is_contained = false;
-        for domain in &all_domains {
-            match spec_domain.contains(domain.as_str()) {
-                true => {
-                    // info!(
-                    //     "[{}] is contained - [{}] resources - spec [{}]",
-                    //     &spec.domain,
-                    //     data.get(&spec.domain).unwrap(),
-                    //     short_path
-                    // );
-                    is_contained = true;
-                    break;
-                }
-                false => {
-                    // info!(
-                    //     "[{}] is *not* contained - [{}] resources - spec [{}]",
-                    //     &spec.domain,
-                    //     data.get(&spec.domain).unwrap(),
-                    //     short_path
-                    // );
-                    is_contained = false;
-                }
-            }
-            debug!(
-                "Matching [{}] with [{}] - is_contained [{}]",
-                spec_domain, domain, is_contained
-            );
-        }
+        let is_contained = is_declared_domain(&spec_domain, &all_domains);
 
         if !is_contained {
             let error = DomainError {
@@ -166,11 +138,20 @@ pub async fn get_domains_errors() -> impl Responder {
     HttpResponse::Ok().json(errors)
 }
 
+/// the domain of a spec is declared if it contains one of the declared (and official) domains - contains() cannot
+/// work as the yml contains /v1 and not the domain
+pub fn is_declared_domain(spec_domain: &str, all_domains: &[String]) -> bool {
+    let is_contained = all_domains.iter().any(|domain| spec_domain.contains(domain.as_str()));
+    debug!("Matching [{}] with [{:?}] - is_contained [{}]", spec_domain, all_domains, is_contained);
+
+    is_contained
+}
+
 #[get("/v1/domains/stats")]
 pub async fn get_domains_stats() -> impl Responder {
     info!("get domains stats");
 
-    let all_specs: Vec<SpecItem> = list_specs(&SETTINGS.catalogs);
+    let all_specs: Vec<SpecItem> = list_specs(&crate::app::catalogs::get_all_catalogs());
 
     let data: std::collections::HashMap<String, usize> = get_endpoints_num_per_subdomain(&all_specs);
 
@@ -243,6 +224,52 @@ pub async fn get_domains_stats() -> impl Responder {
     HttpResponse::Ok().json(response_as_vec)
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DomainEvolution {
+    pub domain: String,
+    pub specs: usize,
+    pub changes: usize,
+    pub version_bumps: usize,
+    pub first_change: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_version_bump: Option<chrono::DateTime<chrono::Utc>>,
+    pub version_bumps_per_month: f64,
+}
+
+/// how frequent the specs of each domain evolve, based on the git history of the catalogs
+#[get("/v1/domains/evolutions")]
+pub async fn get_domains_evolutions() -> impl Responder {
+    info!("get domains evolutions");
+
+    let mut specs = Vec::new();
+    for catalog in &crate::app::catalogs::get_all_catalogs() {
+        let history = match catalog::history::get_history(catalog) {
+            Ok(history) => history,
+            Err(why) => {
+                warn!("{} - its specs are not part of the evolutions", why);
+                continue;
+            }
+        };
+
+        for spec in catalog::list_catalog_specs(catalog) {
+            specs.push((spec.get_domain(), catalog::history::get_revisions(&history, catalog, spec.get_file_path())));
+        }
+    }
+
+    let evolutions: Vec<DomainEvolution> = catalog::history::get_evolutions_per_domain(&specs, chrono::Utc::now()).into_iter()
+        .map(|evolution| DomainEvolution {
+            domain: evolution.domain,
+            specs: evolution.specs,
+            changes: evolution.changes,
+            version_bumps: evolution.version_bumps,
+            first_change: evolution.first_change,
+            last_version_bump: evolution.last_version_bump,
+            version_bumps_per_month: evolution.version_bumps_per_month,
+        })
+        .collect();
+
+    HttpResponse::Ok().json(evolutions)
+}
+
 #[get("/v1/domains")]
 pub async fn get_domains() -> impl Responder {
     info!("get domains");
@@ -257,7 +284,7 @@ pub async fn get_domains() -> impl Responder {
         };
 
 
-    let all_specs: Vec<SpecItem> = list_specs(&SETTINGS.catalogs);
+    let all_specs: Vec<SpecItem> = list_specs(&crate::app::catalogs::get_all_catalogs());
     let non_emtpy_domains: std::collections::HashMap<String, usize> = get_endpoints_num_per_subdomain(&all_specs);
 
     //TODO : crappy!! find a way to better handle the /v1 - maybe relying on servers attr in OAI is not the right way and having dedicated OAI attributes would be easier / proper (alos taking into sonsiderations code generation plugins)
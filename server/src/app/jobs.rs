@@ -0,0 +1,243 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use log::{debug, info};
+
+/// finished jobs kept in memory, the oldest ones are forgotten
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A long running task (e.g. the refresh of the catalogs), run in the background
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Job {
+    pub id: String,
+    pub kind: String,
+    pub state: JobState,
+    /// what the job is currently doing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u128>,
+    pub catalogs: Vec<CatalogProgress>,
+    pub specs: usize,
+    pub errors: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CatalogProgress {
+    pub catalog_id: String,
+    pub state: JobState,
+    pub specs: usize,
+    pub errors: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Job {
+    pub fn is_finished(&self) -> bool {
+        self.state == JobState::Succeeded || self.state == JobState::Failed
+    }
+
+    /// why the job failed or, if it succeeded, why some of its catalogs failed
+    pub fn get_error(&self) -> Option<String> {
+        if self.state == JobState::Failed {
+            return Some(self.error.clone().unwrap_or_else(|| String::from("unknown error")));
+        }
+
+        let failed: Vec<String> = self.catalogs.iter()
+            .filter_map(|progress| progress.error.as_ref().map(|why| format!("{} - {}", progress.catalog_id, why)))
+            .collect();
+        match failed.is_empty() {
+            true => None,
+            false => Some(format!("Unable to refresh catalogs [{}]", failed.join(", "))),
+        }
+    }
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<HashMap<String, Job>> = Mutex::new(HashMap::new());
+}
+
+#[get("/v1/jobs/{id}")]
+pub async fn get_job_by_id(path: web::Path<String>) -> impl Responder {
+    let id: String = path.into_inner();
+    info!("get job for id [{:?}]", &id);
+
+    match get_job(&id) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+pub fn get_job(id: &str) -> Option<Job> {
+    JOBS.lock().unwrap().get(id).cloned()
+}
+
+/// Creates a pending job, unless a job of the same kind is not finished yet - it is then returned (with false)
+pub fn create_job(kind: &str, catalog_ids: Vec<String>) -> (Job, bool) {
+    create_exclusive_job(kind, catalog_ids, |other| other == kind)
+}
+
+/// Creates a pending job, unless a job whose kind is `excluded` is not finished yet - it is then returned (with false)
+pub fn create_exclusive_job<F>(kind: &str, catalog_ids: Vec<String>, excluded: F) -> (Job, bool)
+where
+    F: Fn(&str) -> bool,
+{
+    let mut jobs = JOBS.lock().unwrap();
+    if let Some(job) = jobs.values().find(|job| excluded(&job.kind) && !job.is_finished()) {
+        return (job.clone(), false);
+    }
+
+    let job = Job {
+        id: format!("{}", uuid::Uuid::new_v4()),
+        kind: String::from(kind),
+        state: JobState::Pending,
+        step: None,
+        created_at: Utc::now(),
+        started_at: None,
+        ended_at: None,
+        duration_ms: None,
+        catalogs: catalog_ids.into_iter().map(|catalog_id| CatalogProgress {
+            catalog_id,
+            state: JobState::Pending,
+            specs: 0,
+            errors: 0,
+            duration_ms: None,
+            error: None,
+        }).collect(),
+        specs: 0,
+        errors: 0,
+        error: None,
+    };
+    info!("Created job [{:?}] - [{:?}]", job.id, job.kind);
+    jobs.insert(String::from(&job.id), job.clone());
+
+    //forget the oldest finished jobs
+    let mut finished: Vec<(DateTime<Utc>, String)> = jobs.values().filter(|job| job.is_finished()).map(|job| (job.created_at, String::from(&job.id))).collect();
+    if finished.len() > MAX_FINISHED_JOBS {
+        finished.sort();
+        for (_created_at, id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+            jobs.remove(id);
+        }
+    }
+
+    (job, true)
+}
+
+pub fn update_job<F>(id: &str, update: F)
+where
+    F: FnOnce(&mut Job),
+{
+    match JOBS.lock().unwrap().get_mut(id) {
+        Some(job) => {
+            update(job);
+            debug!("Job [{:?}] is [{:?}] - step [{:?}]", job.id, job.state, job.step);
+        },
+        None => debug!("Job [{:?}] is unknown", id),
+    }
+}
+
+pub fn update_catalog_progress<F>(id: &str, catalog_id: &str, update: F)
+where
+    F: FnOnce(&mut CatalogProgress),
+{
+    update_job(id, |job| {
+        if let Some(progress) = job.catalogs.iter_mut().find(|progress| progress.catalog_id == catalog_id) {
+            update(progress);
+        }
+    });
+}
+
+/// message of a panic caught while running a job
+pub fn get_panic_message(why: &Box<dyn std::any::Any + Send>) -> String {
+    match (why.downcast_ref::<&str>(), why.downcast_ref::<String>()) {
+        (Some(reason), _) => reason.to_string(),
+        (_, Some(reason)) => reason.to_string(),
+        _ => String::from("unknown error"),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::JobState;
+
+    #[test]
+    fn test_jobs_of_the_same_kind_are_coalesced() {
+        let (job, created) = super::create_job("test_coalesce", vec![String::from("catalog-1")]);
+        assert!(created);
+        assert_eq!(job.state, JobState::Pending);
+        assert_eq!(job.catalogs[0].catalog_id, "catalog-1");
+
+        //the pending job is returned
+        let (existing, created) = super::create_job("test_coalesce", Vec::new());
+        assert!(!created);
+        assert_eq!(existing.id, job.id);
+
+        super::update_job(&job.id, |job| job.state = JobState::Running);
+        super::update_catalog_progress(&job.id, "catalog-1", |progress| progress.specs = 3);
+        let running = super::get_job(&job.id).unwrap();
+        assert_eq!(running.catalogs[0].specs, 3);
+        assert_eq!(super::create_job("test_coalesce", Vec::new()).0.id, job.id);
+
+        super::update_job(&job.id, |job| job.state = JobState::Succeeded);
+        let (next, created) = super::create_job("test_coalesce", Vec::new());
+        assert!(created);
+        assert_ne!(next.id, job.id);
+        assert!(super::get_job(&job.id).unwrap().is_finished());
+        assert!(super::get_job("unknown").is_none());
+    }
+
+    #[test]
+    fn test_exclusive_jobs() {
+        let excluded = |kind: &str| kind.starts_with("test_exclusive");
+        let (job, created) = super::create_exclusive_job("test_exclusive_all", Vec::new(), excluded);
+        assert!(created);
+
+        //a job of another kind is not created while the first one is not finished
+        let (existing, created) = super::create_exclusive_job("test_exclusive_one", Vec::new(), excluded);
+        assert!(!created);
+        assert_eq!(existing.id, job.id);
+        assert!(super::create_job("test_other", Vec::new()).1);
+
+        super::update_job(&job.id, |job| job.state = JobState::Failed);
+        let (next, created) = super::create_exclusive_job("test_exclusive_one", Vec::new(), excluded);
+        assert!(created);
+        assert_eq!(next.kind, "test_exclusive_one");
+    }
+
+    #[test]
+    fn test_get_error() {
+        let (job, _created) = super::create_job("test_get_error", vec![String::from("catalog-1"), String::from("catalog-2")]);
+        super::update_job(&job.id, |job| job.state = JobState::Succeeded);
+        assert_eq!(super::get_job(&job.id).unwrap().get_error(), None);
+
+        super::update_catalog_progress(&job.id, "catalog-2", |progress| progress.error = Some(String::from("unable to fetch")));
+        assert_eq!(super::get_job(&job.id).unwrap().get_error(), Some(String::from("Unable to refresh catalogs [catalog-2 - unable to fetch]")));
+
+        super::update_job(&job.id, |job| {
+            job.state = JobState::Failed;
+            job.error = Some(String::from("unable to index"));
+        });
+        assert_eq!(super::get_job(&job.id).unwrap().get_error(), Some(String::from("unable to index")));
+    }
+}
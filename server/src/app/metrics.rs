@@ -27,8 +27,9 @@ pub struct Metrics {
     pub pr_num: Vec<(DateTime<Utc>, i32)>,
     pub pr_ages: Vec<(DateTime<Utc>, i64, i64, i64, i64)>,
     pub endpoints_num: Vec<(DateTime<Utc>, i32)>, //Vec<(DateTime<Utc>, Option<String>, Option<String>, i32)>,
-    pub zally_violations: Vec<(DateTime<Utc>, std::collections::HashMap<i64, usize>)>,
+    pub zally_violations: Vec<(DateTime<Utc>, std::collections::HashMap<String, usize>)>,
     pub endpoints_num_per_audience: Vec<(DateTime<Utc>, std::collections::HashMap<String, usize>)>,
+    pub lint_violations: Vec<(DateTime<Utc>, std::collections::HashMap<String, usize>)>,
 }
 
 #[get("/v1/metrics")]
@@ -68,7 +69,7 @@ pub async fn get_all_metrics() -> impl Responder {
             }
         };
 
-    let zally_ignore_timeseries: Vec<(DateTime<Utc>, std::collections::HashMap<i64, usize>)> =
+    let zally_ignore_timeseries: Vec<(DateTime<Utc>, std::collections::HashMap<String, usize>)> =
         match crate::app::dao::repo_metrics::get_metrics_zally_ignore(&SETTINGS.database) {
             Ok(val) => val.points,
             Err(why) => {
@@ -88,6 +89,15 @@ pub async fn get_all_metrics() -> impl Responder {
             }
         };
 
+    let lint_violations: Vec<(DateTime<Utc>, std::collections::HashMap<String, usize>)> =
+        match crate::app::dao::repo_metrics::get_metrics_lint_violations(&SETTINGS.database) {
+            Ok(val) => val.points,
+            Err(why) => {
+                error!("Error while getting get_metrics_lint_violations [{}]", why);
+                Vec::new()
+            }
+        };
+
     //will combine PR informations with metrics
     let merged_prs: Vec<PullRequest> = get_pull_requests("MERGED").await.values;
     let merged_prs: Vec<(DateTime<Utc>, PullRequest)> = merged_prs
@@ -134,6 +144,7 @@ pub async fn get_all_metrics() -> impl Responder {
         endpoints_num: endpoints_number,
         endpoints_num_per_audience: endpoints_audience_number,
         zally_violations: zally_ignore_timeseries,
+        lint_violations,
     };
 
     HttpResponse::Ok().json(metrics)
@@ -142,7 +153,13 @@ pub async fn get_all_metrics() -> impl Responder {
 #[post("/v1/metrics/refresh")]
 pub async fn refresh_metrics() -> impl Responder {
     info!("refresh metrics");
-    //
+    let pull_requests_size = snapshot_metrics().await;
+
+    HttpResponse::Ok().json(pull_requests_size)
+}
+
+/// Computes and saves the current value of every metric. Returns the number of open pull requests
+pub async fn snapshot_metrics() -> i32 {
     let pull_requests: PullRequests = get_pull_requests("OPEN").await;
 
     //keep metric pr_num
@@ -164,7 +181,7 @@ pub async fn refresh_metrics() -> impl Responder {
     .unwrap();
 
     //get # of endpoints
-    let all_specs: Vec<SpecItem> = list_specs(&SETTINGS.catalogs);
+    let all_specs: Vec<SpecItem> = list_specs(&crate::app::catalogs::get_all_catalogs());
 
     let all_specs_paths: Vec<String> = all_specs.iter().map(|val| val.get_file_path().to_string()).collect();
     info!(
@@ -193,8 +210,15 @@ pub async fn refresh_metrics() -> impl Responder {
         stats,
     )
     .unwrap();
+
+    //save metrics lint violations per rule
+    let stats = crate::app::dao::catalog::lint::get_violations_per_rule(&all_specs, &SETTINGS.lint);
+    crate::app::dao::repo_metrics::save_metrics_lint_violations(&SETTINGS.database, Utc::now(), stats).unwrap();
+
+    //save the scorecards of the day
+    crate::app::scorecards::snapshot_scorecards(&all_specs);
     //
-    HttpResponse::Ok().json(pull_requests.size)
+    pull_requests.size
 }
 
 fn get_metrics_pull_requests_number(pull_requests: &PullRequests) -> (DateTime<Utc>, i32) {
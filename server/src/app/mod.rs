@@ -3,10 +3,13 @@ pub mod deployments;
 pub mod domains;
 pub mod envs;
 pub mod metrics;
+pub mod scorecards;
 pub mod tiers;
 pub mod systems;
 pub mod catalogs;
 pub mod search;
 pub mod config;
+pub mod scheduler;
+pub mod jobs;
 
 pub mod dao;
\ No newline at end of file
@@ -0,0 +1,309 @@
+use actix_web::{get, HttpResponse, Responder};
+use chrono::{DateTime, Duration, Local, NaiveTime, Utc};
+use clokwerk::{Interval, Job, NextTime, ScheduleHandle, Scheduler, TimeUnits};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::shared::settings::SETTINGS;
+
+use log::{info, warn, error};
+
+/// When a background job runs, parsed from the `[scheduler]` settings
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    /// `every 15m`, `every 2 hours`...
+    Every(Interval),
+    /// `daily at 02:00`
+    DailyAt(NaiveTime),
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        lazy_static! {
+            static ref EVERY: Regex = Regex::new(r"^every\s+(\d+)\s*(s|secs?|seconds?|m|mins?|minutes?|h|hours?|d|days?)$").unwrap();
+            static ref DAILY_AT: Regex = Regex::new(r"^daily\s+at\s+(\d{1,2}:\d{2})$").unwrap();
+        }
+
+        let expression = expression.trim().to_lowercase();
+        if let Some(cap) = EVERY.captures(&expression) {
+            let value: u32 = cap[1].parse().map_err(|why| format!("Unable to parse schedule [{}] - {}", expression, why))?;
+            if value == 0 {
+                return Err( format!("Unable to parse schedule [{}] - the interval must be greater than 0", expression) );
+            }
+
+            let interval = match &cap[2][..1] {
+                "s" => value.seconds(),
+                "m" => value.minutes(),
+                "h" => value.hours(),
+                _ => value.days(),
+            };
+            return Ok(Schedule::Every(interval));
+        }
+
+        if let Some(cap) = DAILY_AT.captures(&expression) {
+            return match NaiveTime::parse_from_str(&cap[1], "%H:%M") {
+                Ok(time) => Ok(Schedule::DailyAt(time)),
+                Err(why) => Err( format!("Unable to parse schedule [{}] - {}", expression, why) ),
+            };
+        }
+
+        Err( format!("Unable to parse schedule [{}] - expected `every <n><s|m|h|d>` or `daily at <HH:MM>`", expression) )
+    }
+
+    pub fn next_run(&self, from: &DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Schedule::Every(interval) => interval.next(from),
+            Schedule::DailyAt(time) => {
+                let today = from.date_naive().and_time(*time).and_local_timezone(Local).earliest().unwrap_or(*from);
+                match today > *from {
+                    true => today,
+                    false => today + Duration::days(1),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobStatus {
+    pub name: String,
+    pub schedule: String,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_start: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_end: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_duration_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    /// runs skipped because the previous one was still running
+    pub skipped_runs: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Jobs {
+    pub jobs: Vec<JobStatus>,
+}
+
+/// the error of the run, if any, is kept as the last error of the job
+type JobFn = fn() -> Result<(), String>;
+
+struct ScheduledJob {
+    schedule: Schedule,
+    status: JobStatus,
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<Vec<ScheduledJob>> = Mutex::new(Vec::new());
+    /// the scheduler thread stops as soon as the handle is dropped
+    static ref HANDLE: Mutex<Option<ScheduleHandle>> = Mutex::new(None);
+}
+
+#[get("/v1/scheduler")]
+pub async fn get_scheduler_status() -> impl Responder {
+    info!("get scheduler status");
+
+    HttpResponse::Ok().json(Jobs { jobs: get_jobs_status() })
+}
+
+pub fn get_jobs_status() -> Vec<JobStatus> {
+    let now = Local::now();
+
+    JOBS.lock().unwrap().iter().map(|job| {
+        let mut status = job.status.clone();
+        status.next_run = Some(job.schedule.next_run(&now).with_timezone(&Utc));
+        status
+    }).collect()
+}
+
+/// Schedules catalog pulls (and re-indexing) and metrics snapshots as configured in the `[scheduler]` settings
+pub fn start() {
+    let mut scheduler = Scheduler::new();
+
+    let jobs: [(&'static str, &String, JobFn); 2] = [
+        ("catalogs_refresh", &SETTINGS.scheduler.catalogs_refresh, || {
+            //coalesced with the refreshes requested through the API
+            match crate::app::catalogs::create_refresh_job() {
+                (job, true) => crate::app::catalogs::run_refresh_job(&job.id),
+                (_job, false) => Ok(()),
+            }
+        }),
+        ("metrics_snapshot", &SETTINGS.scheduler.metrics_snapshot, || {
+            //metrics are computed through async calls
+            let pull_requests_size = actix_web::rt::System::new().block_on(crate::app::metrics::snapshot_metrics());
+            info!("Metrics snapshot done - got [{:?}] open pull requests", pull_requests_size);
+            Ok(())
+        }),
+    ];
+
+    for (name, expression, job) in jobs {
+        if expression.trim().is_empty() {
+            info!("Job [{:?}] is not scheduled", name);
+            continue;
+        }
+
+        match register(name, expression) {
+            Ok(Schedule::Every(interval)) => {
+                scheduler.every(interval).run(move || trigger(name, job));
+            },
+            Ok(Schedule::DailyAt(time)) => {
+                scheduler.every(1.day()).at_time(time).run(move || trigger(name, job));
+            },
+            Err(why) => error!("Job [{:?}] will not be scheduled - {}", name, why),
+        };
+    }
+
+    *HANDLE.lock().unwrap() = Some(scheduler.watch_thread(std::time::Duration::from_secs(1)));
+}
+
+fn register(name: &str, expression: &str) -> Result<Schedule, String> {
+    let schedule = Schedule::parse(expression)?;
+    info!("Job [{:?}] is scheduled [{:?}]", name, expression);
+
+    let mut jobs = JOBS.lock().unwrap();
+    jobs.retain(|job| job.status.name != name);
+    jobs.push(ScheduledJob {
+        schedule: schedule.clone(),
+        status: JobStatus {
+            name: String::from(name),
+            schedule: String::from(expression),
+            running: false,
+            last_run_start: None,
+            last_run_end: None,
+            last_run_duration_ms: None,
+            last_error: None,
+            next_run: None,
+            skipped_runs: 0,
+        },
+    });
+
+    Ok(schedule)
+}
+
+/// Runs the job in its own thread, unless its previous run is not over
+fn trigger(name: &'static str, job: JobFn) {
+    {
+        let mut jobs = JOBS.lock().unwrap();
+        let status = match jobs.iter_mut().find(|job| job.status.name == name) {
+            Some(job) => &mut job.status,
+            None => {
+                error!("Job [{:?}] is not registered", name);
+                return;
+            }
+        };
+
+        if status.running {
+            warn!("Job [{:?}] is still running since [{:?}] - skip this run", name, status.last_run_start);
+            status.skipped_runs += 1;
+            return;
+        }
+        status.running = true;
+        status.last_run_start = Some(Utc::now());
+    }
+
+    std::thread::spawn(move || {
+        info!("Job [{:?}] is starting", name);
+        let now = std::time::Instant::now();
+        let result = std::panic::catch_unwind(job);
+
+        let mut jobs = JOBS.lock().unwrap();
+        if let Some(scheduled) = jobs.iter_mut().find(|job| job.status.name == name) {
+            let status = &mut scheduled.status;
+            status.running = false;
+            status.last_run_end = Some(Utc::now());
+            status.last_run_duration_ms = Some(now.elapsed().as_millis());
+            status.last_error = match result {
+                Ok(Ok(())) => None,
+                Ok(Err(why)) => {
+                    error!("Job [{:?}] failed - [{}]", name, why);
+                    Some(why)
+                },
+                Err(why) => {
+                    let reason = crate::app::jobs::get_panic_message(&why);
+                    error!("Job [{:?}] failed - [{}]", name, reason);
+                    Some(reason)
+                }
+            };
+        }
+        info!("Job [{:?}] is done in [{}] milli seconds", name, now.elapsed().as_millis());
+    });
+}
+
+#[cfg(test)]
+pub mod tests {
+    use chrono::{Local, NaiveTime, TimeZone, Timelike};
+    use clokwerk::TimeUnits;
+    use super::Schedule;
+
+    #[test]
+    fn test_parse_schedule() {
+        assert_eq!(Schedule::parse("every 15m").unwrap(), Schedule::Every(15.minutes()));
+        assert_eq!(Schedule::parse(" Every 2 hours ").unwrap(), Schedule::Every(2.hours()));
+        assert_eq!(Schedule::parse("every 30s").unwrap(), Schedule::Every(30.seconds()));
+        assert_eq!(Schedule::parse("every 1 day").unwrap(), Schedule::Every(1.days()));
+        assert_eq!(Schedule::parse("daily at 02:00").unwrap(), Schedule::DailyAt(NaiveTime::from_hms_opt(2, 0, 0).unwrap()));
+
+        assert!(Schedule::parse("every 0m").is_err());
+        assert!(Schedule::parse("daily at 25:00").is_err());
+        assert!(Schedule::parse("weekly").unwrap_err().contains("expected"));
+    }
+
+    #[test]
+    fn test_next_run() {
+        let now = Local.with_ymd_and_hms(2023, 10, 1, 10, 20, 0).unwrap();
+
+        let next_run = Schedule::parse("every 15m").unwrap().next_run(&now);
+        assert_eq!((next_run.hour(), next_run.minute()), (10, 30));
+
+        let next_run = Schedule::parse("daily at 11:00").unwrap().next_run(&now);
+        assert_eq!(next_run, Local.with_ymd_and_hms(2023, 10, 1, 11, 0, 0).unwrap());
+
+        let next_run = Schedule::parse("daily at 02:00").unwrap().next_run(&now);
+        assert_eq!(next_run, Local.with_ymd_and_hms(2023, 10, 2, 2, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_trigger_skips_overlapping_runs() {
+        fn slow_job() -> Result<(), String> {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            Ok(())
+        }
+        fn failing_job() -> Result<(), String> {
+            Err(String::from("unable to pull the catalogs"))
+        }
+        fn panicking_job() -> Result<(), String> {
+            panic!("unable to reach the server");
+        }
+        let get_status = |name: &str| super::get_jobs_status().into_iter().find(|job| job.name == name).unwrap();
+
+        super::register("test_slow_job", "every 1h").unwrap();
+        super::trigger("test_slow_job", slow_job);
+        super::trigger("test_slow_job", slow_job);
+        let status = get_status("test_slow_job");
+        assert!(status.running);
+        assert_eq!(status.skipped_runs, 1);
+        assert!(status.next_run.is_some());
+
+        std::thread::sleep(std::time::Duration::from_millis(600));
+        let status = get_status("test_slow_job");
+        assert!(!status.running);
+        assert!(status.last_run_duration_ms.unwrap() >= 300);
+        assert!(status.last_error.is_none());
+
+        super::register("test_failing_job", "daily at 02:00").unwrap();
+        super::trigger("test_failing_job", failing_job);
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let status = get_status("test_failing_job");
+        assert!(!status.running);
+        assert_eq!(status.last_error, Some(String::from("unable to pull the catalogs")));
+
+        super::register("test_panicking_job", "daily at 02:00").unwrap();
+        super::trigger("test_panicking_job", panicking_job);
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert_eq!(get_status("test_panicking_job").last_error, Some(String::from("unable to reach the server")));
+    }
+}
@@ -0,0 +1,409 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::app::dao::catalog::spec::SpecItem;
+use crate::app::dao::catalog::{ErrorCategory, SpecInError, ZallyIgnore};
+use crate::shared::settings::*;
+
+use log::{debug, error, info, warn};
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/*
+ * Governance scorecards related APIs
+ */
+
+/// What a spec loses points on
+pub struct Criterion {
+    pub id: &'static str,
+    /// points lost per finding, unless configured in `[scorecard.weights]`
+    pub weight: f64,
+    pub description: &'static str,
+}
+
+pub const CRITERIA: [Criterion; 6] = [
+    Criterion { id: "lint-violation", weight: 2.0, description: "per violation of the lint rules or of the ruleset of the catalog" },
+    Criterion { id: "zally-ignore", weight: 1.0, description: "per rule suppressed by x-zally-ignore" },
+    Criterion { id: "missing-api-id", weight: 10.0, description: "info does not declare x-api-id" },
+    Criterion { id: "missing-description", weight: 5.0, description: "info does not declare a description" },
+    Criterion { id: "unknown-domain", weight: 10.0, description: "the domain of the spec is not declared" },
+    Criterion { id: "parse-error", weight: 20.0, description: "per parse error, e.g. an unresolved $ref" },
+];
+
+pub const MAX_SCORE: f64 = 100.0;
+/// what the snapshots are taken on
+pub const DIMENSIONS: [&str; 6] = ["all", "spec", "catalog", "domain", "system", "layer"];
+
+pub fn get_weight(criterion: &Criterion, settings: &Scorecard) -> f64 {
+    settings.weights.get(criterion.id).copied().unwrap_or(criterion.weight)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpecScorecard {
+    pub spec_path: String,
+    pub catalog_id: String,
+    pub api_id: String,
+    pub domain: String,
+    pub layer: String,
+    pub systems: Vec<String>,
+    /// number of findings per criterion
+    pub findings: BTreeMap<String, usize>,
+    pub score: f64,
+}
+
+/// mean score of the specs of a domain, system or layer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GroupScorecard {
+    pub name: String,
+    pub score: f64,
+    pub specs: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Weight {
+    pub criterion: String,
+    pub weight: f64,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Scorecards {
+    /// mean score of all the specs
+    pub score: f64,
+    pub weights: Vec<Weight>,
+    pub specs: Vec<SpecScorecard>,
+    pub catalogs: Vec<GroupScorecard>,
+    pub domains: Vec<GroupScorecard>,
+    pub systems: Vec<GroupScorecard>,
+    pub layers: Vec<GroupScorecard>,
+}
+
+#[get("/v1/scorecards")]
+pub async fn get_all_scorecards() -> impl Responder {
+    info!("get all scorecards");
+
+    let all_specs: Vec<SpecItem> = crate::app::dao::catalog::list_specs(&crate::app::catalogs::get_all_catalogs());
+
+    HttpResponse::Ok().json(get_scorecards(&all_specs))
+}
+
+#[derive(Deserialize)]
+pub struct ScorecardTrendsQuery {
+    /// `all` (the default), `spec`, `catalog`, `domain`, `system` or `layer`
+    dimension: Option<String>,
+    /// e.g. a domain, all of them if none
+    name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScorecardTrends {
+    pub dimension: String,
+    /// the daily scores, per name
+    pub points: Vec<(DateTime<Utc>, HashMap<String, f64>)>,
+}
+
+#[get("/v1/scorecards/trends")]
+pub async fn get_scorecard_trends(query: web::Query<ScorecardTrendsQuery>) -> impl Responder {
+    let dimension = query.dimension.as_deref().unwrap_or("all");
+    info!("get scorecard trends for [{:?}]", dimension);
+
+    if !DIMENSIONS.contains(&dimension) {
+        return HttpResponse::BadRequest().body(format!("Unknown dimension [{}], expected one of [{}]", dimension, DIMENSIONS.join(", ")));
+    }
+
+    let points = match crate::app::dao::repo_metrics::get_metrics_scorecards(&SETTINGS.database, dimension) {
+        Ok(val) => val.points,
+        Err(why) => {
+            error!("Error while getting get_metrics_scorecards [{}]", why);
+            Vec::new()
+        }
+    };
+    let points = points.into_iter()
+        .map(|(date_time, scores)| (date_time, scores.into_iter().filter(|(name, _score)| query.name.as_ref().map_or(true, |query_name| query_name == name)).collect()))
+        .collect();
+
+    HttpResponse::Ok().json(ScorecardTrends { dimension: String::from(dimension), points })
+}
+
+/// Computes the scorecards of the specs, along with their parse errors, `x-zally-ignore` and the declared domains.
+/// The files which could not be parsed at all are scored too
+pub fn get_scorecards(all_specs: &[SpecItem]) -> Scorecards {
+    let catalogs = crate::app::catalogs::get_all_catalogs();
+    let errors_per_catalog: Vec<(&Catalog, Vec<SpecInError>)> = catalogs.iter()
+        .map(|catalog| (catalog, crate::app::dao::catalog::list_catalog_errors(catalog)))
+        .collect();
+    let errors: Vec<SpecInError> = errors_per_catalog.iter().flat_map(|(_catalog, errors)| errors.iter().cloned()).collect();
+    let ignores = crate::app::dao::catalog::list_zally_ignores(all_specs);
+    let repo_domains_dao = crate::app::dao::repo_domains::DomainImplFactory::get_impl();
+    let domains: Option<Vec<String>> = match repo_domains_dao.list_all_domains(&SETTINGS.database) {
+        Ok(all_domains) => Some(all_domains.iter().map(|val| String::from(&val.name)).collect()),
+        Err(why) => {
+            warn!("Unable to get domains, they are not scored - [{:?}]", why);
+            None
+        }
+    };
+
+    let mut specs = score_specs(all_specs, &errors, &ignores, domains.as_deref(), &SETTINGS.lint, &SETTINGS.scorecard);
+    for (catalog, errors) in &errors_per_catalog {
+        specs.extend(score_unparsed_files(catalog, errors, all_specs));
+    }
+    Scorecards {
+        score: mean(specs.iter().map(|spec| spec.score)),
+        weights: CRITERIA.iter().map(|criterion| Weight {
+            criterion: String::from(criterion.id),
+            weight: get_weight(criterion, &SETTINGS.scorecard),
+            description: String::from(criterion.description),
+        }).collect(),
+        catalogs: aggregate(&specs, |spec| vec![ String::from(&spec.catalog_id) ]),
+        //unparsed files have neither domain, system nor layer
+        domains: aggregate(&specs, |spec| vec![ String::from(&spec.domain) ].into_iter().filter(|domain| !domain.is_empty()).collect()),
+        systems: aggregate(&specs, |spec| spec.systems.clone()),
+        layers: aggregate(&specs, |spec| vec![ String::from(&spec.layer) ].into_iter().filter(|layer| !layer.is_empty()).collect()),
+        specs,
+    }
+}
+
+/// Saves the scores of the day, per dimension
+pub fn snapshot_scorecards(all_specs: &[SpecItem]) {
+    let scorecards = get_scorecards(all_specs);
+    let today = Utc.from_utc_datetime(&Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap());
+
+    let groups = |groups: &[GroupScorecard]| groups.iter().map(|group| (String::from(&group.name), group.score)).collect::<HashMap<String, f64>>();
+    let snapshots = [
+        ("all", HashMap::from([ (String::from("all"), scorecards.score) ])),
+        ("spec", scorecards.specs.iter().map(|spec| (String::from(&spec.spec_path), spec.score)).collect()),
+        ("catalog", groups(&scorecards.catalogs)),
+        ("domain", groups(&scorecards.domains)),
+        ("system", groups(&scorecards.systems)),
+        ("layer", groups(&scorecards.layers)),
+    ];
+    for (dimension, scores) in snapshots {
+        if let Err(why) = crate::app::dao::repo_metrics::save_metrics_scorecards(&SETTINGS.database, today, dimension, scores) {
+            error!("Unable to save the [{:?}] scorecards - [{}]", dimension, why);
+        }
+    }
+    info!("Saved the scorecards of [{:?}] specs - score [{:?}]", scorecards.specs.len(), scorecards.score);
+}
+
+/// Scores each spec out of `MAX_SCORE`, the weighted findings being deducted. The domains are not checked if `domains` is none
+pub fn score_specs(all_specs: &[SpecItem], errors: &[SpecInError], ignores: &[ZallyIgnore], domains: Option<&[String]>, lint: &Lint, settings: &Scorecard) -> Vec<SpecScorecard> {
+    let mut errors_per_spec: HashMap<(&str, ErrorCategory), usize> = HashMap::new();
+    for error in errors {
+        *errors_per_spec.entry((error.file_path.as_str(), error.category)).or_insert(0) += 1;
+    }
+    let mut ignores_per_spec: HashMap<(&str, &str), usize> = HashMap::new();
+    for ignore in ignores {
+        *ignores_per_spec.entry((ignore.catalog_id.as_str(), ignore.spec_path.as_str())).or_insert(0) += 1;
+    }
+
+    let mut scorecards = Vec::new();
+    for spec in all_specs {
+        //errors are reported on the file path of the specs, refer to dao::catalog::load_catalog
+        let file_path = format!("{:?}", std::path::Path::new(spec.get_file_path()));
+        let api_id = spec.get_api_id();
+        let domain = spec.get_domain();
+
+        let mut findings = BTreeMap::new();
+        findings.insert(String::from("lint-violation"), crate::app::dao::catalog::lint::lint(spec, lint).len()
+            + errors_per_spec.get(&(file_path.as_str(), ErrorCategory::Ruleset)).unwrap_or(&0));
+        findings.insert(String::from("zally-ignore"), *ignores_per_spec.get(&(spec.get_catalog_id(), spec.get_spec_short_path())).unwrap_or(&0));
+        findings.insert(String::from("missing-api-id"), usize::from(api_id.is_empty() || api_id == "0"));
+        findings.insert(String::from("missing-description"), usize::from(spec.get_description().trim().is_empty()));
+        findings.insert(String::from("unknown-domain"), domains.map_or(0, |domains| usize::from(!crate::app::domains::is_declared_domain(&domain, domains))));
+        findings.insert(String::from("parse-error"), *errors_per_spec.get(&(file_path.as_str(), ErrorCategory::Parse)).unwrap_or(&0));
+
+        let penalty: f64 = CRITERIA.iter()
+            .map(|criterion| get_weight(criterion, settings) * *findings.get(criterion.id).unwrap_or(&0) as f64)
+            .sum();
+        debug!("Scored spec [{:?}] - findings [{:?}] penalty [{:?}]", spec.get_file_path(), findings, penalty);
+
+        scorecards.push(SpecScorecard {
+            spec_path: String::from(spec.get_spec_short_path()),
+            catalog_id: String::from(spec.get_catalog_id()),
+            api_id,
+            domain,
+            layer: spec.get_layer(),
+            systems: spec.get_systems(),
+            findings,
+            score: round((MAX_SCORE - penalty).max(0.0)),
+        });
+    }
+    scorecards.sort_by(|a, b| a.spec_path.cmp(&b.spec_path));
+
+    scorecards
+}
+
+/// Files of the catalog which could not be parsed at all (i.e. not a `SpecItem`) score 0, with their parse errors as findings
+pub fn score_unparsed_files(catalog: &Catalog, errors: &[SpecInError], all_specs: &[SpecItem]) -> Vec<SpecScorecard> {
+    let parsed: HashSet<String> = all_specs.iter()
+        .map(|spec| format!("{:?}", std::path::Path::new(spec.get_file_path())))
+        .collect();
+    let mut parse_errors: BTreeMap<&str, usize> = BTreeMap::new();
+    for error in errors.iter().filter(|error| error.category == ErrorCategory::Parse && !parsed.contains(&error.file_path)) {
+        *parse_errors.entry(error.file_path.as_str()).or_insert(0) += 1;
+    }
+
+    parse_errors.into_iter().map(|(file_path, parse_errors)| {
+        //errors are reported on the (debug formatted) file path
+        let file_path = file_path.trim_matches('"');
+        debug!("Scored unparsed file [{:?}] - parse errors [{:?}]", file_path, parse_errors);
+
+        SpecScorecard {
+            spec_path: String::from(file_path.strip_prefix(catalog.catalog_dir.as_str()).unwrap_or(file_path)),
+            catalog_id: String::from(&catalog.catalog_id),
+            api_id: String::new(),
+            domain: String::new(),
+            layer: String::new(),
+            systems: Vec::new(),
+            findings: BTreeMap::from([ (String::from("parse-error"), parse_errors) ]),
+            score: 0.0,
+        }
+    }).collect()
+}
+
+/// mean score per name, `names` giving the domains, systems or layers of a spec
+pub fn aggregate<F>(scorecards: &[SpecScorecard], names: F) -> Vec<GroupScorecard>
+where
+    F: Fn(&SpecScorecard) -> Vec<String>,
+{
+    let mut scores_per_name: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for scorecard in scorecards {
+        for name in names(scorecard) {
+            scores_per_name.entry(name).or_default().push(scorecard.score);
+        }
+    }
+
+    scores_per_name.into_iter()
+        .map(|(name, scores)| GroupScorecard { name, specs: scores.len(), score: mean(scores.into_iter()) })
+        .collect()
+}
+
+fn mean<I: Iterator<Item = f64>>(scores: I) -> f64 {
+    let (sum, count) = scores.fold((0.0, 0), |(sum, count), score| (sum + score, count + 1));
+    match count {
+        0 => MAX_SCORE,
+        _ => round(sum / count as f64),
+    }
+}
+
+fn round(score: f64) -> f64 {
+    (score * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::app::dao::catalog::{ErrorCategory, IgnoreScope, SpecInError, ZallyIgnore};
+    use crate::shared::settings::{Catalog, Lint, Scorecard};
+
+    const COMPLIANT: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  description: the pets
+  version: 1.0.0
+  x-api-id: "42"
+servers:
+  - url: /v1/animals/pets
+x-layer: application
+x-systems: [ zoo ]
+paths:
+  /pets:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    const NOT_COMPLIANT: &str = r#"
+openapi: 3.0.0
+info:
+  title: owners
+  version: 1.0.0
+servers:
+  - url: /v1/people/owners
+x-layer: application
+x-systems: [ zoo, city ]
+paths:
+  /owners:
+    get:
+      responses:
+        '200':
+          description: ok
+"#;
+
+    #[test]
+    fn test_score_specs() {
+        let compliant = crate::app::dao::catalog::spec::from_str(String::from("/specs/pets-openapi.yaml"), String::from("catalog"), String::from("/specs"), COMPLIANT).unwrap();
+        let not_compliant = crate::app::dao::catalog::spec::from_str(String::from("/specs/owners-openapi.yaml"), String::from("catalog"), String::from("/specs"), NOT_COMPLIANT).unwrap();
+        let errors = vec![
+            SpecInError { file_path: format!("{:?}", std::path::Path::new("/specs/owners-openapi.yaml")), reason: String::from("unresolved $ref"), category: ErrorCategory::Parse },
+            SpecInError { file_path: format!("{:?}", std::path::Path::new("/specs/owners-openapi.yaml")), reason: String::from("[error] contact - missing"), category: ErrorCategory::Ruleset },
+            SpecInError { file_path: format!("{:?}", std::path::Path::new("/specs/unparsable.yaml")), reason: String::from("not a spec"), category: ErrorCategory::Parse },
+        ];
+        let ignores = vec![ ZallyIgnore {
+            rule: String::from("M010"),
+            scope: IgnoreScope::Global,
+            spec_path: String::from(not_compliant.get_spec_short_path()),
+            catalog_id: String::from("catalog"),
+            domain: not_compliant.get_domain(),
+            path: None,
+            operation: None,
+        } ];
+        //only the ruleset of the catalog counts
+        let lint = Lint {
+            rules: crate::app::dao::catalog::lint::RULES.iter().map(|rule| (String::from(rule.id), String::from("off"))).collect(),
+            ..Default::default()
+        };
+        let settings = Scorecard { weights: std::collections::HashMap::from([ (String::from("zally-ignore"), 4.0) ]) };
+        let domains = vec![ String::from("/animals") ];
+
+        let scorecards = super::score_specs(&[compliant.clone(), not_compliant.clone()], &errors, &ignores, Some(&domains), &lint, &settings);
+        assert_eq!(scorecards.len(), 2);
+        assert_eq!(scorecards[0].spec_path, not_compliant.get_spec_short_path());
+        assert_eq!(scorecards[0].findings.values().copied().collect::<Vec<usize>>(), vec![ 1, 1, 1, 1, 1, 1 ]);
+        // - (2 + 4 + 10 + 5 + 10 + 20)
+        assert_eq!(scorecards[0].score, 49.0);
+        assert_eq!(scorecards[1].score, super::MAX_SCORE);
+        assert!(scorecards[1].findings.values().all(|findings| *findings == 0));
+
+        //domains are not checked
+        let scorecards = super::score_specs(std::slice::from_ref(&not_compliant), &errors, &ignores, None, &lint, &Scorecard::default());
+        assert_eq!(scorecards[0].findings.get("unknown-domain").unwrap(), &0);
+        assert_eq!(scorecards[0].score, 62.0);
+
+        //the file which is not a spec is scored on its own, the parse error of the spec is not
+        let catalog = Catalog { catalog_id: String::from("catalog"), catalog_dir: String::from("/specs"), ..Default::default() };
+        let unparsed = super::score_unparsed_files(&catalog, &errors, &[compliant, not_compliant]);
+        assert_eq!(unparsed.len(), 1);
+        assert_eq!(unparsed[0].spec_path, "/unparsable.yaml");
+        assert_eq!(unparsed[0].catalog_id, "catalog");
+        assert_eq!(unparsed[0].findings.get("parse-error").unwrap(), &1);
+        assert_eq!(unparsed[0].score, 0.0);
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let scorecard = |domain: &str, systems: Vec<&str>, score: f64| super::SpecScorecard {
+            spec_path: String::from("spec.yaml"),
+            catalog_id: String::from("catalog"),
+            api_id: String::from("42"),
+            domain: String::from(domain),
+            layer: String::from("application"),
+            systems: systems.into_iter().map(String::from).collect(),
+            findings: std::collections::BTreeMap::new(),
+            score,
+        };
+        let scorecards = vec![ scorecard("/v1/animals", vec![ "zoo" ], 100.0), scorecard("/v1/animals", vec![ "zoo", "city" ], 45.0), scorecard("/v1/people", vec![ "city" ], 80.0) ];
+
+        let domains = super::aggregate(&scorecards, |spec| vec![ String::from(&spec.domain) ]);
+        assert_eq!(domains, vec![
+            super::GroupScorecard { name: String::from("/v1/animals"), score: 72.5, specs: 2 },
+            super::GroupScorecard { name: String::from("/v1/people"), score: 80.0, specs: 1 },
+        ]);
+        let systems = super::aggregate(&scorecards, |spec| spec.systems.clone());
+        assert_eq!(systems[0], super::GroupScorecard { name: String::from("city"), score: 62.5, specs: 2 });
+        assert_eq!(systems[1], super::GroupScorecard { name: String::from("zoo"), score: 72.5, specs: 2 });
+        assert_eq!(super::aggregate(&scorecards, |spec| vec![ String::from(&spec.layer) ])[0].score, 75.0);
+    }
+}
@@ -34,10 +34,11 @@ pub async fn search_specs(query: Json<Query>) -> impl Responder{
     let search_results = search(&SETTINGS.search.index_path, String::from(&query.query), query.limit);
     let results = match search_results {
         Ok(results) => {
+            let catalogs = crate::app::catalogs::get_all_catalogs();
             let mut tmp = Vec::new();
             for result in results {
                 let catalog_id = &result.catalog_id[0];
-                let returned_catalog = get_catalog_by_id(&SETTINGS.catalogs, &catalog_id);
+                let returned_catalog = get_catalog_by_id(&catalogs, catalog_id);
 
                 let mut new_spec_path = String::from(&result.spec_path[0]);
                 if let Some(catalog) = returned_catalog{
@@ -85,7 +85,7 @@ pub async fn get_all_domains_per_system_and_layer(path: web::Path<(String, Strin
     let (system, layer) = path.into_inner();
     
 
-    let returned_domains = self::get_domains_per_system_and_layer(&SETTINGS.catalogs, &system, &layer);
+    let returned_domains = self::get_domains_per_system_and_layer(&crate::app::catalogs::get_all_catalogs(), &system, &layer);
     let returned_domain_length = &returned_domains.len();
     let mut returned_domains_as_vec: Vec<Domain> = returned_domains.into_iter().collect();
     returned_domains_as_vec.sort_by( |a, b| {
@@ -276,7 +276,8 @@ mod tests {
             catalog_scm_pull_cmd: String::from("not used here"),
             catalog_path: path.into_os_string().into_string().unwrap(),
             catalog_scm_clone: false,
-            catalog_http_base_uri: String::from("not used here")
+            catalog_http_base_uri: String::from("not used here"),
+            ..Default::default()
         };
         let mut catalogs = Vec::new();
         catalogs.push(catalog);
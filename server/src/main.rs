@@ -90,6 +90,8 @@ async fn main() {
 
     //do this call to warm up internal caches
     crate::app::catalogs::resfresh_caches_and_indexes(true);
+    crate::app::catalogs::watch_catalogs();
+    crate::app::scheduler::start();
 
     /*
      * HTTP Server
@@ -110,6 +112,7 @@ async fn main() {
             .service(app::domains::get_domains_stats)
             .service(app::domains::create_domain)
             .service(app::domains::get_domains_errors)
+            .service(app::domains::get_domains_evolutions)
             .service(web::scope("/v1/domains").service(
                 web::resource("/{id}").route(web::delete().to(app::domains::delete_domain)),
             ))
@@ -144,15 +147,32 @@ async fn main() {
             .service(app::apis::get_oldest_pr)
             .service(app::apis::get_merged_pr)
             .service(app::metrics::refresh_metrics)
+            .service(app::scorecards::get_all_scorecards)
+            .service(app::scorecards::get_scorecard_trends)
             .service(app::systems::get_all_systems)
             .service(app::systems::get_all_domains_per_system_and_layer)
             .service(app::domains::get_all_domains_for_all_catalogs)
             .service(app::catalogs::get_catalog_by_id)
             .service(app::catalogs::get_all_catalog)
             .service(app::catalogs::refresh_all_catalogs)
+            .service(app::catalogs::refresh_catalog)
+            .service(app::catalogs::get_catalog_specs)
+            .service(app::catalogs::get_catalog_diff)
+            .service(app::catalogs::create_catalog)
+            .service(app::catalogs::update_catalog)
+            .service(app::catalogs::delete_catalog)
+            .service(app::scheduler::get_scheduler_status)
+            .service(app::jobs::get_job_by_id)
             .service(app::search::search_specs)
             .service(app::config::get_config_for_ui)
             .service(app::apis::get_all_errors)
+            .service(app::apis::get_all_lints)
+            .service(app::apis::get_all_ignores)
+            .service(app::apis::get_all_conflicts)
+            .service(app::apis::get_security_matrix)
+            .service(app::apis::get_unsecured_operations)
+            .service(app::apis::get_bundled_spec)
+            .service(app::apis::get_spec_history)
             //Static resources mapping
             .service(actix_files::Files::new("/",  &SETTINGS.server.static_resources_path)
                 .redirect_to_slash_directory()
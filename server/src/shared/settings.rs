@@ -45,7 +45,7 @@ pub struct UiConfig {
     pub beta: bool
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct Catalog {
     pub catalog_id: String, 
     pub catalog_name: String,
@@ -56,6 +56,49 @@ pub struct Catalog {
     pub catalog_scm_pull_cmd: String,
     pub catalog_scm_clone: bool,
     pub catalog_http_base_uri: String,
+    /// git repository (cloned and fetched natively) - if not set, `catalog_scm_clone_cmd` and `catalog_scm_pull_cmd` are used
+    #[serde(default)]
+    pub catalog_scm_url: String,
+    /// branch to checkout, the default branch of the repository if empty
+    #[serde(default)]
+    pub catalog_scm_branch: String,
+    /// number of commits to fetch, 0 for the full history
+    #[serde(default)]
+    pub catalog_scm_depth: i32,
+    #[serde(default)]
+    pub catalog_scm_username: String,
+    /// password or (personal access) token
+    #[serde(default)]
+    pub catalog_scm_password: String,
+    /// environment variable holding the password or token, if `catalog_scm_password` is not set -
+    /// the catalogs registered through the API only keep this reference
+    #[serde(default)]
+    pub catalog_scm_password_env: String,
+    /// reload the created, modified and deleted files as soon as they change on disk
+    #[serde(default)]
+    pub catalog_watch: bool,
+    /// `git` (the default), `archive` (zip or tar.gz bundle) or `http_index` (JSON list of spec URLs)
+    #[serde(default)]
+    pub catalog_source_type: String,
+    /// archive (URL or local path) or index URL - fetched into `catalog_path` on each refresh
+    #[serde(default)]
+    pub catalog_source_url: String,
+    /// governance ruleset (YAML), relative to `catalog_path` - evaluated against the specs of the catalog on each refresh
+    #[serde(default)]
+    pub catalog_ruleset_path: String,
+}
+
+/// catalogs registered through the API, refer to `POST /v1/catalogs`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RegisteredCatalogs {
+    /// directory the registered catalogs are cloned (or fetched) into, each one in its own sub-directory -
+    /// catalogs cannot be registered through the API if empty
+    #[serde(default)]
+    pub root_path: String,
+    /// the registered catalogs can only read their password from the environment variables starting with it -
+    /// `scm_password_env` is rejected if empty
+    #[serde(default)]
+    pub password_env_prefix: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -64,9 +107,42 @@ pub struct Search {
 }
 
 
+/// when background jobs run, e.g. `every 15m` or `daily at 02:00` - empty to disable the job
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Scheduler {
+    #[serde(default)]
+    pub catalogs_refresh: String,
+    #[serde(default)]
+    pub metrics_snapshot: String,
+}
+
+/// ruleset used to lint the specs, refer to `dao::catalog::lint::RULES` for the available rules
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Lint {
+    /// severity per rule id (`error`, `warning`, `info` or `hint`), `off` to disable the rule - the default severity otherwise
+    #[serde(default)]
+    pub rules: std::collections::HashMap<String, String>,
+    /// regex the base path of the servers must match, `^/v[0-9]+(/|$)` if empty
+    #[serde(default)]
+    pub base_path_pattern: String,
+    /// `camelCase` (the default) or `snake_case`
+    #[serde(default)]
+    pub property_case: String,
+}
+
+/// weights of the governance scorecard, refer to `app::scorecards::CRITERIA` for the criteria
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Scorecard {
+    /// points a spec loses per finding, per criterion id - the default weight otherwise
+    #[serde(default)]
+    pub weights: std::collections::HashMap<String, f64>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     pub catalogs: Vec<Catalog>,
+    #[serde(default)]
+    pub registered_catalogs: RegisteredCatalogs,
     pub stash_config: StashConfig,
     pub database: Database,
     pub server: Server,
@@ -74,6 +150,12 @@ pub struct Settings {
     pub systems_and_layers: SystemsAndLayers,
     pub ui_config: UiConfig,
     pub search: Search,
+    #[serde(default)]
+    pub scheduler: Scheduler,
+    #[serde(default)]
+    pub lint: Lint,
+    #[serde(default)]
+    pub scorecard: Scorecard,
 }
 
 lazy_static! {